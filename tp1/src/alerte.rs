@@ -0,0 +1,237 @@
+//! Règles d'alerte par compte et destinations des alertes déclenchées.
+
+use crate::{langue, tr};
+use chrono::NaiveDateTime;
+use std::fmt;
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::path::PathBuf;
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy)]
+pub enum RegleAlerte {
+    SoldeBas(f32),
+    RetraitImportant(f32),
+}
+
+impl RegleAlerte {
+    /// Forme stable, indépendante de la langue : `solde_bas:100` ou
+    /// `retrait_important:500`.
+    pub fn code(&self) -> String {
+        match self {
+            RegleAlerte::SoldeBas(seuil) => format!("solde_bas:{}", seuil),
+            RegleAlerte::RetraitImportant(plafond) => format!("retrait_important:{}", plafond),
+        }
+    }
+
+    /// Relit la forme produite par [`RegleAlerte::code`].
+    pub fn depuis_code(code: &str) -> Option<Self> {
+        let (nom, seuil) = code.split_once(':')?;
+        let seuil = seuil
+            .parse::<f32>()
+            .ok()
+            .filter(|seuil| seuil.is_finite())?;
+        match nom {
+            "solde_bas" => Some(RegleAlerte::SoldeBas(seuil)),
+            "retrait_important" => Some(RegleAlerte::RetraitImportant(seuil)),
+            _ => None,
+        }
+    }
+
+    /// Évalue la règle après un mouvement de `montant` (négatif pour un débit)
+    /// ayant laissé le compte à `solde`. Retourne le message d'alerte si elle se déclenche.
+    pub fn evaluer(&self, montant: f32, solde: f32) -> Option<String> {
+        if montant >= 0.0 {
+            return None;
+        }
+        match *self {
+            RegleAlerte::SoldeBas(seuil) if solde < seuil => Some(tr!(
+                "alerte.solde_bas",
+                langue::montant(solde),
+                langue::montant(seuil)
+            )),
+            RegleAlerte::RetraitImportant(plafond) if -montant > plafond => Some(tr!(
+                "alerte.retrait_important",
+                langue::montant(-montant),
+                langue::montant(plafond)
+            )),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for RegleAlerte {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let regle = match self {
+            RegleAlerte::SoldeBas(seuil) => tr!("alerte.regle_solde_bas", langue::montant(*seuil)),
+            RegleAlerte::RetraitImportant(plafond) => {
+                tr!("alerte.regle_retrait_important", langue::montant(*plafond))
+            }
+        };
+        write!(f, "{}", regle)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Alerte {
+    pub date: NaiveDateTime,
+    pub numero: String,
+    pub message: String,
+}
+
+impl fmt::Display for Alerte {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let date = self.date.format("%d/%m/%Y %H:%M:%S");
+        write!(
+            f,
+            "{}",
+            tr!("alerte.ligne", date, self.numero, self.message)
+        )
+    }
+}
+
+/// Destination vers laquelle les alertes déclenchées sont envoyées.
+pub trait SortieAlerte {
+    fn envoyer(&mut self, alerte: &Alerte) -> io::Result<()>;
+
+    /// Forme stable, indépendante de la langue, qui désigne la destination.
+    fn code(&self) -> String;
+}
+
+pub struct SortieConsole;
+
+impl SortieAlerte for SortieConsole {
+    fn envoyer(&mut self, alerte: &Alerte) -> io::Result<()> {
+        println!("⚠ {} {}", tr!("alerte.titre"), alerte);
+        Ok(())
+    }
+
+    fn code(&self) -> String {
+        "console".to_string()
+    }
+}
+
+pub struct SortieFichier {
+    chemin: PathBuf,
+}
+
+impl SortieFichier {
+    pub fn new(chemin: PathBuf) -> Self {
+        SortieFichier { chemin }
+    }
+}
+
+impl SortieAlerte for SortieFichier {
+    fn envoyer(&mut self, alerte: &Alerte) -> io::Result<()> {
+        let mut fichier = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.chemin)?;
+        writeln!(fichier, "{}", alerte)
+    }
+
+    fn code(&self) -> String {
+        format!("fichier:{}", self.chemin.display())
+    }
+}
+
+/// Délai de connexion et d'écriture par défaut d'une [`SortieTcp`].
+pub const DELAI_TCP: Duration = Duration::from_secs(2);
+
+/// Envoie chaque alerte sur une ligne à un collecteur TCP,
+/// par exemple le serveur de journalisation du TP3. Un collecteur injoignable
+/// ou bloqué ne retient l'opération que le temps du délai.
+pub struct SortieTcp {
+    adresse: String,
+    delai: Duration,
+}
+
+impl SortieTcp {
+    pub fn new(adresse: String) -> Self {
+        SortieTcp {
+            adresse,
+            delai: DELAI_TCP,
+        }
+    }
+
+    pub fn avec_delai(mut self, delai: Duration) -> Self {
+        self.delai = delai;
+        self
+    }
+}
+
+impl SortieAlerte for SortieTcp {
+    fn envoyer(&mut self, alerte: &Alerte) -> io::Result<()> {
+        let mut derniere_erreur = None;
+        for adresse in self.adresse.to_socket_addrs()? {
+            match TcpStream::connect_timeout(&adresse, self.delai) {
+                Ok(mut flux) => {
+                    flux.set_read_timeout(Some(self.delai))?;
+                    flux.set_write_timeout(Some(self.delai))?;
+                    return writeln!(flux, "ALERTE {}", alerte);
+                }
+                Err(e) => derniere_erreur = Some(e),
+            }
+        }
+        Err(derniere_erreur
+            .unwrap_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, self.adresse.clone())))
+    }
+
+    fn code(&self) -> String {
+        format!("tcp:{}", self.adresse)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use std::io::{BufRead, BufReader};
+    use std::net::TcpListener;
+
+    fn alerte() -> Alerte {
+        Alerte {
+            date: NaiveDate::from_ymd_opt(2024, 3, 1)
+                .unwrap()
+                .and_hms_opt(9, 0, 0)
+                .unwrap(),
+            numero: "12345".to_string(),
+            message: "test".to_string(),
+        }
+    }
+
+    #[test]
+    fn regles_declenchees_sur_les_debits_seulement() {
+        assert!(RegleAlerte::SoldeBas(100.0).evaluer(-50.0, 80.0).is_some());
+        assert!(RegleAlerte::SoldeBas(100.0).evaluer(50.0, 80.0).is_none());
+        assert!(RegleAlerte::SoldeBas(100.0).evaluer(-50.0, 100.0).is_none());
+        assert!(
+            RegleAlerte::RetraitImportant(500.0)
+                .evaluer(-600.0, 0.0)
+                .is_some()
+        );
+        assert!(
+            RegleAlerte::RetraitImportant(500.0)
+                .evaluer(-500.0, 0.0)
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn sortie_tcp_envoie_une_ligne_et_echoue_sans_collecteur() {
+        let ecoute = TcpListener::bind("127.0.0.1:0").unwrap();
+        let adresse = ecoute.local_addr().unwrap().to_string();
+        let mut sortie = SortieTcp::new(adresse.clone()).avec_delai(Duration::from_millis(500));
+        sortie.envoyer(&alerte()).unwrap();
+        let (flux, _) = ecoute.accept().unwrap();
+        let mut ligne = String::new();
+        BufReader::new(flux).read_line(&mut ligne).unwrap();
+        assert!(ligne.starts_with("ALERTE ") && ligne.contains("12345"));
+
+        drop(ecoute);
+        assert!(sortie.envoyer(&alerte()).is_err());
+        let mut invalide = SortieTcp::new("pas une adresse".to_string());
+        assert!(invalide.envoyer(&alerte()).is_err());
+    }
+}
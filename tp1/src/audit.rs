@@ -0,0 +1,439 @@
+//! Journal d'audit chaîné : chaque entrée contient l'empreinte de la précédente.
+//! Les opérations et les erreurs y sont écrites sous leur forme stable, qui ne
+//! dépend pas de la langue de la session qui les a enregistrées.
+
+use crate::chiffrement::Coffre;
+use crate::erreur::ErreurBancaire;
+use crate::observateur::{Evenement, Observateur};
+use crate::tr;
+use chrono::Local;
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Empreinte de référence de la première entrée du journal.
+pub const EMPREINTE_INITIALE: &str =
+    "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// Une ligne du journal, champs séparés par des tabulations.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EntreeAudit {
+    pub sequence: u64,
+    pub date: String,
+    pub acteur: String,
+    pub resultat: String,
+    pub action: String,
+    pub precedente: String,
+    pub empreinte: String,
+}
+
+impl EntreeAudit {
+    fn calculer_empreinte(&self) -> String {
+        let mut hasher = Sha256::new();
+        for champ in [
+            self.sequence.to_string().as_str(),
+            &self.date,
+            &self.acteur,
+            &self.resultat,
+            &self.action,
+            &self.precedente,
+        ] {
+            hasher.update(champ.as_bytes());
+            hasher.update(b"\t");
+        }
+        format!("{:x}", hasher.finalize())
+    }
+
+    fn depuis_ligne(ligne: &str) -> Option<Self> {
+        let champs: Vec<&str> = ligne.split('\t').collect();
+        let [
+            sequence,
+            date,
+            acteur,
+            resultat,
+            action,
+            precedente,
+            empreinte,
+        ] = champs[..]
+        else {
+            return None;
+        };
+        Some(EntreeAudit {
+            sequence: sequence.parse().ok()?,
+            date: date.to_string(),
+            acteur: acteur.to_string(),
+            resultat: resultat.to_string(),
+            action: action.to_string(),
+            precedente: precedente.to_string(),
+            empreinte: empreinte.to_string(),
+        })
+    }
+}
+
+impl fmt::Display for EntreeAudit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}\t{}\t{}\t{}\t{}\t{}\t{}",
+            self.sequence,
+            self.date,
+            self.acteur,
+            self.resultat,
+            self.action,
+            self.precedente,
+            self.empreinte
+        )
+    }
+}
+
+// Les tabulations et retours à la ligne sont réservés au format du journal.
+fn nettoyer(texte: &str) -> String {
+    texte.replace(['\t', '\n', '\r'], " ")
+}
+
+/// Fichier « tête » qui mémorise la dernière entrée, pour détecter
+/// la suppression des entrées en fin de journal. Il est écrit avec le même
+/// coffre que le journal.
+pub fn chemin_tete(journal: &Path) -> PathBuf {
+    let mut nom = journal.as_os_str().to_owned();
+    nom.push(".tete");
+    PathBuf::from(nom)
+}
+
+/// Observateur qui ajoute au journal chaque opération, réussie ou non,
+/// avec l'acteur courant. Le journal n'est jamais réécrit, seulement complété ;
+/// avec un coffre chiffré, chaque entrée est chiffrée séparément.
+pub struct JournalAudit {
+    chemin: PathBuf,
+    coffre: Coffre,
+    acteur: String,
+    sequence: u64,
+    derniere_empreinte: String,
+}
+
+impl JournalAudit {
+    /// Ouvre le journal en reprenant la chaîne là où elle s'est arrêtée.
+    pub fn ouvrir(chemin: PathBuf, acteur: String, coffre: Coffre) -> io::Result<Self> {
+        let mut journal = JournalAudit {
+            chemin,
+            coffre,
+            acteur: nettoyer(&acteur),
+            sequence: 0,
+            derniere_empreinte: EMPREINTE_INITIALE.to_string(),
+        };
+        if let Some(derniere) = lire_entrees(&journal.chemin, &journal.coffre)?
+            .into_iter()
+            .flatten()
+            .last()
+        {
+            journal.sequence = derniere.sequence;
+            journal.derniere_empreinte = derniere.empreinte;
+        }
+        Ok(journal)
+    }
+
+    pub fn acteur(&self) -> &str {
+        &self.acteur
+    }
+
+    fn ajouter(&mut self, resultat: String, action: String) -> io::Result<()> {
+        let mut entree = EntreeAudit {
+            sequence: self.sequence + 1,
+            date: Local::now().to_rfc3339(),
+            acteur: self.acteur.clone(),
+            resultat: nettoyer(&resultat),
+            action: nettoyer(&action),
+            precedente: self.derniere_empreinte.clone(),
+            empreinte: String::new(),
+        };
+        entree.empreinte = entree.calculer_empreinte();
+
+        self.coffre
+            .ajouter_ligne(&self.chemin, &entree.to_string())?;
+        self.coffre.ecrire(
+            &chemin_tete(&self.chemin),
+            &format!("{}\t{}\n", entree.sequence, entree.empreinte),
+        )?;
+
+        self.sequence = entree.sequence;
+        self.derniere_empreinte = entree.empreinte;
+        Ok(())
+    }
+}
+
+impl Observateur for JournalAudit {
+    fn apres(&mut self, evenement: &Evenement, resultat: Result<(), &ErreurBancaire>) {
+        let reussie = resultat.is_ok();
+        let resultat = match resultat {
+            Ok(()) => "OK".to_string(),
+            Err(e) => format!("ECHEC:{}", e.code()),
+        };
+        if let Err(e) = self.ajouter(resultat, evenement.forme_canonique()) {
+            eprintln!(
+                "{}",
+                tr!("audit.ecriture_impossible", self.chemin.display(), e)
+            );
+        }
+        if let Evenement::ChangementActeur { acteur }
+        | Evenement::Connexion {
+            identifiant: acteur,
+        } = evenement
+            && reussie
+        {
+            self.acteur = nettoyer(acteur);
+        }
+    }
+}
+
+/// Lignes du journal dans l'ordre ; `None` pour une ligne illisible.
+fn lire_entrees(chemin: &Path, coffre: &Coffre) -> io::Result<Vec<Option<EntreeAudit>>> {
+    let contenu = match coffre.lire(chemin) {
+        Ok(contenu) => contenu,
+        Err(e) if e.introuvable() => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+    Ok(contenu.lines().map(EntreeAudit::depuis_ligne).collect())
+}
+
+/// Anomalie détectée lors de la vérification du journal.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Anomalie {
+    LigneIllisible(usize),
+    SequenceRompue {
+        ligne: usize,
+        attendue: u64,
+        trouvee: u64,
+    },
+    ChainageRompu(usize),
+    EntreeModifiee(usize),
+    FinSupprimee {
+        attendue: u64,
+        trouvee: u64,
+    },
+    TeteIllisible,
+}
+
+impl fmt::Display for Anomalie {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let message = match self {
+            Anomalie::LigneIllisible(ligne) => tr!("audit.ligne_illisible", ligne),
+            Anomalie::SequenceRompue {
+                ligne,
+                attendue,
+                trouvee,
+            } => tr!("audit.sequence_rompue", ligne, attendue, trouvee),
+            Anomalie::ChainageRompu(ligne) => tr!("audit.chainage_rompu", ligne),
+            Anomalie::EntreeModifiee(ligne) => tr!("audit.entree_modifiee", ligne),
+            Anomalie::FinSupprimee { attendue, trouvee } => {
+                tr!("audit.fin_supprimee", attendue, trouvee)
+            }
+            Anomalie::TeteIllisible => tr!("audit.tete_illisible"),
+        };
+        write!(f, "{}", message)
+    }
+}
+
+fn lire_tete(tete: &str) -> Option<(u64, String)> {
+    let (sequence, empreinte) = tete.trim().split_once('\t')?;
+    Some((sequence.parse().ok()?, empreinte.to_string()))
+}
+
+/// Relit tout le journal et retourne le nombre d'entrées et les anomalies trouvées.
+pub fn verifier(chemin: &Path, coffre: &Coffre) -> io::Result<(usize, Vec<Anomalie>)> {
+    let entrees = lire_entrees(chemin, coffre)?;
+    let mut anomalies = Vec::new();
+    let mut sequence = 0;
+    let mut precedente = EMPREINTE_INITIALE.to_string();
+
+    for (i, entree) in entrees.iter().enumerate() {
+        let ligne = i + 1;
+        let Some(entree) = entree else {
+            anomalies.push(Anomalie::LigneIllisible(ligne));
+            continue;
+        };
+        if entree.sequence != sequence + 1 {
+            anomalies.push(Anomalie::SequenceRompue {
+                ligne,
+                attendue: sequence + 1,
+                trouvee: entree.sequence,
+            });
+        }
+        if entree.precedente != precedente {
+            anomalies.push(Anomalie::ChainageRompu(ligne));
+        }
+        if entree.calculer_empreinte() != entree.empreinte {
+            anomalies.push(Anomalie::EntreeModifiee(ligne));
+        }
+        sequence = entree.sequence;
+        precedente = entree.empreinte.clone();
+    }
+
+    // Sans tête lisible, rien ne prouve que la fin du journal est intacte.
+    let tete = match coffre.lire(&chemin_tete(chemin)) {
+        Ok(tete) => lire_tete(&tete),
+        Err(e) if e.introuvable() && entrees.is_empty() => return Ok((0, anomalies)),
+        Err(_) => None,
+    };
+    match tete {
+        Some((attendue, empreinte)) if attendue != sequence || empreinte != precedente => {
+            anomalies.push(Anomalie::FinSupprimee {
+                attendue,
+                trouvee: sequence,
+            });
+        }
+        Some(_) => {}
+        None => anomalies.push(Anomalie::TeteIllisible),
+    }
+
+    Ok((entrees.len(), anomalies))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn journal_temporaire(nom: &str) -> PathBuf {
+        let chemin =
+            std::env::temp_dir().join(format!("tp1_audit_{}_{}.log", nom, std::process::id()));
+        let _ = fs::remove_file(&chemin);
+        let _ = fs::remove_file(chemin_tete(&chemin));
+        chemin
+    }
+
+    fn remplir(chemin: &Path, coffre: &Coffre) {
+        let mut journal =
+            JournalAudit::ouvrir(chemin.to_path_buf(), "alice".to_string(), coffre.clone())
+                .unwrap();
+        let depot = Evenement::Depot {
+            numero: "12345".to_string(),
+            montant: 10.0,
+            libelle: "Dépôt".to_string(),
+        };
+        journal.apres(&depot, Ok(()));
+        journal.apres(&depot, Err(&ErreurBancaire::SoldeInsuffisant));
+        journal.apres(
+            &Evenement::ChangementActeur {
+                acteur: "bob".to_string(),
+            },
+            Ok(()),
+        );
+        journal.apres(&depot, Ok(()));
+    }
+
+    #[test]
+    fn journal_intact_et_repris_apres_reouverture() {
+        let chemin = journal_temporaire("intact");
+        let clair = Coffre::en_clair();
+        remplir(&chemin, &clair);
+        remplir(&chemin, &clair);
+
+        assert_eq!(verifier(&chemin, &clair).unwrap(), (8, Vec::new()));
+        let entrees: Vec<EntreeAudit> = lire_entrees(&chemin, &clair)
+            .unwrap()
+            .into_iter()
+            .flatten()
+            .collect();
+        assert_eq!(entrees[1].resultat, "ECHEC:solde_insuffisant");
+        assert_eq!(entrees[1].action, "depot;5:12345;2:10;7:Dépôt");
+        assert_eq!(entrees[2].acteur, "alice");
+        assert_eq!(entrees[3].acteur, "bob");
+        assert_eq!(entrees[4].precedente, entrees[3].empreinte);
+    }
+
+    #[test]
+    fn journal_chiffre() {
+        let chemin = journal_temporaire("chiffre");
+        let coffre = Coffre::avec_phrase("secret").avec_iterations(1000);
+        remplir(&chemin, &coffre);
+        remplir(&chemin, &coffre);
+
+        assert!(!fs::read_to_string(&chemin).unwrap().contains("alice"));
+        assert_eq!(verifier(&chemin, &coffre).unwrap(), (8, Vec::new()));
+        let erreur = verifier(&chemin, &Coffre::en_clair()).unwrap_err();
+        assert_eq!(erreur.kind(), io::ErrorKind::InvalidData);
+
+        // Une tête en clair forgée après troncature n'est pas acceptée.
+        let tete = chemin_tete(&chemin);
+        assert!(crate::chiffrement::est_chiffre(&tete).unwrap());
+        fs::write(&tete, "8\tforgee\n").unwrap();
+        assert_eq!(
+            verifier(&chemin, &coffre).unwrap().1,
+            [Anomalie::TeteIllisible]
+        );
+    }
+
+    #[test]
+    fn detecte_modification_et_suppression() {
+        let chemin = journal_temporaire("altere");
+        let clair = Coffre::en_clair();
+        remplir(&chemin, &clair);
+        let lignes: Vec<String> = fs::read_to_string(&chemin)
+            .unwrap()
+            .lines()
+            .map(String::from)
+            .collect();
+
+        let modifie = lignes[0].replace(";2:10;", ";2:99;");
+        fs::write(
+            &chemin,
+            [modifie.as_str(), &lignes[1], &lignes[2], &lignes[3], ""].join("\n"),
+        )
+        .unwrap();
+        assert_eq!(
+            verifier(&chemin, &clair).unwrap().1,
+            [Anomalie::EntreeModifiee(1)]
+        );
+
+        fs::write(
+            &chemin,
+            [lignes[0].as_str(), &lignes[2], &lignes[3], ""].join("\n"),
+        )
+        .unwrap();
+        let (_, anomalies) = verifier(&chemin, &clair).unwrap();
+        assert!(anomalies.contains(&Anomalie::ChainageRompu(2)));
+
+        fs::write(&chemin, [lignes[0].as_str(), &lignes[1], ""].join("\n")).unwrap();
+        assert_eq!(
+            verifier(&chemin, &clair).unwrap().1,
+            [Anomalie::FinSupprimee {
+                attendue: 4,
+                trouvee: 2
+            }]
+        );
+        fs::write(&chemin, "").unwrap();
+        assert_eq!(
+            verifier(&chemin, &clair).unwrap().1,
+            [Anomalie::FinSupprimee {
+                attendue: 4,
+                trouvee: 0
+            }]
+        );
+    }
+
+    #[test]
+    fn troncature_avec_tete_supprimee_ou_alteree() {
+        let chemin = journal_temporaire("tete");
+        let clair = Coffre::en_clair();
+        assert_eq!(verifier(&chemin, &clair).unwrap(), (0, Vec::new()));
+        remplir(&chemin, &clair);
+        let lignes: Vec<String> = fs::read_to_string(&chemin)
+            .unwrap()
+            .lines()
+            .map(String::from)
+            .collect();
+
+        fs::write(&chemin, [lignes[0].as_str(), &lignes[1], ""].join("\n")).unwrap();
+        fs::remove_file(chemin_tete(&chemin)).unwrap();
+        assert_eq!(
+            verifier(&chemin, &clair).unwrap().1,
+            [Anomalie::TeteIllisible]
+        );
+        fs::write(chemin_tete(&chemin), "illisible\n").unwrap();
+        assert_eq!(
+            verifier(&chemin, &clair).unwrap().1,
+            [Anomalie::TeteIllisible]
+        );
+    }
+}
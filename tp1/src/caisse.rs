@@ -0,0 +1,187 @@
+//! Caisse du guichet : fond de caisse, dépôts et retraits en espèces de la
+//! journée, et clôture qui compare les espèces attendues aux espèces comptées.
+
+use crate::{langue, tr};
+use chrono::NaiveDateTime;
+use std::io::{self, Write};
+
+/// Dépôt (montant positif) ou retrait (négatif) en espèces passé au guichet.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MouvementCaisse {
+    pub date: NaiveDateTime,
+    pub guichetier: String,
+    pub numero: String,
+    pub montant: f32,
+}
+
+/// Caisse ouverte par un guichetier avec son fond de caisse.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SessionCaisse {
+    pub guichetier: String,
+    pub ouverte_le: NaiveDateTime,
+    pub fond: f32,
+    pub mouvements: Vec<MouvementCaisse>,
+}
+
+impl SessionCaisse {
+    pub fn new(guichetier: String, fond: f32, ouverte_le: NaiveDateTime) -> Self {
+        SessionCaisse {
+            guichetier,
+            ouverte_le,
+            fond,
+            mouvements: Vec::new(),
+        }
+    }
+
+    pub fn total_depots(&self) -> f32 {
+        self.mouvements
+            .iter()
+            .filter(|m| m.montant > 0.0)
+            .map(|m| m.montant)
+            .sum()
+    }
+
+    /// Total des retraits, en valeur positive.
+    pub fn total_retraits(&self) -> f32 {
+        self.mouvements
+            .iter()
+            .filter(|m| m.montant < 0.0)
+            .map(|m| -m.montant)
+            .sum()
+    }
+
+    /// Espèces qui devraient se trouver dans la caisse.
+    pub fn especes_attendues(&self) -> f32 {
+        self.fond + self.total_depots() - self.total_retraits()
+    }
+
+    pub fn afficher(&self, sortie: &mut impl Write) -> io::Result<()> {
+        writeln!(
+            sortie,
+            "{}",
+            tr!(
+                "caisse.ouverte_par",
+                self.guichetier,
+                self.ouverte_le.format("%d/%m/%Y %H:%M")
+            )
+        )?;
+        for mouvement in &self.mouvements {
+            writeln!(
+                sortie,
+                "  {} | {:<12} | {:<8} | {:>11}",
+                mouvement.date.format("%H:%M"),
+                mouvement.guichetier,
+                mouvement.numero,
+                langue::montant(mouvement.montant)
+            )?;
+        }
+        let nombre = |depot: bool| {
+            self.mouvements
+                .iter()
+                .filter(|m| (m.montant > 0.0) == depot)
+                .count()
+        };
+        writeln!(sortie, "{}", tr!("caisse.fond", langue::montant(self.fond)))?;
+        writeln!(
+            sortie,
+            "{}",
+            tr!(
+                "caisse.depots",
+                nombre(true),
+                langue::montant(self.total_depots())
+            )
+        )?;
+        writeln!(
+            sortie,
+            "{}",
+            tr!(
+                "caisse.retraits",
+                nombre(false),
+                langue::montant(self.total_retraits())
+            )
+        )?;
+        writeln!(
+            sortie,
+            "{}",
+            tr!(
+                "caisse.attendues",
+                langue::montant(self.especes_attendues())
+            )
+        )
+    }
+}
+
+/// Clôture d'une caisse en fin de journée.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RapportCloture {
+    pub session: SessionCaisse,
+    pub cloturee_le: NaiveDateTime,
+    pub especes_comptees: f32,
+}
+
+impl RapportCloture {
+    /// Espèces comptées moins espèces attendues : positif s'il y a trop
+    /// d'espèces dans la caisse, négatif s'il en manque.
+    pub fn ecart(&self) -> f32 {
+        self.especes_comptees - self.session.especes_attendues()
+    }
+
+    pub fn afficher(&self, sortie: &mut impl Write) -> io::Result<()> {
+        writeln!(
+            sortie,
+            "=== {} ===",
+            tr!(
+                "caisse.cloture_du",
+                self.cloturee_le.format("%d/%m/%Y %H:%M")
+            )
+        )?;
+        self.session.afficher(sortie)?;
+        writeln!(
+            sortie,
+            "{}",
+            tr!("caisse.comptees", langue::montant(self.especes_comptees))
+        )?;
+        // Arrondi au centime : les montants f32 ne tombent pas toujours juste.
+        let ecart = (self.ecart() * 100.0).round() / 100.0;
+        if ecart == 0.0 {
+            writeln!(sortie, "{}", tr!("caisse.juste"))
+        } else {
+            writeln!(sortie, "{}", tr!("caisse.ecart", langue::montant(ecart)))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Local;
+
+    #[test]
+    fn especes_attendues_et_ecart() {
+        let maintenant = Local::now().naive_local();
+        let mut session = SessionCaisse::new("alice".to_string(), 500.0, maintenant);
+        for (numero, montant) in [("12345", 200.0), ("67890", -120.0), ("12345", -30.0)] {
+            session.mouvements.push(MouvementCaisse {
+                date: maintenant,
+                guichetier: "alice".to_string(),
+                numero: numero.to_string(),
+                montant,
+            });
+        }
+        assert_eq!(session.total_depots(), 200.0);
+        assert_eq!(session.total_retraits(), 150.0);
+        assert_eq!(session.especes_attendues(), 550.0);
+
+        let rapport = RapportCloture {
+            session,
+            cloturee_le: maintenant,
+            especes_comptees: 540.0,
+        };
+        assert_eq!(rapport.ecart(), -10.0);
+        let mut sortie = Vec::new();
+        rapport.afficher(&mut sortie).unwrap();
+        let sortie = String::from_utf8(sortie).unwrap();
+        assert!(sortie.contains("Espèces attendues: 550,00€"));
+        assert!(sortie.contains("Écart: -10,00€"));
+    }
+}
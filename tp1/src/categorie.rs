@@ -0,0 +1,71 @@
+//! Catégorisation automatique des transactions par mots-clés.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+#[derive(Debug, Clone)]
+pub struct RegleCategorie {
+    pub mot_cle: String,
+    pub categorie: String,
+}
+
+/// Charge les règles depuis un fichier texte au format `mot_cle=categorie`,
+/// une règle par ligne. Les lignes vides et celles commençant par `#` sont ignorées.
+pub fn charger_regles(chemin: &Path) -> io::Result<Vec<RegleCategorie>> {
+    let contenu = fs::read_to_string(chemin)?;
+    let mut regles = Vec::new();
+
+    for ligne in contenu.lines() {
+        let ligne = ligne.trim();
+        if ligne.is_empty() || ligne.starts_with('#') {
+            continue;
+        }
+        if let Some((mot_cle, categorie)) = ligne.split_once('=') {
+            let mot_cle = mot_cle.trim().to_lowercase();
+            let categorie = categorie.trim().to_string();
+            if !mot_cle.is_empty() && !categorie.is_empty() {
+                regles.push(RegleCategorie { mot_cle, categorie });
+            }
+        }
+    }
+
+    Ok(regles)
+}
+
+/// Retourne la catégorie de la première règle dont le mot-clé apparaît dans le libellé.
+pub fn categoriser(regles: &[RegleCategorie], libelle: &str) -> Option<String> {
+    let libelle = libelle.to_lowercase();
+    regles
+        .iter()
+        .find(|regle| libelle.contains(&regle.mot_cle))
+        .map(|regle| regle.categorie.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn charger_et_appliquer_les_regles() {
+        let chemin = std::env::temp_dir().join(format!("tp1-regles-{}.txt", std::process::id()));
+        fs::write(
+            &chemin,
+            "# commentaire\nLoyer = Logement\n\nsans_egal\ncarrefour=Alimentation\n",
+        )
+        .unwrap();
+        let regles = charger_regles(&chemin).unwrap();
+        fs::remove_file(&chemin).unwrap();
+
+        assert_eq!(regles.len(), 2);
+        assert_eq!(
+            categoriser(&regles, "LOYER octobre").as_deref(),
+            Some("Logement")
+        );
+        assert_eq!(
+            categoriser(&regles, "Carrefour Market").as_deref(),
+            Some("Alimentation")
+        );
+        assert_eq!(categoriser(&regles, "Retrait"), None);
+    }
+}
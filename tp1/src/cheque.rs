@@ -0,0 +1,128 @@
+//! Remises de chèques : le montant reste en attente d'encaissement pendant
+//! quelques jours ouvrés avant d'être crédité, et un chèque peut être rejeté.
+
+use crate::{langue, tr};
+use chrono::{Datelike, Days, NaiveDate, Weekday};
+use std::fmt;
+
+/// Jours ouvrés entre la remise d'un chèque et son encaissement, par défaut.
+pub const DELAI_ENCAISSEMENT_PAR_DEFAUT: u32 = 3;
+/// Frais prélevés au rejet d'un chèque, par défaut.
+pub const FRAIS_REJET_PAR_DEFAUT: f32 = 30.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EtatCheque {
+    /// Remis, pas encore crédité.
+    EnAttente,
+    Encaisse,
+    Rejete,
+}
+
+impl EtatCheque {
+    /// Code stable, indépendant de la langue.
+    pub fn code(&self) -> &'static str {
+        match self {
+            EtatCheque::EnAttente => "en_attente",
+            EtatCheque::Encaisse => "encaisse",
+            EtatCheque::Rejete => "rejete",
+        }
+    }
+
+    pub fn depuis_code(code: &str) -> Option<Self> {
+        [
+            EtatCheque::EnAttente,
+            EtatCheque::Encaisse,
+            EtatCheque::Rejete,
+        ]
+        .into_iter()
+        .find(|etat| etat.code() == code)
+    }
+
+    pub fn libelle(&self) -> &'static str {
+        match self {
+            EtatCheque::EnAttente => langue::texte("cheque.en_attente"),
+            EtatCheque::Encaisse => langue::texte("cheque.encaisse"),
+            EtatCheque::Rejete => langue::texte("cheque.rejete"),
+        }
+    }
+}
+
+/// Chèque remis sur un compte courant.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cheque {
+    pub compte: String,
+    /// Numéro inscrit sur le chèque.
+    pub numero: String,
+    pub emetteur: String,
+    pub montant: f32,
+    pub remis_le: NaiveDate,
+    /// Date à partir de laquelle le chèque peut être encaissé.
+    pub disponible_le: NaiveDate,
+    pub etat: EtatCheque,
+}
+
+impl fmt::Display for Cheque {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            tr!(
+                "cheque.description",
+                self.numero,
+                self.emetteur,
+                langue::montant(self.montant),
+                self.remis_le.format("%d/%m/%Y"),
+                self.disponible_le.format("%d/%m/%Y"),
+                self.etat.libelle()
+            )
+        )
+    }
+}
+
+/// Date située `jours` jours ouvrés après `date`, en sautant les samedis et
+/// dimanches. Les jours fériés ne sont pas pris en compte.
+pub fn jours_ouvres_apres(date: NaiveDate, jours: u32) -> NaiveDate {
+    let mut resultat = date;
+    let mut restants = jours;
+    while restants > 0 {
+        resultat = resultat + Days::new(1);
+        if !matches!(resultat.weekday(), Weekday::Sat | Weekday::Sun) {
+            restants -= 1;
+        }
+    }
+    resultat
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(jour: u32, mois: u32, annee: i32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(annee, mois, jour).unwrap()
+    }
+
+    #[test]
+    fn delai_en_jours_ouvres() {
+        // Le 16/10/2026 est un vendredi.
+        assert_eq!(
+            jours_ouvres_apres(date(16, 10, 2026), 1),
+            date(19, 10, 2026)
+        );
+        assert_eq!(
+            jours_ouvres_apres(date(16, 10, 2026), 3),
+            date(21, 10, 2026)
+        );
+        assert_eq!(
+            jours_ouvres_apres(date(17, 10, 2026), 1),
+            date(19, 10, 2026)
+        );
+        assert_eq!(
+            jours_ouvres_apres(date(19, 10, 2026), 5),
+            date(26, 10, 2026)
+        );
+        assert_eq!(
+            jours_ouvres_apres(date(19, 10, 2026), 0),
+            date(19, 10, 2026)
+        );
+    }
+}
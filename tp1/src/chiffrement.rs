@@ -0,0 +1,683 @@
+//! Chiffrement authentifié des fichiers de données et des journaux, avec une
+//! clé dérivée d'une phrase de passe (PBKDF2-HMAC-SHA256, XChaCha20-Poly1305).
+//!
+//! Un fichier chiffré commence par une ligne d'en-tête suivie de blocs chiffrés,
+//! un par ligne, en hexadécimal, et se termine par une ligne de fin chiffrée
+//! qui donne le nombre de blocs :
+//!
+//! ```text
+//! tp1-chiffre;2;<itérations>;<sel>;<vérificateur>;<identifiant>
+//! <nonce><texte chiffré et étiquette>
+//! <nonce><nombre de blocs chiffré et étiquette>
+//! ```
+//!
+//! Le vérificateur, tiré de la clé, distingue une phrase de passe incorrecte
+//! d'un fichier altéré. Un journal reçoit un bloc par ligne ajoutée, qui remplace
+//! la ligne de fin ; un fichier réécrit d'un coup tient en un seul bloc.
+//!
+//! Chaque bloc est authentifié avec l'en-tête, qui porte un identifiant propre
+//! au fichier, et son rang ; la ligne de fin avec l'en-tête : un bloc retiré,
+//! déplacé, dupliqué ou venu d'un autre fichier, et un fichier tronqué, sont
+//! signalés comme corrompus. Les fichiers de la version
+//! 1, sans rang ni ligne de fin, sont encore lus et passent à la version 2 à la
+//! première ligne ajoutée.
+
+use crate::tr;
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use sha2::{Digest, Sha256};
+use std::cell::RefCell;
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+const MARQUE: &str = "tp1-chiffre";
+const VERSION: u32 = 2;
+/// Version dont les blocs ne sont liés ni à leur rang ni à l'en-tête.
+const VERSION_SANS_RANG: u32 = 1;
+const TAILLE_SEL: usize = 16;
+const TAILLE_NONCE: usize = 24;
+const TAILLE_ETIQUETTE: usize = 16;
+const TAILLE_IDENTIFIANT: usize = 16;
+/// Longueur de la ligne de fin, saut de ligne compris : le nombre de blocs y
+/// est chiffré sur 8 octets.
+const LONGUEUR_FIN: usize = 2 * (TAILLE_NONCE + 8 + TAILLE_ETIQUETTE) + 1;
+
+/// Nombre d'itérations de la dérivation de clé pour les nouveaux fichiers.
+pub const ITERATIONS_PAR_DEFAUT: u32 = 600_000;
+
+/// Erreur de lecture ou d'écriture d'un fichier chiffré.
+#[derive(Debug)]
+pub enum ErreurChiffrement {
+    /// Le fichier est chiffré mais aucune phrase de passe n'a été donnée.
+    PhraseRequise(PathBuf),
+    PhraseIncorrecte(PathBuf),
+    /// Une phrase de passe a été donnée mais le fichier est en clair.
+    NonChiffre(PathBuf),
+    /// En-tête ou bloc illisible, ou bloc dont l'authentification échoue.
+    FichierCorrompu {
+        chemin: PathBuf,
+        ligne: usize,
+    },
+    /// Le chiffrement du contenu à écrire a échoué ; le fichier est inchangé.
+    ChiffrementImpossible(PathBuf),
+    Io(io::Error),
+}
+
+impl fmt::Display for ErreurChiffrement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let message = match self {
+            ErreurChiffrement::PhraseRequise(chemin) => {
+                tr!("chiffrement.phrase_requise", chemin.display())
+            }
+            ErreurChiffrement::PhraseIncorrecte(chemin) => {
+                tr!("chiffrement.phrase_incorrecte", chemin.display())
+            }
+            ErreurChiffrement::NonChiffre(chemin) => {
+                tr!("chiffrement.non_chiffre", chemin.display())
+            }
+            ErreurChiffrement::FichierCorrompu { chemin, ligne } => {
+                tr!("chiffrement.fichier_corrompu", chemin.display(), ligne)
+            }
+            ErreurChiffrement::ChiffrementImpossible(chemin) => {
+                tr!("chiffrement.chiffrement_impossible", chemin.display())
+            }
+            ErreurChiffrement::Io(e) => e.to_string(),
+        };
+        write!(f, "{}", message)
+    }
+}
+
+impl std::error::Error for ErreurChiffrement {}
+
+impl From<io::Error> for ErreurChiffrement {
+    fn from(e: io::Error) -> Self {
+        ErreurChiffrement::Io(e)
+    }
+}
+
+impl From<ErreurChiffrement> for io::Error {
+    fn from(e: ErreurChiffrement) -> Self {
+        match e {
+            ErreurChiffrement::Io(e) => e,
+            autre => io::Error::new(io::ErrorKind::InvalidData, autre),
+        }
+    }
+}
+
+impl ErreurChiffrement {
+    /// Le fichier n'existe pas.
+    pub fn introuvable(&self) -> bool {
+        matches!(self, ErreurChiffrement::Io(e) if e.kind() == io::ErrorKind::NotFound)
+    }
+}
+
+pub(crate) fn hex(octets: &[u8]) -> String {
+    octets.iter().map(|o| format!("{:02x}", o)).collect()
+}
+
+pub(crate) fn depuis_hex(texte: &str) -> Option<Vec<u8>> {
+    if !texte.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..texte.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(texte.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+#[derive(Clone)]
+struct CleDerivee {
+    sel: Vec<u8>,
+    iterations: u32,
+    cle: Key,
+}
+
+impl CleDerivee {
+    fn verificateur(&self) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(b"tp1-verification");
+        hasher.update(self.cle);
+        hex(&hasher.finalize()[..16])
+    }
+
+    // En-tête d'un nouveau fichier. Son identifiant, tiré au hasard, lie les
+    // blocs au fichier : deux fichiers d'un même coffre partagent la clé.
+    fn entete(&self) -> String {
+        let mut identifiant = [0; TAILLE_IDENTIFIANT];
+        OsRng.fill_bytes(&mut identifiant);
+        format!(
+            "{};{};{};{};{};{}",
+            MARQUE,
+            VERSION,
+            self.iterations,
+            hex(&self.sel),
+            self.verificateur(),
+            hex(&identifiant)
+        )
+    }
+
+    fn chiffrer(&self, texte: &[u8], aad: &[u8]) -> Option<String> {
+        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let chiffre = XChaCha20Poly1305::new(&self.cle)
+            .encrypt(&nonce, Payload { msg: texte, aad })
+            .ok()?;
+        Some(format!("{}{}", hex(&nonce), hex(&chiffre)))
+    }
+
+    fn dechiffrer(&self, bloc: &str, aad: &[u8]) -> Option<Vec<u8>> {
+        let octets = depuis_hex(bloc)?;
+        if octets.len() < TAILLE_NONCE {
+            return None;
+        }
+        let (nonce, chiffre) = octets.split_at(TAILLE_NONCE);
+        XChaCha20Poly1305::new(&self.cle)
+            .decrypt(XNonce::from_slice(nonce), Payload { msg: chiffre, aad })
+            .ok()
+    }
+
+    // Bloc de rang `rang` (à partir de 0) du fichier d'en-tête `entete`.
+    fn bloc(&self, entete: &str, rang: u64, texte: &str) -> Option<String> {
+        self.chiffrer(texte.as_bytes(), format!("{}\n{}", entete, rang).as_bytes())
+    }
+
+    fn lire_bloc(&self, entete: &str, rang: u64, bloc: &str) -> Option<String> {
+        let texte = self.dechiffrer(bloc, format!("{}\n{}", entete, rang).as_bytes())?;
+        String::from_utf8(texte).ok()
+    }
+
+    // Ligne de fin d'un fichier de `nombre` blocs.
+    fn fin(&self, entete: &str, nombre: u64) -> Option<String> {
+        self.chiffrer(&nombre.to_be_bytes(), format!("{}\nfin", entete).as_bytes())
+    }
+
+    fn lire_fin(&self, entete: &str, ligne: &str) -> Option<u64> {
+        let nombre = self.dechiffrer(ligne, format!("{}\nfin", entete).as_bytes())?;
+        Some(u64::from_be_bytes(nombre.try_into().ok()?))
+    }
+
+    // Contenu complet d'un fichier chiffré d'un seul bloc.
+    fn fichier(&self, contenu: &str) -> Option<String> {
+        let entete = self.entete();
+        Some(format!(
+            "{}\n{}\n{}\n",
+            entete,
+            self.bloc(&entete, 0, contenu)?,
+            self.fin(&entete, 1)?
+        ))
+    }
+}
+
+/// Première ligne du fichier, `None` s'il est absent ou vide.
+fn premiere_ligne(chemin: &Path) -> io::Result<Option<String>> {
+    let fichier = match File::open(chemin) {
+        Ok(fichier) => fichier,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e),
+    };
+    let mut ligne = String::new();
+    BufReader::new(fichier).read_line(&mut ligne)?;
+    Ok((!ligne.is_empty()).then(|| ligne.trim_end().to_string()))
+}
+
+/// Le fichier commence par un en-tête de fichier chiffré.
+pub fn est_chiffre(chemin: &Path) -> io::Result<bool> {
+    Ok(premiere_ligne(chemin)?.is_some_and(|ligne| ligne.starts_with(MARQUE)))
+}
+
+/// Phrase de passe des fichiers. Sans phrase, les fichiers sont lus et écrits
+/// en clair ; avec une phrase, un fichier en clair est refusé.
+#[derive(Clone)]
+pub struct Coffre {
+    phrase: Option<String>,
+    iterations: u32,
+    // La dérivation est volontairement lente et un journal est complété à
+    // chaque opération : les clés déjà dérivées sont gardées, par sel.
+    cles: RefCell<Vec<CleDerivee>>,
+    // Clé des fichiers créés ou réécrits par ce coffre.
+    nouvelle: RefCell<Option<CleDerivee>>,
+}
+
+impl Coffre {
+    pub fn en_clair() -> Self {
+        Coffre {
+            phrase: None,
+            iterations: ITERATIONS_PAR_DEFAUT,
+            cles: RefCell::new(Vec::new()),
+            nouvelle: RefCell::new(None),
+        }
+    }
+
+    pub fn avec_phrase(phrase: &str) -> Self {
+        Coffre {
+            phrase: Some(phrase.to_string()),
+            ..Coffre::en_clair()
+        }
+    }
+
+    /// Change le coût de la dérivation des clés des nouveaux fichiers ; les
+    /// fichiers existants gardent celui inscrit dans leur en-tête.
+    pub fn avec_iterations(mut self, iterations: u32) -> Self {
+        self.iterations = iterations;
+        self
+    }
+
+    pub fn chiffre(&self) -> bool {
+        self.phrase.is_some()
+    }
+
+    fn deriver(phrase: &str, sel: Vec<u8>, iterations: u32) -> CleDerivee {
+        let mut cle = Key::default();
+        pbkdf2::pbkdf2_hmac::<Sha256>(phrase.as_bytes(), &sel, iterations, &mut cle);
+        CleDerivee {
+            sel,
+            iterations,
+            cle,
+        }
+    }
+
+    fn cle_nouvelle(&self, phrase: &str) -> CleDerivee {
+        self.nouvelle
+            .borrow_mut()
+            .get_or_insert_with(|| {
+                let mut sel = vec![0; TAILLE_SEL];
+                OsRng.fill_bytes(&mut sel);
+                Coffre::deriver(phrase, sel, self.iterations)
+            })
+            .clone()
+    }
+
+    /// Clé et version d'un fichier existant, d'après son en-tête.
+    fn cle_fichier(
+        &self,
+        chemin: &Path,
+        entete: &str,
+    ) -> Result<(CleDerivee, u32), ErreurChiffrement> {
+        let corrompu = || ErreurChiffrement::FichierCorrompu {
+            chemin: chemin.to_path_buf(),
+            ligne: 1,
+        };
+        let champs: Vec<&str> = entete.split(';').collect();
+        let (version, iterations, sel, verificateur) = match champs[..] {
+            [MARQUE, version, iterations, sel, verificateur]
+                if version == VERSION_SANS_RANG.to_string() =>
+            {
+                (version, iterations, sel, verificateur)
+            }
+            [MARQUE, version, iterations, sel, verificateur, identifiant]
+                if version == VERSION.to_string()
+                    && depuis_hex(identifiant).is_some_and(|i| i.len() == TAILLE_IDENTIFIANT) =>
+            {
+                (version, iterations, sel, verificateur)
+            }
+            _ => return Err(corrompu()),
+        };
+        let Some(phrase) = &self.phrase else {
+            return Err(ErreurChiffrement::PhraseRequise(chemin.to_path_buf()));
+        };
+        let (Ok(version @ (VERSION_SANS_RANG | VERSION)), Ok(iterations), Some(sel)) =
+            (version.parse(), iterations.parse(), depuis_hex(sel))
+        else {
+            return Err(corrompu());
+        };
+
+        let connue = self
+            .cles
+            .borrow()
+            .iter()
+            .find(|c| c.sel == sel && c.iterations == iterations)
+            .cloned();
+        let cle = match connue {
+            Some(cle) => cle,
+            None => {
+                let cle = Coffre::deriver(phrase, sel, iterations);
+                self.cles.borrow_mut().push(cle.clone());
+                cle
+            }
+        };
+        if cle.verificateur() != verificateur {
+            return Err(ErreurChiffrement::PhraseIncorrecte(chemin.to_path_buf()));
+        }
+        Ok((cle, version))
+    }
+
+    /// Vérifie que la phrase de passe convient au fichier, sans le déchiffrer.
+    /// Un fichier absent ou vide convient toujours.
+    pub fn controler(&self, chemin: &Path) -> Result<(), ErreurChiffrement> {
+        match premiere_ligne(chemin)? {
+            Some(entete) if entete.starts_with(MARQUE) => {
+                self.cle_fichier(chemin, &entete).map(|_| ())
+            }
+            Some(_) if self.chiffre() => Err(ErreurChiffrement::NonChiffre(chemin.to_path_buf())),
+            _ => Ok(()),
+        }
+    }
+
+    /// Contenu en clair du fichier.
+    pub fn lire(&self, chemin: &Path) -> Result<String, ErreurChiffrement> {
+        let contenu = fs::read_to_string(chemin)?;
+        let mut lignes = contenu.lines();
+        let entete = match lignes.next() {
+            Some(entete) if entete.starts_with(MARQUE) => entete,
+            _ if self.chiffre() && !contenu.is_empty() => {
+                return Err(ErreurChiffrement::NonChiffre(chemin.to_path_buf()));
+            }
+            _ => return Ok(contenu),
+        };
+
+        let (cle, version) = self.cle_fichier(chemin, entete)?;
+        let corrompu = |ligne| ErreurChiffrement::FichierCorrompu {
+            chemin: chemin.to_path_buf(),
+            ligne,
+        };
+        let mut blocs: Vec<&str> = lignes.collect();
+        if version == VERSION_SANS_RANG {
+            let mut texte = String::new();
+            for (i, bloc) in blocs.into_iter().enumerate() {
+                let bloc = cle
+                    .dechiffrer(bloc, &[])
+                    .and_then(|texte| String::from_utf8(texte).ok())
+                    .ok_or_else(|| corrompu(i + 2))?;
+                texte.push_str(&bloc);
+            }
+            return Ok(texte);
+        }
+
+        // La ligne de fin donne le nombre de blocs : un fichier tronqué n'en a
+        // plus, ou pas autant.
+        let fin = blocs.pop().ok_or_else(|| corrompu(2))?;
+        if cle.lire_fin(entete, fin) != Some(blocs.len() as u64) {
+            return Err(corrompu(blocs.len() + 2));
+        }
+        let mut texte = String::new();
+        for (i, bloc) in blocs.into_iter().enumerate() {
+            let bloc = cle
+                .lire_bloc(entete, i as u64, bloc)
+                .ok_or_else(|| corrompu(i + 2))?;
+            texte.push_str(&bloc);
+        }
+        Ok(texte)
+    }
+
+    /// Remplace le contenu du fichier. Le nouveau contenu est écrit à côté puis
+    /// renommé, pour ne jamais laisser un fichier à moitié écrit.
+    pub fn ecrire(&self, chemin: &Path, contenu: &str) -> Result<(), ErreurChiffrement> {
+        let donnees = match &self.phrase {
+            Some(phrase) => self
+                .cle_nouvelle(phrase)
+                .fichier(contenu)
+                .ok_or_else(|| ErreurChiffrement::ChiffrementImpossible(chemin.to_path_buf()))?,
+            None => contenu.to_string(),
+        };
+        let mut temporaire = chemin.as_os_str().to_owned();
+        temporaire.push(".tmp");
+        fs::write(&temporaire, donnees)?;
+        fs::rename(&temporaire, chemin)?;
+        Ok(())
+    }
+
+    /// Ajoute une ligne en fin de fichier, sans relire ni réécrire le reste.
+    pub fn ajouter_ligne(&self, chemin: &Path, ligne: &str) -> Result<(), ErreurChiffrement> {
+        let entete = premiere_ligne(chemin)?;
+        let chiffre = entete.as_deref().is_some_and(|e| e.starts_with(MARQUE));
+        let ligne = format!("{}\n", ligne);
+        let impossible = || ErreurChiffrement::ChiffrementImpossible(chemin.to_path_buf());
+        let entete = match (&self.phrase, entete) {
+            (None, Some(_)) if chiffre => {
+                return Err(ErreurChiffrement::PhraseRequise(chemin.to_path_buf()));
+            }
+            (None, _) => {
+                let mut fichier = OpenOptions::new().create(true).append(true).open(chemin)?;
+                fichier.write_all(ligne.as_bytes())?;
+                return Ok(());
+            }
+            (Some(_), Some(_)) if !chiffre => {
+                return Err(ErreurChiffrement::NonChiffre(chemin.to_path_buf()));
+            }
+            (Some(phrase), None) => {
+                let donnees = self.cle_nouvelle(phrase).fichier(&ligne);
+                fs::write(chemin, donnees.ok_or_else(impossible)?)?;
+                return Ok(());
+            }
+            (Some(_), Some(entete)) => entete,
+        };
+
+        let (cle, version) = self.cle_fichier(chemin, &entete)?;
+        if version == VERSION_SANS_RANG {
+            let contenu = self.lire(chemin)?;
+            return self.ecrire(chemin, &(contenu + &ligne));
+        }
+        // La ligne de fin, de longueur fixe, est remplacée par le nouveau bloc
+        // suivi de la nouvelle ligne de fin.
+        let mut fichier = OpenOptions::new().read(true).write(true).open(chemin)?;
+        let longueur = fichier.metadata()?.len();
+        let debut_fin = longueur
+            .checked_sub(LONGUEUR_FIN as u64)
+            .filter(|debut| *debut > entete.len() as u64);
+        let mut fin = vec![0; LONGUEUR_FIN + 1];
+        let nombre = match debut_fin {
+            Some(debut) => {
+                fichier.seek(SeekFrom::Start(debut - 1))?;
+                fichier.read_exact(&mut fin)?;
+                std::str::from_utf8(&fin)
+                    .ok()
+                    .and_then(|fin| fin.strip_prefix('\n')?.strip_suffix('\n'))
+                    .and_then(|fin| cle.lire_fin(&entete, fin))
+            }
+            None => None,
+        };
+        let (Some(debut), Some(nombre)) = (debut_fin, nombre) else {
+            let lignes = fs::read_to_string(chemin)?.lines().count();
+            return Err(ErreurChiffrement::FichierCorrompu {
+                chemin: chemin.to_path_buf(),
+                ligne: lignes,
+            });
+        };
+        let donnees = format!(
+            "{}\n{}\n",
+            cle.bloc(&entete, nombre, &ligne).ok_or_else(impossible)?,
+            cle.fin(&entete, nombre + 1).ok_or_else(impossible)?
+        );
+        fichier.seek(SeekFrom::Start(debut))?;
+        fichier.write_all(donnees.as_bytes())?;
+        Ok(())
+    }
+
+    /// Réécrit le fichier avec la phrase de `nouveau` ; un coffre en clair le
+    /// déchiffre. Retourne `false` si le fichier n'existe pas.
+    pub fn rechiffrer(&self, chemin: &Path, nouveau: &Coffre) -> Result<bool, ErreurChiffrement> {
+        let contenu = match self.lire(chemin) {
+            Ok(contenu) => contenu,
+            Err(e) if e.introuvable() => return Ok(false),
+            Err(e) => return Err(e),
+        };
+        nouveau.ecrire(chemin, &contenu)?;
+        Ok(true)
+    }
+}
+
+impl Default for Coffre {
+    fn default() -> Self {
+        Coffre::en_clair()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn coffre(phrase: &str) -> Coffre {
+        Coffre::avec_phrase(phrase).avec_iterations(1000)
+    }
+
+    fn fichier_temporaire(nom: &str) -> PathBuf {
+        let chemin =
+            std::env::temp_dir().join(format!("tp1_chiffrement_{}_{}", nom, std::process::id()));
+        let _ = fs::remove_file(&chemin);
+        chemin
+    }
+
+    #[test]
+    fn journal_chiffre_puis_rotation_de_la_phrase() {
+        let chemin = fichier_temporaire("journal");
+        let alice = coffre("alice");
+        alice.ajouter_ligne(&chemin, "Alice Dupont\t1500").unwrap();
+        alice.ajouter_ligne(&chemin, "Bob Martin\t2300").unwrap();
+
+        let brut = fs::read_to_string(&chemin).unwrap();
+        assert!(brut.starts_with(MARQUE));
+        assert!(!brut.contains("Alice"));
+        // Relu par un autre coffre, qui doit dériver la clé depuis l'en-tête.
+        assert_eq!(
+            coffre("alice").lire(&chemin).unwrap(),
+            "Alice Dupont\t1500\nBob Martin\t2300\n"
+        );
+
+        assert!(matches!(
+            coffre("bob").lire(&chemin),
+            Err(ErreurChiffrement::PhraseIncorrecte(_))
+        ));
+        assert!(matches!(
+            Coffre::en_clair().ajouter_ligne(&chemin, "x"),
+            Err(ErreurChiffrement::PhraseRequise(_))
+        ));
+
+        let bob = coffre("bob");
+        assert!(alice.rechiffrer(&chemin, &bob).unwrap());
+        assert!(alice.controler(&chemin).is_err());
+        bob.ajouter_ligne(&chemin, "Claire\t800").unwrap();
+        assert!(
+            bob.lire(&chemin)
+                .unwrap()
+                .ends_with("Bob Martin\t2300\nClaire\t800\n")
+        );
+
+        bob.rechiffrer(&chemin, &Coffre::en_clair()).unwrap();
+        assert!(fs::read_to_string(&chemin).unwrap().starts_with("Alice"));
+        assert!(matches!(
+            bob.lire(&chemin),
+            Err(ErreurChiffrement::NonChiffre(_))
+        ));
+        fs::remove_file(&chemin).unwrap();
+    }
+
+    #[test]
+    fn fichier_altere() {
+        let chemin = fichier_temporaire("altere");
+        let coffre = coffre("phrase");
+        coffre.ecrire(&chemin, "client;C1;Alice\n").unwrap();
+
+        let mut lignes: Vec<String> = fs::read_to_string(&chemin)
+            .unwrap()
+            .lines()
+            .map(String::from)
+            .collect();
+        let dernier = lignes[1].pop().unwrap();
+        lignes[1].push(if dernier == '0' { '1' } else { '0' });
+        fs::write(&chemin, lignes.join("\n")).unwrap();
+
+        let erreur = coffre.lire(&chemin).unwrap_err();
+        assert!(matches!(
+            erreur,
+            ErreurChiffrement::FichierCorrompu { ligne: 2, .. }
+        ));
+        fs::remove_file(&chemin).unwrap();
+    }
+
+    fn journal_de_trois_lignes(nom: &str, coffre: &Coffre) -> (PathBuf, Vec<String>) {
+        let chemin = fichier_temporaire(nom);
+        for ligne in ["un", "deux", "trois"] {
+            coffre.ajouter_ligne(&chemin, ligne).unwrap();
+        }
+        let lignes = fs::read_to_string(&chemin)
+            .unwrap()
+            .lines()
+            .map(String::from)
+            .collect();
+        (chemin, lignes)
+    }
+
+    fn ligne_corrompue(coffre: &Coffre, chemin: &Path, lignes: &[String]) -> usize {
+        fs::write(chemin, lignes.join("\n") + "\n").unwrap();
+        match coffre.lire(chemin) {
+            Err(ErreurChiffrement::FichierCorrompu { ligne, .. }) => ligne,
+            autre => panic!("fichier accepté : {:?}", autre),
+        }
+    }
+
+    #[test]
+    fn blocs_permutes_retires_ou_dupliques() {
+        let coffre = coffre("phrase");
+        let (chemin, lignes) = journal_de_trois_lignes("blocs", &coffre);
+        // En-tête, trois blocs et la ligne de fin.
+        assert_eq!(lignes.len(), 5);
+        assert_eq!(coffre.lire(&chemin).unwrap(), "un\ndeux\ntrois\n");
+
+        let mut permutes = lignes.clone();
+        permutes.swap(1, 2);
+        assert_eq!(ligne_corrompue(&coffre, &chemin, &permutes), 2);
+
+        let mut sans_dernier = lignes.clone();
+        sans_dernier.remove(3);
+        assert_eq!(ligne_corrompue(&coffre, &chemin, &sans_dernier), 4);
+
+        let mut duplique = lignes.clone();
+        duplique.insert(2, lignes[1].clone());
+        assert_eq!(ligne_corrompue(&coffre, &chemin, &duplique), 6);
+
+        // Tronqué après un bloc, avec ou sans sa ligne de fin.
+        assert_eq!(ligne_corrompue(&coffre, &chemin, &lignes[..4]), 4);
+        let mut fin_deplacee = lignes[..2].to_vec();
+        fin_deplacee.push(lignes[4].clone());
+        assert_eq!(ligne_corrompue(&coffre, &chemin, &fin_deplacee), 3);
+
+        // Bloc d'un autre fichier chiffré avec la même clé.
+        let (autre, lignes_autre) = journal_de_trois_lignes("blocs_autre", &coffre);
+        let mut melange = lignes.clone();
+        melange[2] = lignes_autre[2].clone();
+        assert_eq!(ligne_corrompue(&coffre, &chemin, &melange), 3);
+
+        // Un ajout sur un fichier tronqué est refusé.
+        fs::write(&chemin, lignes[..4].join("\n") + "\n").unwrap();
+        assert!(matches!(
+            coffre.ajouter_ligne(&chemin, "quatre"),
+            Err(ErreurChiffrement::FichierCorrompu { ligne: 4, .. })
+        ));
+        fs::remove_file(&chemin).unwrap();
+        fs::remove_file(&autre).unwrap();
+    }
+
+    #[test]
+    fn fichier_de_la_version_1_relu_puis_mis_a_jour() {
+        let chemin = fichier_temporaire("version_1");
+        let coffre = coffre("phrase");
+        let cle = coffre.cle_nouvelle("phrase");
+        let entete = format!(
+            "{};{};{};{};{}",
+            MARQUE,
+            VERSION_SANS_RANG,
+            cle.iterations,
+            hex(&cle.sel),
+            cle.verificateur()
+        );
+        fs::write(
+            &chemin,
+            format!(
+                "{}\n{}\n{}\n",
+                entete,
+                cle.chiffrer(b"un\n", &[]).unwrap(),
+                cle.chiffrer(b"deux\n", &[]).unwrap()
+            ),
+        )
+        .unwrap();
+        assert_eq!(coffre.lire(&chemin).unwrap(), "un\ndeux\n");
+
+        coffre.ajouter_ligne(&chemin, "trois").unwrap();
+        let brut = fs::read_to_string(&chemin).unwrap();
+        assert!(brut.starts_with(&format!("{};{};", MARQUE, VERSION)));
+        assert_eq!(coffre.lire(&chemin).unwrap(), "un\ndeux\ntrois\n");
+        fs::remove_file(&chemin).unwrap();
+    }
+}
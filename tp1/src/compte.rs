@@ -0,0 +1,327 @@
+//! Compte bancaire et son historique de mouvements.
+
+use crate::erreur::ErreurBancaire;
+use crate::pret::Pret;
+use crate::rapport;
+use crate::transaction::{Libelle, Transaction};
+use crate::{langue, tr};
+use chrono::{Local, NaiveDate, NaiveDateTime};
+use std::io::{self, Write};
+
+/// Nature d'un compte : compte courant ou compte de prêt avec son échéancier.
+#[derive(Debug)]
+pub enum TypeCompte {
+    Courant,
+    Pret(Pret),
+}
+
+#[derive(Debug)]
+pub struct CompteBancaire {
+    pub(crate) numero: String,
+    pub(crate) titulaire: String,
+    // Identifiant du client dans le jeu de données ; à défaut, le nom du titulaire.
+    pub(crate) client: String,
+    pub(crate) solde: f32,
+    pub(crate) type_compte: TypeCompte,
+    pub(crate) historique: Vec<Transaction>,
+}
+
+impl CompteBancaire {
+    /// Crée un compte courant ; le solde initial est inscrit comme premier mouvement.
+    pub fn new(numero: String, titulaire: String, solde_initial: f32) -> Self {
+        CompteBancaire::ouvert_le(numero, titulaire, solde_initial, Local::now().naive_local())
+    }
+
+    // Compte courant ouvert à une date passée, pour reprendre un historique existant.
+    pub(crate) fn ouvert_le(
+        numero: String,
+        titulaire: String,
+        solde_initial: f32,
+        date: NaiveDateTime,
+    ) -> Self {
+        let mut compte = CompteBancaire {
+            numero,
+            client: titulaire.clone(),
+            titulaire,
+            solde: 0.0,
+            type_compte: TypeCompte::Courant,
+            historique: Vec::new(),
+        };
+        compte.enregistrer_le(
+            date,
+            solde_initial,
+            Libelle::banque("libelle.solde_initial", &[]),
+        );
+        compte.historique[0].categorie = Some(Libelle::banque("categorie.ouverture", &[]));
+        compte
+    }
+
+    // Le solde d'un compte de prêt est négatif : c'est le capital restant dû.
+    pub(crate) fn nouveau_pret(
+        numero: String,
+        titulaire: String,
+        pret: Pret,
+        date: NaiveDateTime,
+    ) -> Self {
+        let capital = pret.capital;
+        let mut compte = CompteBancaire {
+            numero,
+            client: titulaire.clone(),
+            titulaire,
+            solde: 0.0,
+            type_compte: TypeCompte::Pret(pret),
+            historique: Vec::new(),
+        };
+        compte.enregistrer_le(
+            date,
+            -capital,
+            Libelle::banque("libelle.capital_emprunte", &[]),
+        );
+        compte.historique[0].categorie = Some(Libelle::banque("categorie.ouverture", &[]));
+        compte
+    }
+
+    pub(crate) fn du_client(mut self, client: impl Into<String>) -> Self {
+        self.client = client.into();
+        self
+    }
+
+    // Toute variation du solde passe par ici pour que l'historique reste
+    // la source de vérité des soldes passés.
+    pub(crate) fn enregistrer(&mut self, montant: f32, libelle: impl Into<Libelle>) {
+        self.enregistrer_le(Local::now().naive_local(), montant, libelle);
+    }
+
+    pub(crate) fn enregistrer_le(
+        &mut self,
+        date: NaiveDateTime,
+        montant: f32,
+        libelle: impl Into<Libelle>,
+    ) {
+        self.solde += montant;
+        self.historique
+            .push(Transaction::new(date, libelle.into(), montant));
+    }
+
+    pub fn numero(&self) -> &str {
+        &self.numero
+    }
+
+    pub fn titulaire(&self) -> &str {
+        &self.titulaire
+    }
+
+    /// Identifiant du client, qui distingue deux titulaires homonymes.
+    pub fn client(&self) -> &str {
+        &self.client
+    }
+
+    pub fn solde(&self) -> f32 {
+        self.solde
+    }
+
+    pub fn type_compte(&self) -> &TypeCompte {
+        &self.type_compte
+    }
+
+    /// Prêt associé, si le compte est un compte de prêt.
+    pub fn pret(&self) -> Option<&Pret> {
+        match &self.type_compte {
+            TypeCompte::Pret(pret) => Some(pret),
+            TypeCompte::Courant => None,
+        }
+    }
+
+    /// Mouvements du compte, du plus ancien au plus récent.
+    pub fn historique(&self) -> &[Transaction] {
+        &self.historique
+    }
+
+    /// Solde du compte à la fin de la journée `date`, recalculé depuis l'historique.
+    pub fn solde_au(&self, date: NaiveDate) -> f32 {
+        self.historique
+            .iter()
+            .filter(|t| t.date.date() <= date)
+            .fold(0.0, |solde, t| solde + t.montant)
+    }
+
+    /// Affiche les mouvements jusqu'à `date` incluse, numérotés à partir de 1.
+    pub fn afficher_historique(&self, date: NaiveDate, sortie: &mut impl Write) -> io::Result<()> {
+        for (i, t) in self.historique.iter().enumerate() {
+            if t.date.date() > date {
+                continue;
+            }
+            writeln!(
+                sortie,
+                "  {:>3}. {} | {:<40} | {:<20} | {:>11}",
+                i + 1,
+                t.date.format("%d/%m/%Y %H:%M"),
+                t.libelle,
+                t.categorie
+                    .as_ref()
+                    .map_or_else(rapport::sans_categorie, Libelle::to_string),
+                langue::montant(t.montant)
+            )?;
+        }
+        Ok(())
+    }
+
+    pub fn afficher_solde(&self, sortie: &mut impl Write) -> io::Result<()> {
+        match &self.type_compte {
+            TypeCompte::Courant => writeln!(
+                sortie,
+                "{}",
+                tr!(
+                    "compte.solde",
+                    self.numero,
+                    self.titulaire,
+                    langue::montant(self.solde)
+                )
+            ),
+            TypeCompte::Pret(pret) => {
+                let mensualite = match pret.prochaine_echeance() {
+                    Some(echeance) => langue::montant(echeance.mensualite),
+                    None => tr!("pret.solde"),
+                };
+                writeln!(
+                    sortie,
+                    "{}",
+                    tr!(
+                        "compte.solde_pret",
+                        self.numero,
+                        self.titulaire,
+                        langue::montant(-self.solde),
+                        mensualite
+                    )
+                )
+            }
+        }
+    }
+
+    pub(crate) fn retrait(
+        &mut self,
+        montant: f32,
+        libelle: impl Into<Libelle>,
+    ) -> Result<(), ErreurBancaire> {
+        if let TypeCompte::Pret(_) = self.type_compte {
+            return Err(ErreurBancaire::OperationSurPret);
+        }
+
+        if !montant.is_finite() || montant <= 0.0 {
+            return Err(ErreurBancaire::MontantNonPositif);
+        }
+
+        if montant > self.solde {
+            return Err(ErreurBancaire::SoldeInsuffisant);
+        }
+
+        self.enregistrer(-montant, libelle);
+        Ok(())
+    }
+
+    pub(crate) fn depot(
+        &mut self,
+        montant: f32,
+        libelle: impl Into<Libelle>,
+    ) -> Result<(), ErreurBancaire> {
+        if let TypeCompte::Pret(_) = self.type_compte {
+            return Err(ErreurBancaire::OperationSurPret);
+        }
+
+        if !montant.is_finite() || montant <= 0.0 {
+            return Err(ErreurBancaire::MontantNonPositif);
+        }
+
+        self.enregistrer(montant, libelle);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pret::ModeAmortissement;
+
+    fn le(jour: u32, mois: u32, annee: i32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(annee, mois, jour)
+            .unwrap()
+            .and_hms_opt(10, 0, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn depot_et_retrait_controlent_le_montant_et_le_solde() {
+        let mut compte = CompteBancaire::new("12345".to_string(), "Alice".to_string(), 100.0);
+        compte.depot(50.0, "Dépôt").unwrap();
+        compte.retrait(30.0, "Retrait").unwrap();
+        assert_eq!(compte.solde(), 120.0);
+        assert_eq!(compte.historique().len(), 3);
+        assert_eq!(compte.historique()[2].montant, -30.0);
+
+        for montant in [0.0, -10.0, f32::NAN, f32::INFINITY] {
+            assert_eq!(
+                compte.depot(montant, "Dépôt"),
+                Err(ErreurBancaire::MontantNonPositif)
+            );
+            assert_eq!(
+                compte.retrait(montant, "Retrait"),
+                Err(ErreurBancaire::MontantNonPositif)
+            );
+        }
+        assert_eq!(
+            compte.retrait(120.01, "Retrait"),
+            Err(ErreurBancaire::SoldeInsuffisant)
+        );
+        compte.retrait(120.0, "Retrait").unwrap();
+        assert_eq!(compte.solde(), 0.0);
+        assert_eq!(compte.historique().len(), 4);
+    }
+
+    #[test]
+    fn pas_de_depot_ni_de_retrait_sur_un_pret() {
+        let pret = Pret::new(
+            1000.0,
+            3.0,
+            12,
+            ModeAmortissement::AnnuiteConstante,
+            "12345".to_string(),
+            le(1, 1, 2024).date(),
+        );
+        let mut compte = CompteBancaire::nouveau_pret(
+            "P1".to_string(),
+            "Alice".to_string(),
+            pret,
+            le(1, 1, 2024),
+        );
+        assert_eq!(compte.solde(), -1000.0);
+        assert_eq!(
+            compte.depot(10.0, "Dépôt"),
+            Err(ErreurBancaire::OperationSurPret)
+        );
+        assert_eq!(
+            compte.retrait(10.0, "Retrait"),
+            Err(ErreurBancaire::OperationSurPret)
+        );
+        assert_eq!(compte.historique().len(), 1);
+    }
+
+    #[test]
+    fn solde_au_recalcule_depuis_les_mouvements_dates() {
+        let mut compte = CompteBancaire::ouvert_le(
+            "12345".to_string(),
+            "Alice".to_string(),
+            100.0,
+            le(1, 3, 2024),
+        );
+        compte.enregistrer_le(le(10, 3, 2024), 50.0, "Dépôt");
+        compte.enregistrer_le(le(20, 3, 2024), -80.0, "Retrait");
+        assert_eq!(compte.solde(), 70.0);
+        assert_eq!(compte.historique()[1].date, le(10, 3, 2024));
+
+        assert_eq!(compte.solde_au(le(28, 2, 2024).date()), 0.0);
+        assert_eq!(compte.solde_au(le(1, 3, 2024).date()), 100.0);
+        assert_eq!(compte.solde_au(le(10, 3, 2024).date()), 150.0);
+        assert_eq!(compte.solde_au(le(19, 3, 2024).date()), 150.0);
+        assert_eq!(compte.solde_au(le(20, 3, 2024).date()), 70.0);
+    }
+}
@@ -0,0 +1,104 @@
+use chrono::NaiveDate;
+use std::io::{self, BufRead, Write};
+use tp1::{langue, tr};
+
+/// Entrée et sortie du menu interactif.
+///
+/// Le menu ne lit et n'écrit qu'à travers cette structure, ce qui permet de
+/// le piloter avec un script en test. Une fin d'entrée (EOF) est remontée
+/// comme une erreur `UnexpectedEof` pour que le menu s'arrête proprement.
+pub struct Console<R, W> {
+    entree: R,
+    pub sortie: W,
+    sans_echo: bool,
+}
+
+impl<R: BufRead, W: Write> Console<R, W> {
+    pub fn new(entree: R, sortie: W) -> Self {
+        Console {
+            entree,
+            sortie,
+            sans_echo: false,
+        }
+    }
+
+    /// Avec `actif`, les mots de passe sont saisis sans écho sur le terminal.
+    pub fn saisie_sans_echo(mut self, actif: bool) -> Self {
+        self.sans_echo = actif;
+        self
+    }
+
+    pub fn lire_input(&mut self, prompt: &str) -> io::Result<String> {
+        write!(self.sortie, "{}", prompt)?;
+        self.sortie.flush()?;
+
+        let mut input = String::new();
+        if self.entree.read_line(&mut input)? == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                tr!("console.fin_entree"),
+            ));
+        }
+        Ok(input.trim().to_string())
+    }
+
+    /// Lit un mot de passe, sans écho si la console le permet.
+    pub fn lire_secret(&mut self, prompt: &str) -> io::Result<String> {
+        if self.sans_echo {
+            self.sortie.flush()?;
+            rpassword::prompt_password(prompt)
+        } else {
+            self.lire_input(prompt)
+        }
+    }
+
+    pub fn lire_montant(&mut self, prompt: &str) -> io::Result<f32> {
+        loop {
+            let input = self.lire_input(prompt)?;
+            match langue::lire_nombre(&input) {
+                Some(montant) => return Ok(montant),
+                None => writeln!(self.sortie, "{}", tr!("console.montant_invalide"))?,
+            }
+        }
+    }
+
+    /// Comme `lire_montant`, mais une saisie vide signifie « aucun montant ».
+    pub fn lire_montant_optionnel(&mut self, prompt: &str) -> io::Result<Option<f32>> {
+        loop {
+            let input = self.lire_input(prompt)?;
+            if input.is_empty() {
+                return Ok(None);
+            }
+            match langue::lire_nombre(&input) {
+                Some(montant) => return Ok(Some(montant)),
+                None => writeln!(self.sortie, "{}", tr!("console.montant_invalide"))?,
+            }
+        }
+    }
+
+    pub fn lire_libelle(&mut self, par_defaut: &str) -> io::Result<String> {
+        let libelle = self.lire_input(&tr!("console.libelle", par_defaut))?;
+        if libelle.is_empty() {
+            Ok(par_defaut.to_string())
+        } else {
+            Ok(libelle)
+        }
+    }
+
+    pub fn lire_date(&mut self, prompt: &str) -> io::Result<NaiveDate> {
+        loop {
+            let input = self.lire_input(prompt)?;
+            match NaiveDate::parse_from_str(&input, "%d/%m/%Y") {
+                Ok(date) => return Ok(date),
+                Err(_) => writeln!(self.sortie, "{}", tr!("console.date_invalide"))?,
+            }
+        }
+    }
+
+    pub fn confirmer(&mut self, prompt: &str) -> io::Result<bool> {
+        let reponse = self.lire_input(prompt)?.to_lowercase();
+        Ok(tr!("console.reponses_oui")
+            .split(',')
+            .any(|oui| oui == reponse))
+    }
+}
@@ -0,0 +1,147 @@
+//! Comptes inactifs : classement selon la durée sans mouvement, mise en
+//! dormance, et rapport des comptes qui atteignent le seuil légal au-delà
+//! duquel leurs avoirs doivent être transférés à l'organisme désigné.
+
+use crate::{langue, tr};
+use chrono::{Months, NaiveDate};
+use std::io::{self, Write};
+
+/// Durées d'inactivité qui déterminent la classe d'un compte.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ConditionsDormance {
+    /// Mois sans mouvement après lesquels le compte est dormant.
+    pub dormance_mois: u32,
+    /// Mois sans mouvement après lesquels le seuil légal est atteint.
+    pub seuil_legal_mois: u32,
+    /// Mois avant le seuil légal pendant lesquels le titulaire doit être prévenu.
+    pub preavis_mois: u32,
+}
+
+impl Default for ConditionsDormance {
+    /// Dormant après un an, seuil légal à dix ans, préavis de six mois.
+    fn default() -> Self {
+        ConditionsDormance {
+            dormance_mois: 12,
+            seuil_legal_mois: 120,
+            preavis_mois: 6,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ClasseInactivite {
+    Actif,
+    Dormant,
+    /// Dormant, dans la période de préavis qui précède le seuil légal.
+    SeuilProche,
+    SeuilAtteint,
+}
+
+impl ClasseInactivite {
+    pub fn libelle(&self) -> &'static str {
+        match self {
+            ClasseInactivite::Actif => langue::texte("dormance.actif"),
+            ClasseInactivite::Dormant => langue::texte("dormance.dormant"),
+            ClasseInactivite::SeuilProche => langue::texte("dormance.seuil_proche"),
+            ClasseInactivite::SeuilAtteint => langue::texte("dormance.seuil_atteint"),
+        }
+    }
+}
+
+impl ConditionsDormance {
+    /// Classe d'un compte dont le dernier mouvement date du `dernier_mouvement`.
+    pub fn classer(&self, dernier_mouvement: NaiveDate, date: NaiveDate) -> ClasseInactivite {
+        let apres = |mois: u32| {
+            dernier_mouvement
+                .checked_add_months(Months::new(mois))
+                .is_some_and(|limite| limite <= date)
+        };
+        let preavis = self.seuil_legal_mois.saturating_sub(self.preavis_mois);
+        // Sans préavis, `preavis` vaut le seuil légal, déjà traité.
+        if apres(self.seuil_legal_mois) {
+            ClasseInactivite::SeuilAtteint
+        } else if apres(preavis.max(self.dormance_mois)) {
+            ClasseInactivite::SeuilProche
+        } else if apres(self.dormance_mois) {
+            ClasseInactivite::Dormant
+        } else {
+            ClasseInactivite::Actif
+        }
+    }
+}
+
+/// Classement d'un compte courant à une date.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClassementCompte {
+    pub numero: String,
+    pub titulaire: String,
+    pub solde: f32,
+    pub dernier_mouvement: NaiveDate,
+    pub classe: ClasseInactivite,
+}
+
+/// Affiche le nombre de comptes de chaque classe, puis les comptes qui
+/// approchent ou atteignent le seuil légal, du plus ancien au plus récent.
+pub fn afficher_rapport(
+    classements: &[ClassementCompte],
+    date: NaiveDate,
+    sortie: &mut impl Write,
+) -> io::Result<()> {
+    writeln!(
+        sortie,
+        "=== {} ===",
+        tr!("dormance.titre", date.format("%d/%m/%Y"))
+    )?;
+    for classe in [
+        ClasseInactivite::Actif,
+        ClasseInactivite::Dormant,
+        ClasseInactivite::SeuilProche,
+        ClasseInactivite::SeuilAtteint,
+    ] {
+        let nombre = classements.iter().filter(|c| c.classe == classe).count();
+        writeln!(sortie, "{:<24} {:>6}", classe.libelle(), nombre)?;
+    }
+
+    let mut a_traiter: Vec<&ClassementCompte> = classements
+        .iter()
+        .filter(|c| c.classe >= ClasseInactivite::SeuilProche)
+        .collect();
+    a_traiter.sort_by_key(|c| c.dernier_mouvement);
+    if a_traiter.is_empty() {
+        return writeln!(sortie, "{}", tr!("dormance.aucun_seuil"));
+    }
+    writeln!(sortie, "\n{}", tr!("dormance.comptes_seuil"))?;
+    for c in a_traiter {
+        writeln!(
+            sortie,
+            "  {:<8} | {:<20} | {} | {:>11} | {}",
+            c.numero,
+            c.titulaire,
+            c.dernier_mouvement.format("%d/%m/%Y"),
+            langue::montant(c.solde),
+            c.classe.libelle()
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(jour: u32, mois: u32, annee: i32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(annee, mois, jour).unwrap()
+    }
+
+    #[test]
+    fn classes_selon_la_duree_d_inactivite() {
+        let conditions = ConditionsDormance::default();
+        let dernier = date(15, 3, 2016);
+        let classe = |jour, mois, annee| conditions.classer(dernier, date(jour, mois, annee));
+        assert_eq!(classe(14, 3, 2017), ClasseInactivite::Actif);
+        assert_eq!(classe(15, 3, 2017), ClasseInactivite::Dormant);
+        assert_eq!(classe(14, 9, 2025), ClasseInactivite::Dormant);
+        assert_eq!(classe(15, 9, 2025), ClasseInactivite::SeuilProche);
+        assert_eq!(classe(15, 3, 2026), ClasseInactivite::SeuilAtteint);
+    }
+}
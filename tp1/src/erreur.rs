@@ -0,0 +1,170 @@
+//! Erreurs métier des opérations bancaires.
+
+use crate::tr;
+use std::fmt;
+
+/// Erreur retournée par les opérations sur les comptes.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ErreurBancaire {
+    MontantNonPositif,
+    SoldeInsuffisant,
+    CompteIntrouvable(String),
+    CompteExistant(String),
+    ClientIntrouvable(String),
+    OperationSurPret,
+    PasUnPret(String),
+    PretSolde,
+    DureePretNulle,
+    TauxInvalide,
+    TransactionIntrouvable(usize),
+    Refusee(String),
+    VirementMemeCompte,
+    Fichier(String),
+    ObjectifExistant(String),
+    ObjectifIntrouvable(String),
+    CleIdempotenceReutilisee(String),
+    OperationBloquee(String),
+    ConfirmationRequise(String),
+    SignalementIntrouvable(usize),
+    ChequeDejaRemis(String),
+    ChequeIntrouvable(String),
+    ChequeRejete(String),
+    CaisseDejaOuverte(String),
+    CaisseFermee,
+    JourneeCloturee,
+    UtilisateurExistant(String),
+    MandatExistant(String),
+    MandatIntrouvable(String),
+    MandatInactif(String),
+    PlafondMandatDepasse(String),
+    PrelevementIntrouvable(usize),
+    PrelevementDejaRembourse(usize),
+    DelaiRemboursementDepasse(usize),
+    IdentifiantsInvalides,
+    NonConnecte,
+    NonAutorise(String),
+    ApprobationRequise(String),
+    DeviseInconnue(String),
+}
+
+impl ErreurBancaire {
+    /// Nom stable de l'erreur, indépendant de la langue, pour les fichiers.
+    pub fn code(&self) -> &'static str {
+        match self {
+            ErreurBancaire::MontantNonPositif => "montant_non_positif",
+            ErreurBancaire::SoldeInsuffisant => "solde_insuffisant",
+            ErreurBancaire::CompteIntrouvable(_) => "compte_introuvable",
+            ErreurBancaire::CompteExistant(_) => "compte_existant",
+            ErreurBancaire::ClientIntrouvable(_) => "client_introuvable",
+            ErreurBancaire::OperationSurPret => "operation_sur_pret",
+            ErreurBancaire::PasUnPret(_) => "pas_un_pret",
+            ErreurBancaire::PretSolde => "pret_solde",
+            ErreurBancaire::DureePretNulle => "duree_pret_nulle",
+            ErreurBancaire::TauxInvalide => "taux_invalide",
+            ErreurBancaire::TransactionIntrouvable(_) => "transaction_introuvable",
+            ErreurBancaire::Refusee(_) => "refusee",
+            ErreurBancaire::VirementMemeCompte => "virement_meme_compte",
+            ErreurBancaire::Fichier(_) => "fichier",
+            ErreurBancaire::ObjectifExistant(_) => "objectif_existant",
+            ErreurBancaire::ObjectifIntrouvable(_) => "objectif_introuvable",
+            ErreurBancaire::CleIdempotenceReutilisee(_) => "cle_idempotence_reutilisee",
+            ErreurBancaire::OperationBloquee(_) => "operation_bloquee",
+            ErreurBancaire::ConfirmationRequise(_) => "confirmation_requise",
+            ErreurBancaire::SignalementIntrouvable(_) => "signalement_introuvable",
+            ErreurBancaire::ChequeDejaRemis(_) => "cheque_deja_remis",
+            ErreurBancaire::ChequeIntrouvable(_) => "cheque_introuvable",
+            ErreurBancaire::ChequeRejete(_) => "cheque_rejete",
+            ErreurBancaire::CaisseDejaOuverte(_) => "caisse_deja_ouverte",
+            ErreurBancaire::CaisseFermee => "caisse_fermee",
+            ErreurBancaire::JourneeCloturee => "journee_cloturee",
+            ErreurBancaire::MandatExistant(_) => "mandat_existant",
+            ErreurBancaire::MandatIntrouvable(_) => "mandat_introuvable",
+            ErreurBancaire::MandatInactif(_) => "mandat_inactif",
+            ErreurBancaire::PlafondMandatDepasse(_) => "plafond_mandat_depasse",
+            ErreurBancaire::PrelevementIntrouvable(_) => "prelevement_introuvable",
+            ErreurBancaire::PrelevementDejaRembourse(_) => "prelevement_deja_rembourse",
+            ErreurBancaire::DelaiRemboursementDepasse(_) => "delai_remboursement_depasse",
+            ErreurBancaire::UtilisateurExistant(_) => "utilisateur_existant",
+            ErreurBancaire::IdentifiantsInvalides => "identifiants_invalides",
+            ErreurBancaire::NonConnecte => "non_connecte",
+            ErreurBancaire::NonAutorise(_) => "non_autorise",
+            ErreurBancaire::ApprobationRequise(_) => "approbation_requise",
+            ErreurBancaire::DeviseInconnue(_) => "devise_inconnue",
+        }
+    }
+}
+
+impl fmt::Display for ErreurBancaire {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let message = match self {
+            ErreurBancaire::MontantNonPositif => tr!("erreur.montant_non_positif"),
+            ErreurBancaire::SoldeInsuffisant => tr!("erreur.solde_insuffisant"),
+            ErreurBancaire::CompteIntrouvable(numero) => tr!("erreur.compte_introuvable", numero),
+            ErreurBancaire::CompteExistant(numero) => tr!("erreur.compte_existant", numero),
+            ErreurBancaire::ClientIntrouvable(titulaire) => {
+                tr!("erreur.client_introuvable", titulaire)
+            }
+            ErreurBancaire::OperationSurPret => tr!("erreur.operation_sur_pret"),
+            ErreurBancaire::PasUnPret(numero) => tr!("erreur.pas_un_pret", numero),
+            ErreurBancaire::PretSolde => tr!("erreur.pret_solde"),
+            ErreurBancaire::DureePretNulle => tr!("erreur.duree_pret_nulle"),
+            ErreurBancaire::TauxInvalide => tr!("erreur.taux_invalide"),
+            ErreurBancaire::TransactionIntrouvable(index) => {
+                tr!("erreur.transaction_introuvable", index)
+            }
+            ErreurBancaire::Refusee(motif) => tr!("erreur.refusee", motif),
+            ErreurBancaire::VirementMemeCompte => tr!("erreur.virement_meme_compte"),
+            ErreurBancaire::Fichier(message) => tr!("erreur.fichier", message),
+            ErreurBancaire::ObjectifExistant(nom) => tr!("erreur.objectif_existant", nom),
+            ErreurBancaire::ObjectifIntrouvable(nom) => tr!("erreur.objectif_introuvable", nom),
+            ErreurBancaire::CleIdempotenceReutilisee(cle) => {
+                tr!("erreur.cle_idempotence_reutilisee", cle)
+            }
+            ErreurBancaire::OperationBloquee(motifs) => tr!("erreur.operation_bloquee", motifs),
+            ErreurBancaire::ConfirmationRequise(motifs) => {
+                tr!("erreur.confirmation_requise", motifs)
+            }
+            ErreurBancaire::SignalementIntrouvable(index) => {
+                tr!("erreur.signalement_introuvable", index)
+            }
+            ErreurBancaire::ChequeDejaRemis(numero) => tr!("erreur.cheque_deja_remis", numero),
+            ErreurBancaire::ChequeIntrouvable(numero) => tr!("erreur.cheque_introuvable", numero),
+            ErreurBancaire::ChequeRejete(numero) => tr!("erreur.cheque_rejete", numero),
+            ErreurBancaire::CaisseDejaOuverte(guichetier) => {
+                tr!("erreur.caisse_deja_ouverte", guichetier)
+            }
+            ErreurBancaire::CaisseFermee => tr!("erreur.caisse_fermee"),
+            ErreurBancaire::JourneeCloturee => tr!("erreur.journee_cloturee"),
+            ErreurBancaire::MandatExistant(reference) => tr!("erreur.mandat_existant", reference),
+            ErreurBancaire::MandatIntrouvable(reference) => {
+                tr!("erreur.mandat_introuvable", reference)
+            }
+            ErreurBancaire::MandatInactif(reference) => tr!("erreur.mandat_inactif", reference),
+            ErreurBancaire::PlafondMandatDepasse(reference) => {
+                tr!("erreur.plafond_mandat_depasse", reference)
+            }
+            ErreurBancaire::PrelevementIntrouvable(index) => {
+                tr!("erreur.prelevement_introuvable", index)
+            }
+            ErreurBancaire::PrelevementDejaRembourse(index) => {
+                tr!("erreur.prelevement_deja_rembourse", index)
+            }
+            ErreurBancaire::DelaiRemboursementDepasse(index) => {
+                tr!("erreur.delai_remboursement_depasse", index)
+            }
+            ErreurBancaire::UtilisateurExistant(identifiant) => {
+                tr!("erreur.utilisateur_existant", identifiant)
+            }
+            ErreurBancaire::IdentifiantsInvalides => tr!("erreur.identifiants_invalides"),
+            ErreurBancaire::NonConnecte => tr!("erreur.non_connecte"),
+            ErreurBancaire::NonAutorise(operation) => tr!("erreur.non_autorise", operation),
+            ErreurBancaire::ApprobationRequise(operation) => {
+                tr!("erreur.approbation_requise", operation)
+            }
+            ErreurBancaire::DeviseInconnue(devise) => tr!("erreur.devise_inconnue", devise),
+        };
+        write!(f, "{}", message)
+    }
+}
+
+impl std::error::Error for ErreurBancaire {}
@@ -0,0 +1,577 @@
+//! Lignes de jeu de données qui reprennent l'état de la banque hors comptes :
+//! chèques, mandats et prélèvements, objectifs d'épargne, règles d'alerte,
+//! caisse et clôtures, comptes dormants et signalements de fraude.
+//!
+//! Chaque ligne commence par sa clé, le genre de l'élément suivi des champs
+//! qui le désignent, puis donne l'élément entier :
+//!
+//! ```text
+//! cheque;0;12345;0000123;Bob Martin;250;06/01/2025;09/01/2025;en_attente
+//! objectif;12345;0;Vacances;1000;31/12/2025;67890;50;05/02/2025
+//! dormant;67890
+//! supprime;objectif;12345;1
+//! ```
+//!
+//! Une ligne remplace la précédente de même clé ; `supprime;` suivi d'une clé
+//! retire l'élément. Un élément rangé dans une liste est désigné par son rang
+//! à partir de 0, précédé du compte pour les listes propres à un compte.
+
+use crate::alerte::RegleAlerte;
+use crate::caisse::{MouvementCaisse, RapportCloture, SessionCaisse};
+use crate::cheque::{Cheque, EtatCheque};
+use crate::erreur::ErreurBancaire;
+use crate::fixture::champ;
+use crate::fraude::{Decision, Signalement};
+use crate::objectif::{ObjectifEpargne, VirementPermanent};
+use crate::prelevement::{Mandat, Prelevement};
+use crate::tr;
+use chrono::{NaiveDate, NaiveDateTime};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Display;
+
+const FORMAT_DATE: &str = "%d/%m/%Y";
+const FORMAT_DATE_HEURE: &str = "%d/%m/%Y %H:%M:%S";
+/// Genre de la ligne qui retire un élément.
+const SUPPRESSION: &str = "supprime";
+
+/// Ligne de chaque élément de l'état, par clé.
+pub(crate) type Etat = BTreeMap<String, String>;
+
+// Nombre de champs de la clé, genre compris.
+fn longueur_cle(genre: &str) -> Option<usize> {
+    match genre {
+        "caisse" => Some(1),
+        "cheque" | "mandat" | "prelevement" | "caisse_mouvement" | "cloture" | "dormant"
+        | "signalement" => Some(2),
+        "objectif" | "regle_alerte" | "cloture_mouvement" => Some(3),
+        _ => None,
+    }
+}
+
+/// Le genre est celui d'une ligne de l'état hors comptes.
+pub(crate) fn est_genre(genre: &str) -> bool {
+    genre == SUPPRESSION || longueur_cle(genre).is_some()
+}
+
+fn cle(ligne: &str) -> Option<String> {
+    let champs: Vec<&str> = ligne.split(';').map(str::trim).collect();
+    let longueur = longueur_cle(champs[0])?;
+    (champs.len() >= longueur).then(|| champs[..longueur].join(";"))
+}
+
+/// Applique une ligne à `etat` ; faux si ce n'est pas une ligne d'état valide.
+fn appliquer(etat: &mut Etat, ligne: &str) -> bool {
+    if let Some(supprimee) = ligne
+        .strip_prefix(SUPPRESSION)
+        .and_then(|l| l.strip_prefix(';'))
+    {
+        return match cle(supprimee) {
+            Some(cle) if cle == supprimee.trim() => {
+                etat.remove(&cle);
+                true
+            }
+            _ => false,
+        };
+    }
+    match cle(ligne) {
+        Some(cle) => {
+            etat.insert(cle, ligne.to_string());
+            true
+        }
+        None => false,
+    }
+}
+
+/// Lit les lignes d'état d'un jeu de données, dans l'ordre, en ignorant les autres.
+pub(crate) fn lire(contenu: &str) -> Result<Etat, ErreurBancaire> {
+    let mut etat = Etat::new();
+    for (i, ligne) in contenu.lines().enumerate() {
+        let ligne = ligne.trim();
+        let genre = ligne.split(';').next().unwrap_or_default().trim();
+        if ligne.starts_with('#') || !est_genre(genre) {
+            continue;
+        }
+        if !appliquer(&mut etat, ligne) {
+            return Err(ErreurBancaire::Fichier(tr!(
+                "fixture.erreur_ligne",
+                i + 1,
+                tr!("fixture.ligne_invalide", ligne)
+            )));
+        }
+    }
+    Ok(etat)
+}
+
+/// Lignes qui font passer de l'état `avant` à l'état `apres`.
+pub(crate) fn differences(avant: &Etat, apres: &Etat) -> Vec<String> {
+    let mut lignes: Vec<String> = avant
+        .keys()
+        .filter(|cle| !apres.contains_key(*cle))
+        .map(|cle| format!("{};{}", SUPPRESSION, cle))
+        .collect();
+    lignes.extend(
+        apres
+            .iter()
+            .filter(|(cle, ligne)| avant.get(*cle) != Some(ligne))
+            .map(|(_, ligne)| ligne.clone()),
+    );
+    lignes
+}
+
+/// Élément de l'état repris par une ligne.
+pub(crate) trait Element: Sized {
+    fn champs(&self) -> Vec<String>;
+    fn depuis_champs(champs: &[&str]) -> Option<Self>;
+}
+
+/// Inscrit `element` dans `etat` sous la clé `cle`, genre en tête.
+pub(crate) fn inscrire(etat: &mut Etat, cle: &[&dyn Display], element: &impl Element) {
+    let cle: Vec<String> = cle
+        .iter()
+        .map(|champ| self::champ(&champ.to_string()))
+        .collect();
+    let cle = cle.join(";");
+    let mut ligne = cle.clone();
+    for champ in element.champs() {
+        ligne.push(';');
+        ligne.push_str(&self::champ(&champ));
+    }
+    etat.insert(cle, ligne);
+}
+
+/// Retire de `etat` et retourne les éléments dont la clé commence par les
+/// champs `cle`, genre en tête.
+pub(crate) fn extraire(etat: &mut Etat, cle: &[&dyn Display]) -> Etat {
+    let cle: Vec<String> = cle
+        .iter()
+        .map(|champ| self::champ(&champ.to_string()))
+        .collect();
+    let cle = cle.join(";");
+    let retirees: Vec<String> = etat
+        .range(cle.clone()..)
+        .map(|(existante, _)| existante)
+        .take_while(|existante| existante.starts_with(&cle))
+        .filter(|existante| existante.len() == cle.len() || existante[cle.len()..].starts_with(';'))
+        .cloned()
+        .collect();
+    retirees
+        .into_iter()
+        .filter_map(|existante| etat.remove_entry(&existante))
+        .collect()
+}
+
+/// Éléments du genre `genre` avec les champs de leur clé qui suivent le genre,
+/// dans l'ordre de leurs rangs.
+pub(crate) fn elements<T: Element>(
+    etat: &Etat,
+    genre: &str,
+) -> Result<Vec<(Vec<String>, T)>, ErreurBancaire> {
+    let longueur = longueur_cle(genre).unwrap_or(1);
+    let mut elements = Vec::new();
+    for ligne in etat.values() {
+        let champs: Vec<&str> = ligne.split(';').map(str::trim).collect();
+        if champs[0] != genre {
+            continue;
+        }
+        let element = T::depuis_champs(&champs[longueur..])
+            .ok_or_else(|| ErreurBancaire::Fichier(tr!("fixture.ligne_invalide", ligne)))?;
+        let cle: Vec<String> = champs[1..longueur].iter().map(|c| c.to_string()).collect();
+        elements.push((cle, element));
+    }
+    elements.sort_by_key(|(cle, _)| {
+        cle.iter()
+            .map(|champ| (champ.parse::<usize>().ok(), champ.clone()))
+            .collect::<Vec<_>>()
+    });
+    Ok(elements)
+}
+
+/// Liste des éléments du genre `genre`, rangés par leur rang.
+pub(crate) fn liste<T: Element>(etat: &Etat, genre: &str) -> Result<Vec<T>, ErreurBancaire> {
+    Ok(elements(etat, genre)?
+        .into_iter()
+        .map(|(_, element)| element)
+        .collect())
+}
+
+/// Listes des éléments du genre `genre`, par premier champ de leur clé.
+pub(crate) fn listes<T: Element>(
+    etat: &Etat,
+    genre: &str,
+) -> Result<HashMap<String, Vec<T>>, ErreurBancaire> {
+    let mut listes: HashMap<String, Vec<T>> = HashMap::new();
+    for (mut cle, element) in elements(etat, genre)? {
+        listes.entry(cle.swap_remove(0)).or_default().push(element);
+    }
+    Ok(listes)
+}
+
+fn date(texte: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(texte, FORMAT_DATE).ok()
+}
+
+fn date_heure(texte: &str) -> Option<NaiveDateTime> {
+    NaiveDateTime::parse_from_str(texte, FORMAT_DATE_HEURE).ok()
+}
+
+fn montant(texte: &str) -> Option<f32> {
+    texte
+        .parse::<f32>()
+        .ok()
+        .filter(|montant| montant.is_finite())
+}
+
+/// Élément sans autre champ que sa clé, comme un compte dormant.
+impl Element for () {
+    fn champs(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    fn depuis_champs(champs: &[&str]) -> Option<Self> {
+        champs.is_empty().then_some(())
+    }
+}
+
+impl Element for Cheque {
+    fn champs(&self) -> Vec<String> {
+        vec![
+            self.compte.clone(),
+            self.numero.clone(),
+            self.emetteur.clone(),
+            self.montant.to_string(),
+            self.remis_le.format(FORMAT_DATE).to_string(),
+            self.disponible_le.format(FORMAT_DATE).to_string(),
+            self.etat.code().to_string(),
+        ]
+    }
+
+    fn depuis_champs(champs: &[&str]) -> Option<Self> {
+        let [
+            compte,
+            numero,
+            emetteur,
+            montant,
+            remis_le,
+            disponible_le,
+            etat,
+        ] = champs[..]
+        else {
+            return None;
+        };
+        Some(Cheque {
+            compte: compte.to_string(),
+            numero: numero.to_string(),
+            emetteur: emetteur.to_string(),
+            montant: self::montant(montant)?,
+            remis_le: date(remis_le)?,
+            disponible_le: date(disponible_le)?,
+            etat: EtatCheque::depuis_code(etat)?,
+        })
+    }
+}
+
+impl Element for Mandat {
+    fn champs(&self) -> Vec<String> {
+        vec![
+            self.compte.clone(),
+            self.creancier.clone(),
+            self.reference.clone(),
+            self.signe_le.format(FORMAT_DATE).to_string(),
+            self.montant_max.to_string(),
+            self.revoque_le
+                .map(|date| date.format(FORMAT_DATE).to_string())
+                .unwrap_or_default(),
+        ]
+    }
+
+    fn depuis_champs(champs: &[&str]) -> Option<Self> {
+        let [
+            compte,
+            creancier,
+            reference,
+            signe_le,
+            montant_max,
+            revoque_le,
+        ] = champs[..]
+        else {
+            return None;
+        };
+        Some(Mandat {
+            compte: compte.to_string(),
+            creancier: creancier.to_string(),
+            reference: reference.to_string(),
+            signe_le: date(signe_le)?,
+            montant_max: montant(montant_max)?,
+            revoque_le: match revoque_le {
+                "" => None,
+                revoque_le => Some(date(revoque_le)?),
+            },
+        })
+    }
+}
+
+impl Element for Prelevement {
+    fn champs(&self) -> Vec<String> {
+        vec![
+            self.compte.clone(),
+            self.creancier.clone(),
+            self.reference.clone(),
+            self.montant.to_string(),
+            self.date.format(FORMAT_DATE).to_string(),
+            self.rembourse.to_string(),
+        ]
+    }
+
+    fn depuis_champs(champs: &[&str]) -> Option<Self> {
+        let [compte, creancier, reference, montant, date, rembourse] = champs[..] else {
+            return None;
+        };
+        Some(Prelevement {
+            compte: compte.to_string(),
+            creancier: creancier.to_string(),
+            reference: reference.to_string(),
+            montant: self::montant(montant)?,
+            date: self::date(date)?,
+            rembourse: rembourse.parse().ok()?,
+        })
+    }
+}
+
+impl Element for ObjectifEpargne {
+    fn champs(&self) -> Vec<String> {
+        let mut champs = vec![
+            self.nom.clone(),
+            self.montant_cible.to_string(),
+            self.date_limite.format(FORMAT_DATE).to_string(),
+        ];
+        if let Some(virement) = &self.virement {
+            champs.push(virement.source.clone());
+            champs.push(virement.montant.to_string());
+            champs.push(virement.prochaine_date.format(FORMAT_DATE).to_string());
+        }
+        champs
+    }
+
+    fn depuis_champs(champs: &[&str]) -> Option<Self> {
+        let (&[nom, montant_cible, date_limite], virement) = champs.split_first_chunk()?;
+        let virement = match virement {
+            [] => None,
+            [source, montant, prochaine_date] => Some(VirementPermanent {
+                source: source.to_string(),
+                montant: self::montant(montant)?,
+                prochaine_date: date(prochaine_date)?,
+            }),
+            _ => return None,
+        };
+        Some(ObjectifEpargne {
+            nom: nom.to_string(),
+            montant_cible: montant(montant_cible)?,
+            date_limite: date(date_limite)?,
+            virement,
+        })
+    }
+}
+
+impl Element for RegleAlerte {
+    fn champs(&self) -> Vec<String> {
+        vec![self.code()]
+    }
+
+    fn depuis_champs(champs: &[&str]) -> Option<Self> {
+        match champs {
+            [code] => RegleAlerte::depuis_code(code),
+            _ => None,
+        }
+    }
+}
+
+/// Caisse ouverte, sans ses mouvements, repris par les lignes `caisse_mouvement`.
+impl Element for SessionCaisse {
+    fn champs(&self) -> Vec<String> {
+        vec![
+            self.guichetier.clone(),
+            self.ouverte_le.format(FORMAT_DATE_HEURE).to_string(),
+            self.fond.to_string(),
+        ]
+    }
+
+    fn depuis_champs(champs: &[&str]) -> Option<Self> {
+        let [guichetier, ouverte_le, fond] = champs[..] else {
+            return None;
+        };
+        Some(SessionCaisse::new(
+            guichetier.to_string(),
+            montant(fond)?,
+            date_heure(ouverte_le)?,
+        ))
+    }
+}
+
+impl Element for MouvementCaisse {
+    fn champs(&self) -> Vec<String> {
+        vec![
+            self.date.format(FORMAT_DATE_HEURE).to_string(),
+            self.guichetier.clone(),
+            self.numero.clone(),
+            self.montant.to_string(),
+        ]
+    }
+
+    fn depuis_champs(champs: &[&str]) -> Option<Self> {
+        let [date, guichetier, numero, montant] = champs[..] else {
+            return None;
+        };
+        Some(MouvementCaisse {
+            date: date_heure(date)?,
+            guichetier: guichetier.to_string(),
+            numero: numero.to_string(),
+            montant: self::montant(montant)?,
+        })
+    }
+}
+
+/// Clôture, sans les mouvements de sa caisse, repris par les lignes `cloture_mouvement`.
+impl Element for RapportCloture {
+    fn champs(&self) -> Vec<String> {
+        let mut champs = self.session.champs();
+        champs.push(self.cloturee_le.format(FORMAT_DATE_HEURE).to_string());
+        champs.push(self.especes_comptees.to_string());
+        champs
+    }
+
+    fn depuis_champs(champs: &[&str]) -> Option<Self> {
+        let (session, &[cloturee_le, especes_comptees]) = champs.split_last_chunk()?;
+        Some(RapportCloture {
+            session: SessionCaisse::depuis_champs(session)?,
+            cloturee_le: date_heure(cloturee_le)?,
+            especes_comptees: montant(especes_comptees)?,
+        })
+    }
+}
+
+impl Element for Signalement {
+    fn champs(&self) -> Vec<String> {
+        let mut champs = vec![
+            self.date.format(FORMAT_DATE_HEURE).to_string(),
+            self.numero.clone(),
+            self.operation.clone(),
+            self.decision.code().to_string(),
+            self.confirmee.to_string(),
+            self.examine.to_string(),
+        ];
+        champs.extend(self.motifs.iter().cloned());
+        champs
+    }
+
+    fn depuis_champs(champs: &[&str]) -> Option<Self> {
+        let (&[date, numero, operation, decision, confirmee, examine], motifs) =
+            champs.split_first_chunk()?;
+        Some(Signalement {
+            date: date_heure(date)?,
+            numero: numero.to_string(),
+            operation: operation.to_string(),
+            decision: Decision::depuis_code(decision)?,
+            motifs: motifs.iter().map(|motif| motif.to_string()).collect(),
+            confirmee: confirmee.parse().ok()?,
+            examine: examine.parse().ok()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn differences_rejouees_redonnent_l_etat() {
+        let mut avant = Etat::new();
+        inscrire(&mut avant, &[&"dormant", &"12345"], &());
+        inscrire(
+            &mut avant,
+            &[&"regle_alerte", &"12345", &0],
+            &RegleAlerte::SoldeBas(100.0),
+        );
+        inscrire(
+            &mut avant,
+            &[&"regle_alerte", &"12345", &1],
+            &RegleAlerte::RetraitImportant(500.0),
+        );
+        let mut apres = avant.clone();
+        apres.remove("dormant;12345");
+        inscrire(
+            &mut apres,
+            &[&"regle_alerte", &"12345", &1],
+            &RegleAlerte::SoldeBas(50.0),
+        );
+        inscrire(&mut apres, &[&"dormant", &"67890"], &());
+
+        let lignes = differences(&avant, &apres);
+        assert_eq!(
+            lignes,
+            [
+                "supprime;dormant;12345",
+                "dormant;67890",
+                "regle_alerte;12345;1;solde_bas:50"
+            ]
+        );
+        let journal: Vec<String> = avant.values().cloned().chain(lignes).collect();
+        assert_eq!(lire(&journal.join("\n")).unwrap(), apres);
+
+        let regles: HashMap<String, Vec<RegleAlerte>> = listes(&apres, "regle_alerte").unwrap();
+        assert_eq!(regles["12345"].len(), 2);
+        assert!(lire("supprime;dormant").is_err());
+        assert!(lire("cheque;0;12345").is_ok());
+        assert!(liste::<Cheque>(&lire("cheque;0;12345").unwrap(), "cheque").is_err());
+    }
+
+    #[test]
+    fn elements_relus_a_l_identique() {
+        let date = NaiveDate::from_ymd_opt(2025, 1, 6).unwrap();
+        let mut etat = Etat::new();
+        let objectif = ObjectifEpargne {
+            nom: "Vacances".to_string(),
+            montant_cible: 1000.0,
+            date_limite: date,
+            virement: Some(VirementPermanent {
+                source: "67890".to_string(),
+                montant: 50.0,
+                prochaine_date: date,
+            }),
+        };
+        inscrire(&mut etat, &[&"objectif", &"12345", &0], &objectif);
+        let mut mandats = Vec::new();
+        for (i, revoque_le) in [None, Some(date)].into_iter().enumerate() {
+            let mandat = Mandat {
+                compte: "12345".to_string(),
+                creancier: "EDF; Énergie".to_string(),
+                reference: format!("M{}", i),
+                signe_le: date,
+                montant_max: 120.5,
+                revoque_le,
+            };
+            inscrire(&mut etat, &[&"mandat", &i], &mandat);
+            mandats.push(mandat);
+        }
+        let signalement = Signalement {
+            date: date.and_hms_opt(10, 30, 0).unwrap(),
+            numero: "12345".to_string(),
+            operation: "Retrait".to_string(),
+            decision: Decision::Confirmer,
+            motifs: vec!["a".to_string(), "b".to_string()],
+            confirmee: true,
+            examine: false,
+        };
+        inscrire(&mut etat, &[&"signalement", &0], &signalement);
+
+        let relu = lire(&etat.values().cloned().collect::<Vec<_>>().join("\n")).unwrap();
+        let objectifs: HashMap<String, Vec<ObjectifEpargne>> = listes(&relu, "objectif").unwrap();
+        assert_eq!(objectifs["12345"], [objectif]);
+        mandats[0].creancier = "EDF, Énergie".to_string();
+        mandats[1].creancier = "EDF, Énergie".to_string();
+        assert_eq!(liste::<Mandat>(&relu, "mandat").unwrap(), mandats);
+        let relu: Vec<Signalement> = liste(&relu, "signalement").unwrap();
+        assert_eq!(relu[0].motifs, signalement.motifs);
+        assert_eq!(relu[0].decision, Decision::Confirmer);
+        assert!(relu[0].confirmee && !relu[0].examine);
+    }
+}
@@ -0,0 +1,329 @@
+//! Récapitulatif fiscal annuel d'un client : intérêts perçus et payés, frais
+//! et solde au 31 décembre de chacun de ses comptes, avec leur total. Tous les
+//! comptes sont tenus en [`DEVISE_COMPTES`], rappelée sur chaque ligne.
+//!
+//! Les intérêts perçus et les frais sont les mouvements produits par la banque
+//! sous l'un des libellés [`LIBELLES_INTERETS`] et [`LIBELLES_FRAIS`], quelles que
+//! soient leurs catégories ; les intérêts payés sont ceux des échéances de prêt
+//! réglées dans l'année. Le capital restant dû d'un prêt est une dette : il est
+//! donné à part et n'entre pas dans les soldes.
+
+use crate::compte::CompteBancaire;
+use crate::gestionnaire::DEVISE_COMPTES;
+use crate::rapport::champ_csv;
+use crate::transaction::Libelle;
+use crate::{langue, tr};
+use chrono::{Datelike, NaiveDate};
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::Path;
+
+/// Clés des libellés des intérêts crédités par la banque ; un jeu de données
+/// les écrit `@libelle.interets_crediteurs`.
+pub const LIBELLES_INTERETS: [&str; 1] = ["libelle.interets_crediteurs"];
+/// Clés des libellés des frais prélevés par la banque.
+pub const LIBELLES_FRAIS: [&str; 1] = ["libelle.frais_rejet_cheque"];
+
+/// Montants de l'année pour un compte, ou leur total.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MontantsFiscaux {
+    pub interets_percus: f32,
+    pub interets_payes: f32,
+    pub frais: f32,
+    /// Solde des comptes courants ; nul pour un prêt.
+    pub solde_fin_annee: f32,
+    /// Capital restant dû au 31 décembre sur un prêt.
+    pub capital_restant_du: f32,
+}
+
+impl MontantsFiscaux {
+    fn ajouter(&mut self, autre: &MontantsFiscaux) {
+        self.interets_percus += autre.interets_percus;
+        self.interets_payes += autre.interets_payes;
+        self.frais += autre.frais;
+        self.solde_fin_annee += autre.solde_fin_annee;
+        self.capital_restant_du += autre.capital_restant_du;
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LigneFiscale {
+    pub numero: String,
+    pub montants: MontantsFiscaux,
+}
+
+/// Récapitulatif d'un client pour une année civile.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecapitulatifFiscal {
+    /// Identifiant du client, qui distingue deux titulaires homonymes.
+    pub client: String,
+    pub titulaire: String,
+    pub annee: i32,
+    /// Une ligne par compte ouvert avant la fin de l'année, triées par numéro.
+    pub lignes: Vec<LigneFiscale>,
+}
+
+/// Calcule le récapitulatif de `annee` pour les comptes donnés, qui sont ceux
+/// du client `client`. Les comptes ouverts après l'année sont ignorés.
+pub fn recapitulatif<'a>(
+    client: &str,
+    annee: i32,
+    comptes: impl Iterator<Item = &'a CompteBancaire>,
+) -> RecapitulatifFiscal {
+    let comptes: Vec<&CompteBancaire> = comptes.collect();
+    let titulaire = comptes
+        .first()
+        .map(|compte| compte.titulaire().to_string())
+        .unwrap_or_default();
+    let fin_annee = NaiveDate::from_ymd_opt(annee, 12, 31).unwrap_or(NaiveDate::MAX);
+    let dans_l_annee = |date: NaiveDate| date.year() == annee;
+
+    let mut lignes: Vec<LigneFiscale> = comptes
+        .into_iter()
+        .filter(|compte| {
+            compte
+                .historique()
+                .first()
+                .is_none_or(|ouverture| ouverture.date.date() <= fin_annee)
+        })
+        .map(|compte| {
+            let mut montants = MontantsFiscaux::default();
+            match compte.pret() {
+                // `0.0 -` plutôt qu'une négation, pour ne pas afficher « -0,00 ».
+                Some(_) => montants.capital_restant_du = 0.0 - compte.solde_au(fin_annee),
+                None => montants.solde_fin_annee = compte.solde_au(fin_annee),
+            }
+            for t in compte
+                .historique()
+                .iter()
+                .filter(|t| dans_l_annee(t.date.date()))
+            {
+                let Libelle::Banque { cle, .. } = &t.libelle else {
+                    continue;
+                };
+                if LIBELLES_INTERETS.contains(cle) {
+                    montants.interets_percus += t.montant;
+                } else if LIBELLES_FRAIS.contains(cle) {
+                    montants.frais -= t.montant;
+                }
+            }
+            if let Some(pret) = compte.pret() {
+                montants.interets_payes = pret
+                    .echeances_reglees()
+                    .filter(|(_, paye_le)| dans_l_annee(*paye_le))
+                    .fold(0.0, |total, (echeance, _)| total + echeance.interets);
+            }
+            LigneFiscale {
+                numero: compte.numero().to_string(),
+                montants,
+            }
+        })
+        .collect();
+    lignes.sort_by(|a, b| a.numero.cmp(&b.numero));
+
+    RecapitulatifFiscal {
+        client: client.to_string(),
+        titulaire,
+        annee,
+        lignes,
+    }
+}
+
+impl RecapitulatifFiscal {
+    /// Totaux de tous les comptes du client.
+    pub fn totaux(&self) -> MontantsFiscaux {
+        let mut totaux = MontantsFiscaux::default();
+        for ligne in &self.lignes {
+            totaux.ajouter(&ligne.montants);
+        }
+        totaux
+    }
+
+    pub fn afficher(&self, sortie: &mut impl Write) -> io::Result<()> {
+        writeln!(
+            sortie,
+            "=== {} ===",
+            tr!("fiscal.titre", self.titulaire, self.client, self.annee)
+        )?;
+        if self.lignes.is_empty() {
+            return writeln!(sortie, "{}", tr!("fiscal.aucun_compte"));
+        }
+        writeln!(
+            sortie,
+            "{:<10} | {:<6} | {:>15} | {:>15} | {:>10} | {:>14} | {:>18}",
+            tr!("fiscal.compte"),
+            tr!("fiscal.devise"),
+            tr!("fiscal.interets_percus"),
+            tr!("fiscal.interets_payes"),
+            tr!("fiscal.frais"),
+            tr!("fiscal.solde_fin_annee"),
+            tr!("fiscal.capital_restant_du")
+        )?;
+        for ligne in &self.lignes {
+            afficher_ligne(&ligne.numero, &ligne.montants, sortie)?;
+        }
+        afficher_ligne(&tr!("rapport.total"), &self.totaux(), sortie)
+    }
+
+    /// Écrit le récapitulatif en CSV : une ligne par compte puis une ligne `TOTAL`.
+    pub fn exporter_csv(&self, chemin: &Path) -> io::Result<()> {
+        let mut fichier = File::create(chemin)?;
+        writeln!(
+            fichier,
+            "client,titulaire,annee,compte,devise,interets_percus,interets_payes,frais,solde_fin_annee,capital_restant_du"
+        )?;
+        let totaux = self.totaux();
+        let lignes = self
+            .lignes
+            .iter()
+            .map(|ligne| (ligne.numero.as_str(), &ligne.montants))
+            .chain([("TOTAL", &totaux)]);
+        for (compte, montants) in lignes {
+            writeln!(
+                fichier,
+                "{},{},{},{},{},{:.2},{:.2},{:.2},{:.2},{:.2}",
+                champ_csv(&self.client),
+                champ_csv(&self.titulaire),
+                self.annee,
+                champ_csv(compte),
+                DEVISE_COMPTES,
+                montants.interets_percus,
+                montants.interets_payes,
+                montants.frais,
+                montants.solde_fin_annee,
+                montants.capital_restant_du
+            )?;
+        }
+        Ok(())
+    }
+}
+
+fn afficher_ligne(
+    compte: &str,
+    montants: &MontantsFiscaux,
+    sortie: &mut impl Write,
+) -> io::Result<()> {
+    writeln!(
+        sortie,
+        "{:<10} | {:<6} | {:>15} | {:>15} | {:>10} | {:>14} | {:>18}",
+        compte,
+        DEVISE_COMPTES,
+        langue::nombre(montants.interets_percus),
+        langue::nombre(montants.interets_payes),
+        langue::nombre(montants.frais),
+        langue::nombre(montants.solde_fin_annee),
+        langue::nombre(montants.capital_restant_du)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pret::{ModeAmortissement, Pret};
+    use crate::transaction::Transaction;
+
+    fn interets() -> Libelle {
+        Libelle::banque("libelle.interets_crediteurs", &[])
+    }
+
+    fn frais() -> Libelle {
+        Libelle::banque("libelle.frais_rejet_cheque", &[&"0000123"])
+    }
+
+    fn compte(numero: &str, mouvements: &[(&str, f32, Libelle)]) -> CompteBancaire {
+        let mut compte = CompteBancaire::ouvert_le(
+            numero.to_string(),
+            "Alice Dupont".to_string(),
+            100.0,
+            NaiveDate::from_ymd_opt(2025, 1, 2)
+                .unwrap()
+                .and_hms_opt(9, 0, 0)
+                .unwrap(),
+        );
+        for (date, montant, libelle) in mouvements {
+            let date = NaiveDate::parse_from_str(date, "%d/%m/%Y")
+                .unwrap()
+                .and_hms_opt(12, 0, 0)
+                .unwrap();
+            compte.solde += montant;
+            compte
+                .historique
+                .push(Transaction::new(date, libelle.clone(), *montant));
+        }
+        compte
+    }
+
+    #[test]
+    fn interets_frais_et_solde_de_l_annee() {
+        let comptes = [
+            compte(
+                "B2",
+                &[
+                    ("31/12/2025", 12.5, interets()),
+                    ("15/03/2025", -8.0, frais()),
+                    ("01/01/2026", 20.0, interets()),
+                    // Un libellé saisi n'est ni un intérêt ni un frais de la banque.
+                    ("20/03/2025", -5.0, Libelle::from("Frais de dossier")),
+                ],
+            ),
+            compte("A1", &[("10/06/2025", -30.0, frais())]),
+        ];
+        let resume = recapitulatif("C1", 2025, comptes.iter());
+
+        assert_eq!(resume.lignes[0].numero, "A1");
+        let b2 = &resume.lignes[1].montants;
+        assert_eq!(b2.interets_percus, 12.5);
+        assert_eq!(b2.frais, 8.0);
+        assert_eq!(b2.solde_fin_annee, 99.5);
+
+        assert_eq!(resume.titulaire, "Alice Dupont");
+        let totaux = resume.totaux();
+        assert_eq!(totaux.frais, 38.0);
+        assert_eq!(totaux.solde_fin_annee, 169.5);
+
+        // Les comptes ouverts après l'année ne figurent pas au récapitulatif.
+        assert!(recapitulatif("C1", 2024, comptes.iter()).lignes.is_empty());
+    }
+
+    #[test]
+    fn pret_interets_a_la_date_de_paiement_et_capital_du_a_part() {
+        let jour = |j, m, a| {
+            NaiveDate::from_ymd_opt(a, m, j)
+                .unwrap()
+                .and_hms_opt(12, 0, 0)
+                .unwrap()
+        };
+        let pret = Pret::new(
+            1200.0,
+            12.0,
+            12,
+            ModeAmortissement::AmortissementConstant,
+            "A1".to_string(),
+            jour(15, 12, 2025).date(),
+        );
+        let mut compte_pret = CompteBancaire::nouveau_pret(
+            "P1".to_string(),
+            "Alice Dupont".to_string(),
+            pret,
+            jour(15, 12, 2025),
+        );
+        // La première échéance, prévue le 15/01/2026, est réglée dès le 31/12/2025.
+        compte_pret.enregistrer_le(jour(31, 12, 2025), 100.0, "Échéance");
+        if let crate::compte::TypeCompte::Pret(pret) = &mut compte_pret.type_compte {
+            pret.marquer_payee(jour(31, 12, 2025).date());
+        }
+        let comptes = [compte("A1", &[]), compte_pret];
+
+        let resume = recapitulatif("C1", 2025, comptes.iter());
+        let p1 = &resume.lignes[1].montants;
+        assert_eq!(p1.interets_payes, 12.0);
+        assert_eq!(p1.capital_restant_du, 1100.0);
+        assert_eq!(p1.solde_fin_annee, 0.0);
+        let totaux = resume.totaux();
+        assert_eq!(totaux.solde_fin_annee, 100.0);
+        assert_eq!(totaux.capital_restant_du, 1100.0);
+
+        let suivante = recapitulatif("C1", 2026, comptes.iter());
+        assert_eq!(suivante.lignes[1].montants.interets_payes, 0.0);
+    }
+}
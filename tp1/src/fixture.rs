@@ -0,0 +1,640 @@
+//! Jeux de données : chargement de clients, comptes et mouvements passés
+//! depuis un fichier texte, et génération de gros jeux reproductibles.
+//!
+//! Une ligne par élément, champs séparés par `;`, montants avec un point décimal :
+//!
+//! ```text
+//! client;C1;Alice Dupont
+//! compte;12345;C1;02/01/2025;1000.00
+//! compte;67890;C1;03/01/2025 10:15;50.00
+//! mouvement;12345;05/01/2025 09:00;2100.00;Salaire
+//! mouvement;12345;08/01/2025 18:30;-45.20;Courses Carrefour;Alimentation
+//! pret;P1;12345;10/01/2025 14:00;1200.00;3.5;12;annuite
+//! echeance;P1;10/02/2025
+//! anticipation;P1;200.00
+//! categorie;12345;3;Loisirs
+//! ```
+//!
+//! Un compte référence un client déclaré plus haut et s'ouvre à une date,
+//! avec ou sans heure ; un mouvement référence un compte déclaré plus haut ; la catégorie d'un mouvement est facultative. Les lignes
+//! vides et celles commençant par `#` sont ignorées. Un libellé ou une catégorie
+//! produit par la banque s'écrit `@cle|argument|…` (voir [`Libelle::code`]) et
+//! s'affiche dans la langue courante.
+//!
+//! Un prêt est ouvert sur un compte courant déclaré plus haut, avec sa date
+//! d'ouverture, son capital, son taux annuel, sa durée en mois et son mode
+//! (`annuite` ou `amortissement`). `echeance` marque payée sa prochaine échéance,
+//! `anticipation` en rembourse une partie du capital. `categorie` remplace la
+//! catégorie du mouvement de ce rang (à partir de 1) dans l'historique trié.
+//! Les lignes de l'état hors comptes (voir [`etat`](crate::etat)) sont ignorées.
+
+use crate::compte::{CompteBancaire, TypeCompte};
+use crate::erreur::ErreurBancaire;
+use crate::etat;
+use crate::pret::{ModeAmortissement, Pret};
+use crate::tr;
+use crate::transaction::Libelle;
+use chrono::{Datelike, Days, Months, NaiveDate, NaiveDateTime};
+use std::collections::HashMap;
+use std::fmt::Write;
+
+const FORMAT_DATE: &str = "%d/%m/%Y";
+const FORMAT_DATE_HEURE: &str = "%d/%m/%Y %H:%M";
+
+fn erreur(ligne: usize, message: String) -> ErreurBancaire {
+    ErreurBancaire::Fichier(tr!("fixture.erreur_ligne", ligne, message))
+}
+
+fn lire_montant(ligne: usize, texte: &str) -> Result<f32, ErreurBancaire> {
+    texte
+        .trim()
+        .parse::<f32>()
+        .ok()
+        .filter(|montant| montant.is_finite())
+        .ok_or_else(|| erreur(ligne, tr!("fixture.montant_invalide", texte)))
+}
+
+fn index_compte(
+    index_comptes: &HashMap<String, usize>,
+    ligne: usize,
+    numero: &str,
+) -> Result<usize, ErreurBancaire> {
+    index_comptes.get(numero).copied().ok_or_else(|| {
+        erreur(
+            ligne,
+            ErreurBancaire::CompteIntrouvable(numero.to_string()).to_string(),
+        )
+    })
+}
+
+fn pret_mut(compte: &mut CompteBancaire, ligne: usize) -> Result<&mut Pret, ErreurBancaire> {
+    match &mut compte.type_compte {
+        TypeCompte::Pret(pret) => Ok(pret),
+        TypeCompte::Courant => Err(erreur(
+            ligne,
+            ErreurBancaire::PasUnPret(compte.numero.clone()).to_string(),
+        )),
+    }
+}
+
+/// Lit un jeu de données et retourne ses comptes, triés par ordre d'apparition,
+/// chacun avec son historique trié par date.
+pub fn lire(contenu: &str) -> Result<Vec<CompteBancaire>, ErreurBancaire> {
+    let mut clients: HashMap<&str, &str> = HashMap::new();
+    let mut comptes: Vec<CompteBancaire> = Vec::new();
+    let mut index_comptes: HashMap<String, usize> = HashMap::new();
+    let mut categories = Vec::new();
+
+    for (i, ligne) in contenu.lines().enumerate() {
+        let numero_ligne = i + 1;
+        let ligne = ligne.trim();
+        if ligne.is_empty() || ligne.starts_with('#') {
+            continue;
+        }
+
+        let champs: Vec<&str> = ligne.split(';').map(str::trim).collect();
+        match champs[..] {
+            ["client", identifiant, nom] => {
+                clients.insert(identifiant, nom);
+            }
+            ["compte", numero, client, date, solde_initial] => {
+                let titulaire = clients
+                    .get(client)
+                    .ok_or_else(|| erreur(numero_ligne, tr!("fixture.client_inconnu", client)))?;
+                let date = NaiveDateTime::parse_from_str(date, FORMAT_DATE_HEURE)
+                    .or_else(|_| {
+                        NaiveDate::parse_from_str(date, FORMAT_DATE)
+                            .map(|jour| jour.and_time(Default::default()))
+                    })
+                    .map_err(|_| erreur(numero_ligne, tr!("fixture.date_invalide", date)))?;
+                if index_comptes.contains_key(numero) {
+                    return Err(erreur(
+                        numero_ligne,
+                        ErreurBancaire::CompteExistant(numero.to_string()).to_string(),
+                    ));
+                }
+                index_comptes.insert(numero.to_string(), comptes.len());
+                comptes.push(
+                    CompteBancaire::ouvert_le(
+                        numero.to_string(),
+                        titulaire.to_string(),
+                        lire_montant(numero_ligne, solde_initial)?,
+                        date,
+                    )
+                    .du_client(client),
+                );
+            }
+            [
+                "mouvement",
+                numero,
+                date,
+                montant,
+                libelle,
+                ref categorie @ ..,
+            ] if categorie.len() <= 1 => {
+                let index = index_compte(&index_comptes, numero_ligne, numero)?;
+                let date = NaiveDateTime::parse_from_str(date, FORMAT_DATE_HEURE)
+                    .map_err(|_| erreur(numero_ligne, tr!("fixture.date_invalide", date)))?;
+                let compte = &mut comptes[index];
+                compte.enregistrer_le(
+                    date,
+                    lire_montant(numero_ligne, montant)?,
+                    Libelle::depuis_code(libelle),
+                );
+                if let Some(transaction) = compte.historique.last_mut() {
+                    transaction.categorie = categorie.first().map(|c| Libelle::depuis_code(c));
+                }
+            }
+            [
+                "pret",
+                numero,
+                compte_lie,
+                date,
+                capital,
+                taux_annuel,
+                duree_mois,
+                mode,
+            ] => {
+                let lie = index_compte(&index_comptes, numero_ligne, compte_lie)?;
+                if index_comptes.contains_key(numero) {
+                    return Err(erreur(
+                        numero_ligne,
+                        ErreurBancaire::CompteExistant(numero.to_string()).to_string(),
+                    ));
+                }
+                let date = NaiveDateTime::parse_from_str(date, FORMAT_DATE_HEURE)
+                    .map_err(|_| erreur(numero_ligne, tr!("fixture.date_invalide", date)))?;
+                let (Ok(duree_mois), Some(mode)) = (
+                    duree_mois.parse::<u32>(),
+                    ModeAmortissement::depuis_code(mode),
+                ) else {
+                    return Err(erreur(numero_ligne, tr!("fixture.ligne_invalide", ligne)));
+                };
+                let pret = Pret::new(
+                    lire_montant(numero_ligne, capital)?,
+                    lire_montant(numero_ligne, taux_annuel)?,
+                    duree_mois,
+                    mode,
+                    compte_lie.to_string(),
+                    date.date(),
+                );
+                let (titulaire, client) =
+                    (comptes[lie].titulaire.clone(), comptes[lie].client.clone());
+                index_comptes.insert(numero.to_string(), comptes.len());
+                comptes.push(
+                    CompteBancaire::nouveau_pret(numero.to_string(), titulaire, pret, date)
+                        .du_client(client),
+                );
+            }
+            ["echeance", numero, date] => {
+                let index = index_compte(&index_comptes, numero_ligne, numero)?;
+                let date = NaiveDate::parse_from_str(date, FORMAT_DATE)
+                    .map_err(|_| erreur(numero_ligne, tr!("fixture.date_invalide", date)))?;
+                pret_mut(&mut comptes[index], numero_ligne)?.marquer_payee(date);
+            }
+            ["anticipation", numero, montant] => {
+                let index = index_compte(&index_comptes, numero_ligne, numero)?;
+                let montant = lire_montant(numero_ligne, montant)?;
+                pret_mut(&mut comptes[index], numero_ligne)?.rembourser_par_anticipation(montant);
+            }
+            ["categorie", numero, rang, categorie] => {
+                let index = index_compte(&index_comptes, numero_ligne, numero)?;
+                let rang = rang
+                    .parse::<usize>()
+                    .map_err(|_| erreur(numero_ligne, tr!("fixture.ligne_invalide", ligne)))?;
+                categories.push((numero_ligne, index, rang, Libelle::depuis_code(categorie)));
+            }
+            [genre, ..] if etat::est_genre(genre) => {}
+            _ => return Err(erreur(numero_ligne, tr!("fixture.ligne_invalide", ligne))),
+        }
+    }
+
+    for compte in comptes.iter_mut() {
+        compte.historique.sort_by_key(|t| t.date);
+    }
+    // Le rang d'un mouvement est celui de l'historique trié.
+    for (numero_ligne, index, rang, categorie) in categories {
+        let transaction = rang
+            .checked_sub(1)
+            .and_then(|rang| comptes[index].historique.get_mut(rang))
+            .ok_or_else(|| {
+                erreur(
+                    numero_ligne,
+                    ErreurBancaire::TransactionIntrouvable(rang).to_string(),
+                )
+            })?;
+        transaction.categorie = Some(categorie);
+    }
+    Ok(comptes)
+}
+
+// Le `;` sépare les champs d'une ligne.
+pub(crate) fn champ(texte: &str) -> String {
+    texte.replace([';', '\n', '\r'], ",").trim().to_string()
+}
+
+/// Lignes qui reprennent les mouvements d'un compte à partir du rang `depuis`
+/// de son historique ; à partir du premier, elles déclarent aussi le compte :
+/// un compte courant avec son client, un prêt avec ses conditions.
+pub(crate) fn lignes_compte(compte: &CompteBancaire, depuis: usize) -> Vec<String> {
+    let mut lignes = Vec::new();
+    let mut mouvements = compte.historique.iter().skip(depuis);
+    if depuis == 0
+        && let Some(ouverture) = mouvements.next()
+    {
+        match &compte.type_compte {
+            TypeCompte::Courant => {
+                let client = champ(&compte.client);
+                lignes.push(format!("client;{};{}", client, champ(&compte.titulaire)));
+                lignes.push(format!(
+                    "compte;{};{};{};{}",
+                    champ(&compte.numero),
+                    client,
+                    ouverture.date.format(FORMAT_DATE_HEURE),
+                    ouverture.montant
+                ));
+            }
+            TypeCompte::Pret(pret) => lignes.push(format!(
+                "pret;{};{};{};{};{};{};{}",
+                champ(&compte.numero),
+                champ(&pret.compte_lie),
+                ouverture.date.format(FORMAT_DATE_HEURE),
+                pret.capital,
+                pret.taux_annuel,
+                pret.duree_mois,
+                pret.mode.code()
+            )),
+        }
+    }
+    for t in mouvements {
+        let mut ligne = format!(
+            "mouvement;{};{};{};{}",
+            champ(&compte.numero),
+            t.date.format(FORMAT_DATE_HEURE),
+            t.montant,
+            champ(&t.libelle.code())
+        );
+        if let Some(categorie) = &t.categorie {
+            ligne.push(';');
+            ligne.push_str(&champ(&categorie.code()));
+        }
+        lignes.push(ligne);
+    }
+    lignes
+}
+
+/// Ligne qui marque payée, le `date`, la prochaine échéance du prêt.
+pub(crate) fn ligne_echeance(numero_pret: &str, date: NaiveDate) -> String {
+    format!(
+        "echeance;{};{}",
+        champ(numero_pret),
+        date.format(FORMAT_DATE)
+    )
+}
+
+/// Ligne qui rembourse par anticipation `montant` du capital du prêt.
+pub(crate) fn ligne_anticipation(numero_pret: &str, montant: f32) -> String {
+    format!("anticipation;{};{}", champ(numero_pret), montant)
+}
+
+/// Ligne qui remplace la catégorie du mouvement de rang `rang` (à partir de 1).
+pub(crate) fn ligne_categorie(numero: &str, rang: usize, categorie: &Libelle) -> String {
+    format!(
+        "categorie;{};{};{}",
+        champ(numero),
+        rang,
+        champ(&categorie.code())
+    )
+}
+
+/// Générateur pseudo-aléatoire SplitMix64. Contrairement aux générateurs d'une
+/// bibliothèque externe, sa suite ne changera pas d'une version à l'autre :
+/// une même graine redonne toujours le même jeu de données.
+pub struct Aleatoire(u64);
+
+impl Aleatoire {
+    pub fn new(graine: u64) -> Self {
+        Aleatoire(graine)
+    }
+
+    pub fn suivant(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Entier dans `[min, max]`.
+    pub fn entre(&mut self, min: u64, max: u64) -> u64 {
+        min + self.suivant() % (max - min + 1)
+    }
+
+    pub fn choisir<'a, T>(&mut self, elements: &'a [T]) -> &'a T {
+        &elements[self.entre(0, elements.len() as u64 - 1) as usize]
+    }
+}
+
+const PRENOMS: [&str; 12] = [
+    "Alice", "Bob", "Claire", "Denis", "Emma", "Farid", "Gaëlle", "Hugo", "Inès", "Julien",
+    "Karima", "Louis",
+];
+const NOMS: [&str; 12] = [
+    "Dupont", "Martin", "Bernard", "Petit", "Durand", "Leroy", "Moreau", "Simon", "Laurent",
+    "Lefebvre", "Michel", "Garcia",
+];
+// Libellé et fourchette de montant en centimes des dépenses courantes.
+const DEPENSES: [(&str, u64, u64); 8] = [
+    ("Courses Carrefour", 1500, 15000),
+    ("Restaurant", 1200, 8000),
+    ("Cinéma", 800, 3000),
+    ("Essence", 3000, 9000),
+    ("Billet SNCF", 2000, 15000),
+    ("Facture EDF", 4000, 14000),
+    ("Pharmacie", 500, 4000),
+    ("Librairie", 700, 5000),
+];
+
+fn centimes(montant: i64) -> String {
+    let signe = if montant < 0 { "-" } else { "" };
+    format!(
+        "{}{}.{:02}",
+        signe,
+        montant.abs() / 100,
+        montant.abs() % 100
+    )
+}
+
+/// Produit un jeu de données de `nombre_comptes` comptes courants couvrant
+/// les `annees` années qui précèdent `fin` : salaire et loyer chaque mois sur
+/// le compte principal de chaque client, un virement reçu sur le second, et des
+/// dépenses variées sans jamais passer sous zéro. Le résultat ne dépend que des arguments.
+pub fn generer(graine: u64, nombre_comptes: usize, annees: u32, fin: NaiveDate) -> String {
+    let mut aleatoire = Aleatoire::new(graine);
+    let mut sortie = String::new();
+    let debut = fin - Months::new(12 * annees);
+    let mut numero_compte = 0;
+    let mut numero_client = 0;
+
+    while numero_compte < nombre_comptes {
+        numero_client += 1;
+        let client = format!("C{}", numero_client);
+        let nom = format!(
+            "{} {}",
+            aleatoire.choisir(&PRENOMS),
+            aleatoire.choisir(&NOMS)
+        );
+        let _ = writeln!(sortie, "client;{};{}", client, nom);
+
+        let nombre = if aleatoire.entre(1, 10) <= 7 { 1 } else { 2 };
+        for rang in 0..nombre.min(nombre_comptes - numero_compte) {
+            numero_compte += 1;
+            let numero = format!("{:06}", 100_000 + numero_compte);
+            let ouverture = debut + Days::new(aleatoire.entre(0, 60));
+            let mut solde = aleatoire.entre(10_000, 300_000) as i64;
+            let _ = writeln!(
+                sortie,
+                "compte;{};{};{};{}",
+                numero,
+                client,
+                ouverture.format(FORMAT_DATE),
+                centimes(solde)
+            );
+
+            let salaire = aleatoire.entre(120_000, 400_000) as i64;
+            let loyer = aleatoire.entre(40_000, 120_000) as i64;
+            let mut mouvements: Vec<(NaiveDateTime, i64, &str)> = Vec::new();
+            let mut mois = ouverture.with_day(1).unwrap_or(ouverture);
+            while mois <= fin {
+                let jour = |aleatoire: &mut Aleatoire, min, max| {
+                    let date = mois + Days::new(aleatoire.entre(min, max));
+                    date.and_hms_opt(
+                        aleatoire.entre(8, 20) as u32,
+                        aleatoire.entre(0, 59) as u32,
+                        0,
+                    )
+                };
+                if rang == 0 {
+                    mouvements.extend(jour(&mut aleatoire, 0, 2).map(|d| (d, salaire, "Salaire")));
+                    mouvements.extend(jour(&mut aleatoire, 3, 5).map(|d| (d, -loyer, "Loyer")));
+                } else {
+                    mouvements.extend(
+                        jour(&mut aleatoire, 0, 2).map(|d| (d, salaire / 3, "Virement reçu")),
+                    );
+                }
+                for _ in 0..aleatoire.entre(3, 12) {
+                    let (libelle, min, max) = *aleatoire.choisir(&DEPENSES);
+                    let montant = aleatoire.entre(min, max) as i64;
+                    mouvements.extend(jour(&mut aleatoire, 0, 27).map(|d| (d, -montant, libelle)));
+                }
+                mois = mois + Months::new(1);
+            }
+
+            mouvements.sort_by_key(|(date, _, _)| *date);
+            for (date, montant, libelle) in mouvements {
+                if date.date() < ouverture || date.date() > fin || solde + montant < 0 {
+                    continue;
+                }
+                solde += montant;
+                let _ = writeln!(
+                    sortie,
+                    "mouvement;{};{};{};{}",
+                    numero,
+                    date.format(FORMAT_DATE_HEURE),
+                    centimes(montant),
+                    libelle
+                );
+            }
+        }
+    }
+    sortie
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::langue::{self, Langue};
+
+    #[test]
+    fn lecture_et_erreurs_avec_numero_de_ligne() {
+        let contenu = "\
+# Démo
+client;C1;Alice Dupont
+compte;12345;C1;02/01/2025;1000.00
+mouvement;12345;08/01/2025 18:30;-45.20;Courses;Alimentation
+mouvement;12345;05/01/2025 09:00;2100.00;Salaire
+";
+        let comptes = lire(contenu).unwrap();
+        let compte = &comptes[0];
+        assert_eq!(compte.titulaire(), "Alice Dupont");
+        assert_eq!(compte.solde(), 3054.8);
+        let libelles: Vec<String> = compte
+            .historique()
+            .iter()
+            .map(|t| t.libelle.to_string())
+            .collect();
+        assert_eq!(libelles, ["Solde initial", "Salaire", "Courses"]);
+        assert_eq!(
+            compte.historique()[2].categorie,
+            Some(Libelle::from("Alimentation"))
+        );
+
+        let inconnu = lire("client;C1;Alice\ncompte;1;C2;02/01/2025;0");
+        assert_eq!(
+            inconnu.unwrap_err().to_string(),
+            "Erreur de fichier: ligne 2 : client C2 inconnu"
+        );
+        assert!(lire("mouvement;1;02/01/2025 10:00;5;x").is_err());
+        assert!(lire("client;C1;A\ncompte;1;C1;31/02/2025;0").is_err());
+        for montant in ["NaN", "inf", "-infinity"] {
+            let ligne = format!("client;C1;A\ncompte;1;C1;02/01/2025;{}", montant);
+            assert!(lire(&ligne).is_err());
+            let ligne = format!(
+                "client;C1;A\ncompte;1;C1;02/01/2025;0\nmouvement;1;03/01/2025 10:00;{};x",
+                montant
+            );
+            assert!(lire(&ligne).is_err());
+        }
+    }
+
+    #[test]
+    fn pret_echeances_et_categories_relus() {
+        let contenu = "\
+client;C1;Alice Dupont
+compte;12345;C1;02/01/2025;1000.00
+pret;P1;12345;10/01/2025 14:00;1200;3.5;12;annuite
+echeance;P1;10/02/2025
+anticipation;P1;200
+categorie;12345;3;Loisirs
+mouvement;12345;08/01/2025 18:30;-45.20;Cinéma
+mouvement;12345;05/01/2025 09:00;2100.00;Salaire
+";
+        let comptes = lire(contenu).unwrap();
+        let TypeCompte::Pret(pret) = &comptes[1].type_compte else {
+            panic!("P1 n'est pas un prêt");
+        };
+        assert_eq!(pret.compte_lie, "12345");
+        assert_eq!(pret.mode, ModeAmortissement::AnnuiteConstante);
+        assert_eq!(
+            pret.paiements,
+            [NaiveDate::from_ymd_opt(2025, 2, 10).unwrap()]
+        );
+        assert!(pret.capital_restant < 1000.0);
+        assert_eq!(comptes[1].titulaire(), "Alice Dupont");
+        // Le rang est celui de l'historique trié : le salaire passe avant le cinéma.
+        assert_eq!(
+            comptes[0].historique()[2].categorie,
+            Some(Libelle::from("Loisirs"))
+        );
+        assert_eq!(
+            lignes_compte(&comptes[1], 0)[0],
+            contenu.lines().nth(2).unwrap()
+        );
+
+        let compte = "client;C1;A\ncompte;1;C1;02/01/2025;0\n";
+        assert!(lire(&format!("{}echeance;1;10/02/2025", compte)).is_err());
+        assert!(lire(&format!("{}categorie;1;0;x", compte)).is_err());
+        assert!(
+            lire(&format!(
+                "{}pret;P1;1;10/01/2025 14:00;100;3;12;autre",
+                compte
+            ))
+            .is_err()
+        );
+        assert!(
+            lire(&format!(
+                "{}pret;1;1;10/01/2025 14:00;100;3;12;annuite",
+                compte
+            ))
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn comptes_de_clients_homonymes_relus_avec_leur_heure_d_ouverture() {
+        let contenu = "\
+client;C1;Alice Dupont
+client;C2;Alice Dupont
+compte;12345;C1;02/01/2025 10:15;1000.00
+compte;67890;C2;03/01/2025;50.00
+pret;P1;67890;10/01/2025 14:00;1200.00;3.5;12;annuite
+";
+        let mut comptes = lire(contenu).unwrap();
+        // Un montant qui n'a pas de forme exacte à deux décimales.
+        let date = comptes[0].historique()[0].date;
+        comptes[0].enregistrer_le(date, -1.0 / 3.0, "Arrondi");
+        assert_eq!(comptes[0].client(), "C1");
+        assert_eq!(comptes[2].client(), "C2");
+        assert_eq!(
+            lignes_compte(&comptes[0], 0)[..2],
+            [
+                "client;C1;Alice Dupont",
+                "compte;12345;C1;02/01/2025 10:15;1000"
+            ]
+        );
+
+        let lignes: Vec<String> = comptes.iter().flat_map(|c| lignes_compte(c, 0)).collect();
+        let relus = lire(&lignes.join("\n")).unwrap();
+        for (compte, relu) in comptes.iter().zip(&relus) {
+            assert_eq!(relu.client(), compte.client());
+            assert_eq!(relu.historique()[0].date, compte.historique()[0].date);
+            assert_eq!(relu.solde(), compte.solde());
+        }
+    }
+
+    #[test]
+    fn libelles_de_la_banque_relus_dans_une_autre_langue() {
+        let date = NaiveDate::from_ymd_opt(2025, 1, 2)
+            .unwrap()
+            .and_hms_opt(9, 0, 0)
+            .unwrap();
+        let mut compte = CompteBancaire::ouvert_le("1".to_string(), "A".to_string(), 10.0, date);
+        compte.enregistrer_le(
+            date,
+            -5.0,
+            Libelle::banque("libelle.rejet_cheque", &[&"7|8"]),
+        );
+        compte.enregistrer_le(date, 1.0, "@moi");
+        let lignes = lignes_compte(&compte, 0);
+        assert_eq!(
+            lignes[2],
+            "mouvement;1;02/01/2025 09:00;-5;@libelle.rejet_cheque|7/8"
+        );
+
+        langue::definir(Langue::Anglais);
+        let relu = lire(&lignes.join("\n")).unwrap();
+        let libelles: Vec<String> = relu[0]
+            .historique()
+            .iter()
+            .map(|t| t.libelle.to_string())
+            .collect();
+        let categorie = relu[0].historique()[0]
+            .categorie
+            .as_ref()
+            .unwrap()
+            .to_string();
+        langue::definir(Langue::Francais);
+        assert_eq!(
+            libelles,
+            ["Opening balance", "Bounced cheque no. 7/8", "@moi"]
+        );
+        assert_eq!(categorie, "Opening");
+    }
+
+    #[test]
+    fn generation_reproductible() {
+        let fin = NaiveDate::from_ymd_opt(2026, 6, 30).unwrap();
+        let jeu = generer(7, 25, 2, fin);
+        assert_eq!(jeu, generer(7, 25, 2, fin));
+        assert_ne!(jeu, generer(8, 25, 2, fin));
+
+        let comptes = lire(&jeu).unwrap();
+        assert_eq!(comptes.len(), 25);
+        for compte in &comptes {
+            // Le solde généré ne descend jamais sous zéro, à l'arrondi des f32 près.
+            assert!(compte.solde() > -0.01);
+            assert!(compte.historique().len() > 24 * 3);
+            assert!(compte.historique().iter().all(|t| t.date.date() <= fin));
+        }
+    }
+}
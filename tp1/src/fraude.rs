@@ -0,0 +1,251 @@
+//! Détection de fraude : règles évaluées avant chaque mouvement demandé sur
+//! un compte, et file des opérations signalées en attente d'examen.
+
+use crate::compte::CompteBancaire;
+use crate::{langue, tr};
+use chrono::{NaiveDateTime, TimeDelta};
+use std::fmt;
+
+/// Suite donnée à une opération qu'une règle juge suspecte, de la plus légère
+/// à la plus stricte.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Decision {
+    /// L'opération est exécutée et ajoutée à la file d'examen.
+    Signaler,
+    /// L'opération n'est exécutée que si elle est confirmée.
+    Confirmer,
+    /// L'opération est refusée.
+    Bloquer,
+}
+
+impl Decision {
+    /// Code stable, indépendant de la langue.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Decision::Signaler => "signaler",
+            Decision::Confirmer => "confirmer",
+            Decision::Bloquer => "bloquer",
+        }
+    }
+
+    pub fn depuis_code(code: &str) -> Option<Self> {
+        [Decision::Signaler, Decision::Confirmer, Decision::Bloquer]
+            .into_iter()
+            .find(|decision| decision.code() == code)
+    }
+
+    pub fn libelle(&self) -> &'static str {
+        match self {
+            Decision::Signaler => langue::texte("fraude.signaler"),
+            Decision::Confirmer => langue::texte("fraude.confirmer"),
+            Decision::Bloquer => langue::texte("fraude.bloquer"),
+        }
+    }
+}
+
+/// Mouvement demandé sur un compte, tel que le voient les règles.
+pub struct OperationControlee<'a> {
+    pub compte: &'a CompteBancaire,
+    /// Montant du mouvement, négatif pour un débit.
+    pub montant: f32,
+    /// Devise d'un virement en devise étrangère.
+    pub devise: Option<&'a str>,
+    pub date: NaiveDateTime,
+}
+
+/// Règle de détection ; retourne le motif si l'opération lui paraît suspecte.
+pub trait RegleFraude {
+    fn evaluer(&self, operation: &OperationControlee) -> Option<String>;
+
+    /// Forme stable, indépendante de la langue, de la règle et de ses paramètres.
+    fn code(&self) -> String;
+}
+
+/// Trop de débits sur le compte dans un court intervalle.
+pub struct RetraitsRapproches {
+    pub nombre: usize,
+    pub fenetre: TimeDelta,
+}
+
+impl RegleFraude for RetraitsRapproches {
+    fn evaluer(&self, operation: &OperationControlee) -> Option<String> {
+        if operation.montant >= 0.0 {
+            return None;
+        }
+        let debut = operation.date - self.fenetre;
+        let precedents = operation
+            .compte
+            .historique()
+            .iter()
+            .filter(|t| t.montant < 0.0 && t.date >= debut)
+            .count();
+        (precedents + 1 >= self.nombre).then(|| {
+            tr!(
+                "fraude.retraits_rapproches",
+                precedents + 1,
+                self.fenetre.num_minutes()
+            )
+        })
+    }
+
+    fn code(&self) -> String {
+        format!(
+            "retraits_rapproches:{}:{}",
+            self.nombre,
+            self.fenetre.num_minutes()
+        )
+    }
+}
+
+/// Débit d'un montant juste inférieur à un plafond, à `marge` près (0.05 pour 5 %).
+pub struct JusteSousLePlafond {
+    pub plafond: f32,
+    pub marge: f32,
+}
+
+impl RegleFraude for JusteSousLePlafond {
+    fn evaluer(&self, operation: &OperationControlee) -> Option<String> {
+        let montant = -operation.montant;
+        (montant < self.plafond && montant >= self.plafond * (1.0 - self.marge)).then(|| {
+            tr!(
+                "fraude.juste_sous_le_plafond",
+                langue::montant(montant),
+                langue::montant(self.plafond)
+            )
+        })
+    }
+
+    fn code(&self) -> String {
+        format!("juste_sous_le_plafond:{}:{}", self.plafond, self.marge)
+    }
+}
+
+/// Virement en devise étrangère alors que le compte n'en a fait aucun
+/// pendant `fenetre`.
+pub struct DeviseEtrangere {
+    pub fenetre: TimeDelta,
+}
+
+impl RegleFraude for DeviseEtrangere {
+    fn evaluer(&self, operation: &OperationControlee) -> Option<String> {
+        let devise = operation.devise?;
+        let debut = operation.date - self.fenetre;
+        let habituel = operation
+            .compte
+            .historique()
+            .iter()
+            .any(|t| t.devise.is_some() && t.date >= debut);
+        (!habituel).then(|| tr!("fraude.devise_etrangere", devise))
+    }
+
+    fn code(&self) -> String {
+        format!("devise_etrangere:{}", self.fenetre.num_minutes())
+    }
+}
+
+/// Opération retenue par au moins une règle, en attente d'examen.
+#[derive(Debug, Clone)]
+pub struct Signalement {
+    pub date: NaiveDateTime,
+    pub numero: String,
+    pub operation: String,
+    pub decision: Decision,
+    pub motifs: Vec<String>,
+    /// L'opération a été confirmée malgré une règle qui le demandait.
+    pub confirmee: bool,
+    pub examine: bool,
+}
+
+impl fmt::Display for Signalement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let confirmee = if self.confirmee {
+            tr!("fraude.confirmee")
+        } else {
+            String::new()
+        };
+        write!(
+            f,
+            "[{}] {} - {} ({}{}): {}",
+            self.date.format("%d/%m/%Y %H:%M"),
+            self.numero,
+            self.operation,
+            self.decision.libelle(),
+            confirmee,
+            self.motifs.join(" ; ")
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Local;
+
+    fn operation<'a>(
+        compte: &'a CompteBancaire,
+        montant: f32,
+        devise: Option<&'a str>,
+    ) -> OperationControlee<'a> {
+        OperationControlee {
+            compte,
+            montant,
+            devise,
+            date: Local::now().naive_local(),
+        }
+    }
+
+    #[test]
+    fn regles_sur_l_historique_du_compte() {
+        let mut compte = CompteBancaire::ouvert_le(
+            "1".to_string(),
+            "A".to_string(),
+            2000.0,
+            Local::now().naive_local() - TimeDelta::days(400),
+        );
+
+        let plafond = JusteSousLePlafond {
+            plafond: 1000.0,
+            marge: 0.05,
+        };
+        assert!(plafond.evaluer(&operation(&compte, -990.0, None)).is_some());
+        assert!(
+            plafond
+                .evaluer(&operation(&compte, -1000.0, None))
+                .is_none()
+        );
+        assert!(plafond.evaluer(&operation(&compte, -900.0, None)).is_none());
+
+        compte.enregistrer(-10.0, "Retrait");
+        compte.enregistrer(-10.0, "Retrait");
+        let rapproches = RetraitsRapproches {
+            nombre: 3,
+            fenetre: TimeDelta::hours(1),
+        };
+        assert!(
+            rapproches
+                .evaluer(&operation(&compte, -10.0, None))
+                .is_some()
+        );
+        assert!(
+            rapproches
+                .evaluer(&operation(&compte, 10.0, None))
+                .is_none()
+        );
+
+        let devise = DeviseEtrangere {
+            fenetre: TimeDelta::days(365),
+        };
+        assert!(
+            devise
+                .evaluer(&operation(&compte, -50.0, Some("USD")))
+                .is_some()
+        );
+        assert!(devise.evaluer(&operation(&compte, -50.0, None)).is_none());
+        compte.historique.last_mut().unwrap().devise = Some("USD".to_string());
+        assert!(
+            devise
+                .evaluer(&operation(&compte, -50.0, Some("USD")))
+                .is_none()
+        );
+    }
+}
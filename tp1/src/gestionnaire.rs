@@ -0,0 +1,3565 @@
+//! Gestionnaire de comptes : point d'entrée de toutes les opérations.
+
+use crate::alerte::{Alerte, RegleAlerte, SortieAlerte};
+use crate::caisse::{MouvementCaisse, RapportCloture, SessionCaisse};
+use crate::categorie::{self, RegleCategorie};
+use crate::cheque::{self, Cheque, EtatCheque};
+use crate::chiffrement::Coffre;
+use crate::compte::{CompteBancaire, TypeCompte};
+use crate::dormance::{ClasseInactivite, ClassementCompte, ConditionsDormance};
+use crate::erreur::ErreurBancaire;
+use crate::etat::{self, Etat};
+use crate::fiscal::{self, RecapitulatifFiscal};
+use crate::fixture;
+use crate::fraude::{Decision, OperationControlee, RegleFraude, Signalement};
+use crate::habilitation::{self, Annuaire, Role};
+use crate::idempotence::{self, RegistreIdempotence, Rejeu, Rejouable};
+use crate::langue::Langue;
+use crate::objectif::{ObjectifEpargne, VirementExecute};
+use crate::observateur::{Evenement, Observateur};
+use crate::prelevement::{self, Mandat, Prelevement};
+use crate::pret::{Echeance, ModeAmortissement, Pret};
+use crate::recherche::{self, FiltreComptes, TriComptes};
+use crate::sauvegarde::{self, JournalOperations};
+use crate::tr;
+use crate::transaction::{Libelle, Transaction};
+use chrono::{Local, Months, NaiveDate};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::io::{self, Write};
+use std::path::Path;
+
+/// Devise dans laquelle tous les comptes sont tenus.
+pub const DEVISE_COMPTES: &str = "EUR";
+
+// Partie de l'état hors comptes, relue pour le journal des opérations
+// seulement quand une opération la modifie.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum Partie {
+    Cheques,
+    Mandats,
+    Prelevements,
+    Objectifs(String),
+    ReglesAlertes(String),
+    // Caisse ouverte et clôtures passées.
+    Caisse,
+    Dormants,
+    Signalements,
+}
+
+/// Ensemble des comptes de la banque et point d'entrée de toutes les opérations.
+///
+/// Chaque opération est soumise aux observateurs enregistrés, puis les mouvements
+/// produits sont catégorisés et confrontés aux règles d'alerte du compte.
+pub struct GestionnaireComptes {
+    comptes: HashMap<String, CompteBancaire>,
+    regles_categories: Vec<RegleCategorie>,
+    regles_alertes: HashMap<String, Vec<RegleAlerte>>,
+    sorties_alertes: Vec<Box<dyn SortieAlerte>>,
+    alertes: Vec<Alerte>,
+    objectifs: HashMap<String, Vec<ObjectifEpargne>>,
+    cheques: Vec<Cheque>,
+    delai_encaissement: u32,
+    frais_rejet_cheque: f32,
+    caisse: Option<SessionCaisse>,
+    clotures: Vec<RapportCloture>,
+    mandats: Vec<Mandat>,
+    prelevements: Vec<Prelevement>,
+    delai_remboursement: u32,
+    conditions_dormance: ConditionsDormance,
+    dormants: HashSet<String>,
+    observateurs: Vec<Box<dyn Observateur>>,
+    acteur: String,
+    idempotence: RegistreIdempotence,
+    cle_idempotence: Option<String>,
+    journal_operations: Option<JournalOperations>,
+    // Lignes propres aux comptes en attente d'inscription au journal, comptes
+    // et parties de l'état hors comptes modifiés depuis la dernière inscription,
+    // puis longueur des historiques et état hors comptes déjà inscrits.
+    lignes_journal: Vec<String>,
+    comptes_modifies: BTreeSet<String>,
+    parties_modifiees: BTreeSet<Partie>,
+    historiques_journalises: HashMap<String, usize>,
+    etat_journalise: Etat,
+    regles_fraude: Vec<(Box<dyn RegleFraude>, Decision)>,
+    signalements: Vec<Signalement>,
+    confirmation: bool,
+    utilisateurs: Option<Annuaire>,
+    role: Option<Role>,
+    seuil_approbation: Option<f32>,
+    approbation: Option<String>,
+    cours_devises: HashMap<String, f32>,
+}
+
+impl GestionnaireComptes {
+    pub fn new() -> Self {
+        GestionnaireComptes::avec_observateurs(Vec::new())
+    }
+
+    /// Crée un gestionnaire vide qui notifiera `observateurs` autour de chaque opération.
+    pub fn avec_observateurs(observateurs: Vec<Box<dyn Observateur>>) -> Self {
+        GestionnaireComptes {
+            comptes: HashMap::new(),
+            regles_categories: Vec::new(),
+            regles_alertes: HashMap::new(),
+            sorties_alertes: Vec::new(),
+            alertes: Vec::new(),
+            objectifs: HashMap::new(),
+            cheques: Vec::new(),
+            delai_encaissement: cheque::DELAI_ENCAISSEMENT_PAR_DEFAUT,
+            frais_rejet_cheque: cheque::FRAIS_REJET_PAR_DEFAUT,
+            caisse: None,
+            clotures: Vec::new(),
+            mandats: Vec::new(),
+            prelevements: Vec::new(),
+            delai_remboursement: prelevement::DELAI_REMBOURSEMENT_PAR_DEFAUT,
+            conditions_dormance: ConditionsDormance::default(),
+            dormants: HashSet::new(),
+            observateurs,
+            acteur: String::new(),
+            idempotence: RegistreIdempotence::default(),
+            cle_idempotence: None,
+            journal_operations: None,
+            lignes_journal: Vec::new(),
+            comptes_modifies: BTreeSet::new(),
+            parties_modifiees: BTreeSet::new(),
+            historiques_journalises: HashMap::new(),
+            etat_journalise: Etat::new(),
+            regles_fraude: Vec::new(),
+            signalements: Vec::new(),
+            confirmation: false,
+            utilisateurs: None,
+            role: None,
+            seuil_approbation: None,
+            approbation: None,
+            cours_devises: HashMap::new(),
+        }
+    }
+
+    // Étapes, dans l'ordre :
+    // 1. rejeu : une clé d'idempotence déjà traitée retourne le résultat d'origine ;
+    // 2. veto des observateurs ;
+    // 3. verrou de la journée clôturée ;
+    // 4. droits de l'utilisateur connecté ;
+    // 5. confirmation des comptes dormants ;
+    // 6. règles de fraude ;
+    // 7. exécution de l'opération ;
+    // 8. inscription au journal des opérations de ce qui a changé, même en cas
+    //    d'échec ;
+    // 9. résultat communiqué aux observateurs ;
+    // 10. enregistrement de la clé d'idempotence.
+    fn executer<T: Rejouable>(
+        &mut self,
+        evenement: Evenement,
+        operation: impl FnOnce(&mut Self) -> Result<T, ErreurBancaire>,
+    ) -> Result<T, ErreurBancaire> {
+        let cle = self.cle_idempotence.take();
+        let confirmee = std::mem::take(&mut self.confirmation);
+        let approbation = self.approbation.take();
+        let empreinte = idempotence::empreinte(&evenement);
+        if let Some(cle) = &cle {
+            match self.idempotence.rechercher(cle, &empreinte) {
+                Rejeu::Resultat(resultat) => {
+                    return T::decoder(&resultat).ok_or_else(|| {
+                        ErreurBancaire::Fichier(tr!("idempotence.resultat_illisible", cle))
+                    });
+                }
+                Rejeu::AutreOperation => {
+                    return Err(ErreurBancaire::CleIdempotenceReutilisee(cle.clone()));
+                }
+                Rejeu::Inconnue => {}
+            }
+        }
+
+        let refus = self
+            .observateurs
+            .iter_mut()
+            .find_map(|observateur| observateur.avant(&evenement).err());
+
+        let resultat = match refus {
+            Some(motif) => Err(ErreurBancaire::Refusee(motif)),
+            None if self.journee_cloturee()
+                && !matches!(
+                    evenement,
+                    Evenement::ChangementActeur { .. }
+                        | Evenement::Connexion { .. }
+                        | Evenement::Approbation { .. }
+                        | Evenement::SignalementExamine { .. }
+                        | Evenement::MiseEnDormance { .. }
+                        | Evenement::ConditionsChequesDefinies { .. }
+                        | Evenement::DelaiRemboursementDefini { .. }
+                        | Evenement::ConditionsDormanceDefinies { .. }
+                        | Evenement::SeuilApprobationDefini { .. }
+                        | Evenement::CoursDeviseDefini { .. }
+                        | Evenement::UtilisateursDefinis { .. }
+                        | Evenement::RegleFraudeAjoutee { .. }
+                        | Evenement::SortieAlerteAjoutee { .. }
+                ) =>
+            {
+                Err(ErreurBancaire::JourneeCloturee)
+            }
+            None => self
+                .controler_droits(&evenement, approbation.is_some())
+                .and_then(|()| self.controler_dormance(&evenement, confirmee))
+                .and_then(|reveil| {
+                    self.controler_fraude(&evenement, confirmee)?;
+                    let valeur = operation(self)?;
+                    // Le compte sort de dormance avec sa première opération réussie.
+                    if let Some(numero) = reveil {
+                        self.dormants.remove(&numero);
+                        self.modifier(Partie::Dormants);
+                    }
+                    Ok(valeur)
+                }),
+        };
+
+        self.journaliser();
+        for observateur in self.observateurs.iter_mut() {
+            observateur.apres(&evenement, resultat.as_ref().map(|_| ()));
+        }
+
+        if let (Some(cle), Ok(valeur)) = (cle, &resultat)
+            && let Err(e) = self
+                .idempotence
+                .enregistrer(&cle, empreinte, valeur.encoder())
+        {
+            self.signaler_incident(tr!("idempotence.ecriture_impossible", cle, e));
+        }
+        resultat
+    }
+
+    // Transmet aux observateurs un incident qui ne fait pas échouer l'opération.
+    fn signaler_incident(&mut self, message: String) {
+        for observateur in self.observateurs.iter_mut() {
+            observateur.incident(&message);
+        }
+    }
+
+    /// Remplace le registre des clés d'idempotence, par exemple par un registre
+    /// conservé dans un fichier.
+    pub fn definir_registre_idempotence(&mut self, registre: RegistreIdempotence) {
+        self.idempotence = registre;
+    }
+
+    // Nombre de mouvements de chaque compte.
+    fn longueurs_historiques(&self) -> HashMap<String, usize> {
+        self.comptes
+            .values()
+            .map(|c| (c.numero.clone(), c.historique.len()))
+            .collect()
+    }
+
+    // Parties qui composent l'état hors comptes.
+    fn parties(&self) -> Vec<Partie> {
+        let mut parties = vec![
+            Partie::Cheques,
+            Partie::Mandats,
+            Partie::Prelevements,
+            Partie::Caisse,
+            Partie::Dormants,
+            Partie::Signalements,
+        ];
+        parties.extend(self.objectifs.keys().cloned().map(Partie::Objectifs));
+        parties.extend(
+            self.regles_alertes
+                .keys()
+                .cloned()
+                .map(Partie::ReglesAlertes),
+        );
+        parties
+    }
+
+    // Éléments de l'état hors comptes, sous la forme inscrite au journal.
+    fn etat(&self) -> Etat {
+        let mut etat = Etat::new();
+        for partie in &self.parties() {
+            self.inscrire_partie(&mut etat, partie);
+        }
+        etat
+    }
+
+    // Inscrit dans `etat` les éléments d'une partie de l'état hors comptes.
+    fn inscrire_partie(&self, etat: &mut Etat, partie: &Partie) {
+        match partie {
+            Partie::Cheques => {
+                for (i, cheque) in self.cheques.iter().enumerate() {
+                    etat::inscrire(etat, &[&"cheque", &i], cheque);
+                }
+            }
+            Partie::Mandats => {
+                for (i, mandat) in self.mandats.iter().enumerate() {
+                    etat::inscrire(etat, &[&"mandat", &i], mandat);
+                }
+            }
+            Partie::Prelevements => {
+                for (i, prelevement) in self.prelevements.iter().enumerate() {
+                    etat::inscrire(etat, &[&"prelevement", &i], prelevement);
+                }
+            }
+            Partie::Objectifs(numero) => {
+                for (i, objectif) in self.objectifs(numero).iter().enumerate() {
+                    etat::inscrire(etat, &[&"objectif", numero, &i], objectif);
+                }
+            }
+            Partie::ReglesAlertes(numero) => {
+                for (i, regle) in self.regles_alerte(numero).iter().enumerate() {
+                    etat::inscrire(etat, &[&"regle_alerte", numero, &i], regle);
+                }
+            }
+            Partie::Caisse => {
+                if let Some(caisse) = &self.caisse {
+                    etat::inscrire(etat, &[&"caisse"], caisse);
+                    for (i, mouvement) in caisse.mouvements.iter().enumerate() {
+                        etat::inscrire(etat, &[&"caisse_mouvement", &i], mouvement);
+                    }
+                }
+                for (i, cloture) in self.clotures.iter().enumerate() {
+                    etat::inscrire(etat, &[&"cloture", &i], cloture);
+                    for (j, mouvement) in cloture.session.mouvements.iter().enumerate() {
+                        etat::inscrire(etat, &[&"cloture_mouvement", &i, &j], mouvement);
+                    }
+                }
+            }
+            Partie::Dormants => {
+                for numero in &self.dormants {
+                    etat::inscrire(etat, &[&"dormant", numero], &());
+                }
+            }
+            Partie::Signalements => {
+                for (i, signalement) in self.signalements.iter().enumerate() {
+                    etat::inscrire(etat, &[&"signalement", &i], signalement);
+                }
+            }
+        }
+    }
+
+    // Retire de l'état déjà inscrit au journal les éléments d'une partie.
+    fn extraire_partie_journalisee(&mut self, partie: &Partie) -> Etat {
+        let etat = &mut self.etat_journalise;
+        match partie {
+            Partie::Cheques => etat::extraire(etat, &[&"cheque"]),
+            Partie::Mandats => etat::extraire(etat, &[&"mandat"]),
+            Partie::Prelevements => etat::extraire(etat, &[&"prelevement"]),
+            Partie::Objectifs(numero) => etat::extraire(etat, &[&"objectif", numero]),
+            Partie::ReglesAlertes(numero) => etat::extraire(etat, &[&"regle_alerte", numero]),
+            Partie::Caisse => ["caisse", "caisse_mouvement", "cloture", "cloture_mouvement"]
+                .iter()
+                .flat_map(|genre| etat::extraire(etat, &[genre]))
+                .collect(),
+            Partie::Dormants => etat::extraire(etat, &[&"dormant"]),
+            Partie::Signalements => etat::extraire(etat, &[&"signalement"]),
+        }
+    }
+
+    // Note la partie de l'état hors comptes qu'une opération modifie, pour
+    // l'inscrire au journal à la fin de l'opération.
+    fn modifier(&mut self, partie: Partie) {
+        self.parties_modifiees.insert(partie);
+    }
+
+    // Reprend l'état hors comptes lu dans un jeu de données ; rien n'est repris
+    // si une de ses lignes est invalide.
+    fn restaurer_etat(&mut self, etat: &Etat) -> Result<(), ErreurBancaire> {
+        let cheques: Vec<Cheque> = etat::liste(etat, "cheque")?;
+        let mandats: Vec<Mandat> = etat::liste(etat, "mandat")?;
+        let prelevements: Vec<Prelevement> = etat::liste(etat, "prelevement")?;
+        let objectifs: HashMap<String, Vec<ObjectifEpargne>> = etat::listes(etat, "objectif")?;
+        let regles_alertes: HashMap<String, Vec<RegleAlerte>> = etat::listes(etat, "regle_alerte")?;
+        let caisse: Option<SessionCaisse> = etat::liste(etat, "caisse")?.pop();
+        let mouvements_caisse: Vec<MouvementCaisse> = etat::liste(etat, "caisse_mouvement")?;
+        let mut clotures: Vec<RapportCloture> = etat::liste(etat, "cloture")?;
+        let mut mouvements_clotures: HashMap<String, Vec<MouvementCaisse>> =
+            etat::listes(etat, "cloture_mouvement")?;
+        let dormants = etat::elements::<()>(etat, "dormant")?;
+        let signalements: Vec<Signalement> = etat::liste(etat, "signalement")?;
+
+        self.cheques.extend(cheques);
+        self.mandats.extend(mandats);
+        self.prelevements.extend(prelevements);
+        for (numero, liste) in objectifs {
+            self.objectifs.entry(numero).or_default().extend(liste);
+        }
+        for (numero, liste) in regles_alertes {
+            self.regles_alertes.entry(numero).or_default().extend(liste);
+        }
+        if let Some(mut caisse) = caisse {
+            caisse.mouvements = mouvements_caisse;
+            self.caisse = Some(caisse);
+        }
+        for (i, cloture) in clotures.iter_mut().enumerate() {
+            cloture.session.mouvements = mouvements_clotures
+                .remove(&i.to_string())
+                .unwrap_or_default();
+        }
+        self.clotures.extend(clotures);
+        self.dormants
+            .extend(dormants.into_iter().map(|(mut cle, ())| cle.swap_remove(0)));
+        self.signalements.extend(signalements);
+        self.parties_modifiees.extend(self.parties());
+        Ok(())
+    }
+
+    // Inscrit au journal des opérations ce qui a changé depuis sa dernière
+    // inscription, en ne relisant que les comptes et les parties de l'état
+    // modifiés : comptes ouverts et mouvements passés, lignes propres aux
+    // comptes en attente, puis éléments de l'état hors comptes.
+    fn journaliser(&mut self) {
+        let propres = std::mem::take(&mut self.lignes_journal);
+        let numeros = std::mem::take(&mut self.comptes_modifies);
+        let parties = std::mem::take(&mut self.parties_modifiees);
+        if self.journal_operations.is_none() {
+            return;
+        }
+        let mut comptes: Vec<&CompteBancaire> =
+            numeros.iter().filter_map(|n| self.comptes.get(n)).collect();
+        // Un prêt est déclaré après son compte lié.
+        comptes.sort_by_key(|c| c.pret().is_some());
+        let mut lignes: Vec<String> = comptes
+            .iter()
+            .flat_map(|c| {
+                let depuis = self.historiques_journalises.get(&c.numero).copied();
+                fixture::lignes_compte(c, depuis.unwrap_or(0))
+            })
+            .collect();
+        let longueurs: Vec<(String, usize)> = comptes
+            .iter()
+            .map(|c| (c.numero.clone(), c.historique.len()))
+            .collect();
+        self.historiques_journalises.extend(longueurs);
+        lignes.extend(propres);
+        for partie in &parties {
+            let avant = self.extraire_partie_journalisee(partie);
+            let mut apres = Etat::new();
+            self.inscrire_partie(&mut apres, partie);
+            lignes.extend(etat::differences(&avant, &apres));
+            self.etat_journalise.extend(apres);
+        }
+        if let Some(journal) = &self.journal_operations
+            && !lignes.is_empty()
+            && let Err(e) = journal.ajouter(lignes)
+        {
+            self.signaler_incident(tr!("sauvegarde.ecriture_impossible", e));
+        }
+    }
+
+    /// Associe une clé d'idempotence à la prochaine opération :
+    /// `gestionnaire.avec_cle_idempotence("import-42").depot(...)`. Si cette clé
+    /// a déjà servi pour la même opération, son résultat d'origine est retourné
+    /// sans la rejouer ; si elle a servi pour une autre opération, l'appel échoue
+    /// avec `CleIdempotenceReutilisee`.
+    pub fn avec_cle_idempotence(&mut self, cle: &str) -> &mut Self {
+        self.cle_idempotence = Some(cle.to_string());
+        self
+    }
+
+    /// Personne à l'origine des opérations suivantes, vide tant qu'elle n'est pas connue.
+    pub fn acteur(&self) -> &str {
+        &self.acteur
+    }
+
+    /// Change l'acteur des opérations suivantes ; le changement est lui-même
+    /// soumis aux observateurs, ce qui permet au journal d'audit de le suivre.
+    /// Une fois les utilisateurs définis, l'acteur est l'utilisateur connecté et
+    /// ce changement est refusé.
+    pub fn definir_acteur(&mut self, acteur: &str) -> Result<(), ErreurBancaire> {
+        let evenement = Evenement::ChangementActeur {
+            acteur: acteur.to_string(),
+        };
+        self.executer(evenement, |g| {
+            g.acteur = acteur.to_string();
+            Ok(())
+        })
+    }
+
+    /// Active le contrôle des droits : chaque opération demande alors un utilisateur
+    /// connecté dont le rôle l'autorise. Sans annuaire, tout est permis ; une fois
+    /// l'annuaire en place, seul un superviseur peut le remplacer.
+    pub fn definir_utilisateurs(&mut self, utilisateurs: Annuaire) -> Result<(), ErreurBancaire> {
+        let evenement = Evenement::UtilisateursDefinis {
+            identifiants: utilisateurs.identifiants().map(String::from).collect(),
+        };
+        self.executer(evenement, |g| {
+            g.utilisateurs = Some(utilisateurs);
+            g.role = None;
+            Ok(())
+        })
+    }
+
+    /// Connecte l'utilisateur, qui devient l'acteur des opérations suivantes.
+    /// La tentative est soumise aux observateurs, qu'elle réussisse ou non.
+    pub fn connecter(
+        &mut self,
+        identifiant: &str,
+        mot_de_passe: &str,
+    ) -> Result<Role, ErreurBancaire> {
+        let evenement = Evenement::Connexion {
+            identifiant: identifiant.to_string(),
+        };
+        self.executer(evenement, |g| {
+            let role = g
+                .utilisateurs
+                .as_ref()
+                .ok_or(ErreurBancaire::IdentifiantsInvalides)?
+                .authentifier(identifiant, mot_de_passe)?
+                .role;
+            g.acteur = identifiant.to_string();
+            g.role = Some(role);
+            Ok(())
+        })?;
+        self.role.ok_or(ErreurBancaire::NonConnecte)
+    }
+
+    /// Rôle de l'utilisateur connecté.
+    pub fn role(&self) -> Option<Role> {
+        self.role
+    }
+
+    /// Les débits (retraits, virements et prélèvements) de plus de `seuil` euros
+    /// passés par un autre rôle qu'un superviseur demandent alors l'approbation d'un superviseur. Seul un
+    /// superviseur peut changer ce seuil une fois l'annuaire en place.
+    pub fn definir_seuil_approbation(&mut self, seuil: f32) -> Result<(), ErreurBancaire> {
+        self.executer(Evenement::SeuilApprobationDefini { seuil }, |g| {
+            if !seuil.is_finite() || seuil <= 0.0 {
+                return Err(ErreurBancaire::MontantNonPositif);
+            }
+            g.seuil_approbation = Some(seuil);
+            Ok(())
+        })
+    }
+
+    /// Fixe à `cours` euros le prix d'une unité de `devise` (code ISO comme `USD`),
+    /// utilisé pour débiter les virements externes versés dans cette devise.
+    pub fn definir_cours_devise(&mut self, devise: &str, cours: f32) -> Result<(), ErreurBancaire> {
+        let devise = devise.trim().to_uppercase();
+        let evenement = Evenement::CoursDeviseDefini {
+            devise: devise.clone(),
+            cours,
+        };
+        self.executer(evenement, |g| {
+            if !cours.is_finite() || cours <= 0.0 {
+                return Err(ErreurBancaire::MontantNonPositif);
+            }
+            if devise.is_empty() || devise == DEVISE_COMPTES {
+                return Err(ErreurBancaire::DeviseInconnue(devise));
+            }
+            g.cours_devises.insert(devise, cours);
+            Ok(())
+        })
+    }
+
+    /// Contre-valeur en euros, arrondie au centime, de `montant` exprimé en `devise`.
+    pub fn en_euros(&self, montant: f32, devise: &str) -> Result<f32, ErreurBancaire> {
+        if devise == DEVISE_COMPTES {
+            return Ok(montant);
+        }
+        let cours = self
+            .cours_devises
+            .get(devise)
+            .ok_or_else(|| ErreurBancaire::DeviseInconnue(devise.to_string()))?;
+        Ok((montant * cours * 100.0).round() / 100.0)
+    }
+
+    /// Fait approuver la prochaine opération par un superviseur, qui s'identifie
+    /// avec son mot de passe : `gestionnaire.avec_approbation("sam", mdp)?.retrait(...)`.
+    /// L'approbation est soumise aux observateurs et ne vaut que pour l'opération qui suit.
+    pub fn avec_approbation(
+        &mut self,
+        superviseur: &str,
+        mot_de_passe: &str,
+    ) -> Result<&mut Self, ErreurBancaire> {
+        let evenement = Evenement::Approbation {
+            superviseur: superviseur.to_string(),
+        };
+        let description = evenement.to_string();
+        self.executer(evenement, |g| {
+            let utilisateur = g
+                .utilisateurs
+                .as_ref()
+                .ok_or(ErreurBancaire::IdentifiantsInvalides)?
+                .authentifier(superviseur, mot_de_passe)?;
+            if utilisateur.role != Role::Superviseur {
+                return Err(ErreurBancaire::NonAutorise(description));
+            }
+            g.approbation = Some(superviseur.to_string());
+            Ok(())
+        })?;
+        Ok(self)
+    }
+
+    // Vérifie que l'utilisateur connecté a le droit de soumettre l'opération et,
+    // pour un débit au-delà du seuil, qu'un superviseur l'a approuvé.
+    fn controler_droits(
+        &self,
+        evenement: &Evenement,
+        approuvee: bool,
+    ) -> Result<(), ErreurBancaire> {
+        if self.utilisateurs.is_none() {
+            return Ok(());
+        }
+        if let Evenement::ChangementActeur { .. } = evenement {
+            return Err(ErreurBancaire::NonAutorise(evenement.to_string()));
+        }
+        let Some(droit) = habilitation::droit_requis(evenement) else {
+            return Ok(());
+        };
+        let role = self.role.ok_or(ErreurBancaire::NonConnecte)?;
+        if !role.a_le_droit(droit) {
+            return Err(ErreurBancaire::NonAutorise(evenement.to_string()));
+        }
+        match (self.mouvement_demande(evenement), self.seuil_approbation) {
+            (Some((_, montant, _)), Some(seuil))
+                if -montant > seuil && role != Role::Superviseur && !approuvee =>
+            {
+                Err(ErreurBancaire::ApprobationRequise(evenement.to_string()))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Ouvre la caisse du guichet au nom de l'acteur courant, avec `fond` euros en
+    /// espèces. Tant qu'elle est ouverte, chaque dépôt et retrait y est inscrit avec
+    /// l'acteur qui l'a passé.
+    pub fn ouvrir_caisse(&mut self, fond: f32) -> Result<(), ErreurBancaire> {
+        self.executer(Evenement::OuvertureCaisse { fond }, |g| {
+            if let Some(caisse) = &g.caisse {
+                return Err(ErreurBancaire::CaisseDejaOuverte(caisse.guichetier.clone()));
+            }
+            if !fond.is_finite() || fond < 0.0 {
+                return Err(ErreurBancaire::MontantNonPositif);
+            }
+            g.caisse = Some(SessionCaisse::new(
+                g.acteur.clone(),
+                fond,
+                Local::now().naive_local(),
+            ));
+            g.modifier(Partie::Caisse);
+            Ok(())
+        })
+    }
+
+    /// Caisse ouverte, s'il y en a une.
+    pub fn caisse(&self) -> Option<&SessionCaisse> {
+        self.caisse.as_ref()
+    }
+
+    /// Ferme la caisse avec les espèces comptées et clôture la journée : toute
+    /// opération est ensuite refusée jusqu'au lendemain.
+    pub fn cloturer_caisse(
+        &mut self,
+        especes_comptees: f32,
+    ) -> Result<RapportCloture, ErreurBancaire> {
+        let evenement = Evenement::ClotureCaisse { especes_comptees };
+        self.executer(evenement, |g| {
+            let session = g.caisse.take().ok_or(ErreurBancaire::CaisseFermee)?;
+            g.clotures.push(RapportCloture {
+                session,
+                cloturee_le: Local::now().naive_local(),
+                especes_comptees,
+            });
+            g.modifier(Partie::Caisse);
+            Ok(())
+        })?;
+        self.clotures
+            .last()
+            .cloned()
+            .ok_or(ErreurBancaire::CaisseFermee)
+    }
+
+    /// Clôtures de caisse passées, de la plus ancienne à la plus récente.
+    pub fn clotures(&self) -> &[RapportCloture] {
+        &self.clotures
+    }
+
+    /// La journée en cours a été clôturée.
+    pub fn journee_cloturee(&self) -> bool {
+        let aujourd_hui = Local::now().date_naive();
+        self.clotures
+            .iter()
+            .any(|cloture| cloture.cloturee_le.date() == aujourd_hui)
+    }
+
+    // Inscrit un mouvement d'espèces dans la caisse, si elle est ouverte.
+    fn mouvement_caisse(&mut self, numero: &str, montant: f32) {
+        if let Some(caisse) = self.caisse.as_mut() {
+            caisse.mouvements.push(MouvementCaisse {
+                date: Local::now().naive_local(),
+                guichetier: self.acteur.clone(),
+                numero: numero.to_string(),
+                montant,
+            });
+            self.modifier(Partie::Caisse);
+        }
+    }
+
+    /// Ajoute une règle de fraude évaluée avant chaque mouvement demandé sur un compte,
+    /// avec la suite à donner quand elle se déclenche.
+    pub fn ajouter_regle_fraude(
+        &mut self,
+        regle: Box<dyn RegleFraude>,
+        decision: Decision,
+    ) -> Result<(), ErreurBancaire> {
+        let evenement = Evenement::RegleFraudeAjoutee {
+            regle: regle.code(),
+            decision,
+        };
+        self.executer(evenement, |g| {
+            g.regles_fraude.push((regle, decision));
+            Ok(())
+        })
+    }
+
+    /// Confirme la prochaine opération : les règles de décision `Confirmer` ne la
+    /// bloquent pas, elle reste signalée pour examen.
+    pub fn avec_confirmation(&mut self) -> &mut Self {
+        self.confirmation = true;
+        self
+    }
+
+    /// Opérations signalées par les règles de fraude, dans l'ordre, examinées ou non.
+    pub fn signalements(&self) -> &[Signalement] {
+        &self.signalements
+    }
+
+    /// Marque comme examiné le signalement `index` (à partir de 1).
+    pub fn examiner_signalement(&mut self, index: usize) -> Result<(), ErreurBancaire> {
+        self.executer(Evenement::SignalementExamine { index }, |g| {
+            let signalement = index
+                .checked_sub(1)
+                .and_then(|i| g.signalements.get_mut(i))
+                .ok_or(ErreurBancaire::SignalementIntrouvable(index))?;
+            signalement.examine = true;
+            g.modifier(Partie::Signalements);
+            Ok(())
+        })
+    }
+
+    /// Change les durées d'inactivité qui classent les comptes.
+    pub fn definir_conditions_dormance(
+        &mut self,
+        conditions: ConditionsDormance,
+    ) -> Result<(), ErreurBancaire> {
+        let evenement = Evenement::ConditionsDormanceDefinies {
+            dormance_mois: conditions.dormance_mois,
+            seuil_legal_mois: conditions.seuil_legal_mois,
+            preavis_mois: conditions.preavis_mois,
+        };
+        self.executer(evenement, |g| {
+            g.conditions_dormance = conditions;
+            Ok(())
+        })
+    }
+
+    /// Classe les comptes courants selon la durée écoulée au `date` depuis leur
+    /// dernier mouvement, par numéro, et met en dormance ceux qui ne sont plus
+    /// actifs. La première opération demandée ensuite sur un compte dormant doit
+    /// être confirmée ; une fois réussie, elle le rend actif. Comme pour
+    /// [`encaisser_cheques`](Self::encaisser_cheques), une clé d'idempotence
+    /// associée à l'appel est ignorée.
+    pub fn classer_comptes_inactifs(&mut self, date: NaiveDate) -> Vec<ClassementCompte> {
+        self.cle_idempotence = None;
+        let classements: Vec<ClassementCompte> = self
+            .comptes()
+            .into_iter()
+            .filter(|c| c.pret().is_none())
+            .filter_map(|c| {
+                let dernier_mouvement = c.historique.iter().map(|t| t.date.date()).max()?;
+                Some(ClassementCompte {
+                    numero: c.numero.clone(),
+                    titulaire: c.titulaire.clone(),
+                    solde: c.solde,
+                    dernier_mouvement,
+                    classe: self.conditions_dormance.classer(dernier_mouvement, date),
+                })
+            })
+            .collect();
+
+        for classement in &classements {
+            if classement.classe == ClasseInactivite::Actif
+                || self.dormants.contains(&classement.numero)
+            {
+                continue;
+            }
+            let evenement = Evenement::MiseEnDormance {
+                numero: classement.numero.clone(),
+                dernier_mouvement: classement.dernier_mouvement,
+            };
+            let _ = self.executer(evenement, |g| {
+                g.dormants.insert(classement.numero.clone());
+                g.modifier(Partie::Dormants);
+                Ok(())
+            });
+        }
+        classements
+    }
+
+    /// Le compte a été mis en dormance et n'a pas eu d'opération depuis.
+    pub fn est_dormant(&self, numero: &str) -> bool {
+        self.dormants.contains(numero)
+    }
+
+    // Compte, montant signé en euros (négatif pour un débit) et devise étrangère
+    // éventuelle du mouvement que l'événement demande ; `None` aussi pour un
+    // virement externe dans une devise sans cours, qui échouera.
+    fn mouvement_demande<'a>(
+        &self,
+        evenement: &'a Evenement,
+    ) -> Option<(&'a String, f32, Option<&'a str>)> {
+        match evenement {
+            Evenement::Depot {
+                numero, montant, ..
+            }
+            | Evenement::RemiseCheque {
+                numero, montant, ..
+            } => Some((numero, *montant, None)),
+            Evenement::Retrait {
+                numero, montant, ..
+            }
+            | Evenement::PrelevementMandat {
+                numero, montant, ..
+            } => Some((numero, -montant, None)),
+            Evenement::Virement {
+                source, montant, ..
+            } => Some((source, -montant, None)),
+            Evenement::VirementExterne {
+                source,
+                montant,
+                devise,
+                ..
+            } => {
+                let debit = self.en_euros(*montant, devise).ok()?;
+                Some((source, -debit, Some(devise.as_str())))
+            }
+            _ => None,
+        }
+    }
+
+    // Exige une confirmation pour un mouvement demandé sur un compte mis en
+    // dormance, ou inactif depuis le délai de dormance sans avoir été classé ;
+    // retourne le compte à réveiller si l'opération réussit.
+    fn controler_dormance(
+        &self,
+        evenement: &Evenement,
+        confirmee: bool,
+    ) -> Result<Option<String>, ErreurBancaire> {
+        let Some((numero, ..)) = self.mouvement_demande(evenement) else {
+            return Ok(None);
+        };
+        let Some(dernier_mouvement) = self
+            .comptes
+            .get(numero)
+            .and_then(|c| c.historique.iter().map(|t| t.date.date()).max())
+        else {
+            return Ok(None);
+        };
+        let inactif = self
+            .conditions_dormance
+            .classer(dernier_mouvement, Local::now().date_naive())
+            != ClasseInactivite::Actif;
+        if !inactif && !self.dormants.contains(numero) {
+            return Ok(None);
+        }
+        if !confirmee {
+            return Err(ErreurBancaire::ConfirmationRequise(tr!(
+                "dormance.a_confirmer",
+                numero,
+                dernier_mouvement.format("%d/%m/%Y")
+            )));
+        }
+        Ok(Some(numero.clone()))
+    }
+
+    // Évalue les règles de fraude sur le mouvement que l'événement demande,
+    // enregistre un signalement si l'une d'elles se déclenche, et refuse
+    // l'opération si la plus stricte des décisions l'exige.
+    fn controler_fraude(
+        &mut self,
+        evenement: &Evenement,
+        confirmee: bool,
+    ) -> Result<(), ErreurBancaire> {
+        let Some((numero, montant, devise)) = self.mouvement_demande(evenement) else {
+            return Ok(());
+        };
+        let Some(compte) = self.comptes.get(numero) else {
+            return Ok(());
+        };
+
+        let operation = OperationControlee {
+            compte,
+            montant,
+            devise: devise.filter(|devise| *devise != DEVISE_COMPTES),
+            date: Local::now().naive_local(),
+        };
+        let declenchees: Vec<(String, Decision)> = self
+            .regles_fraude
+            .iter()
+            .filter_map(|(regle, decision)| regle.evaluer(&operation).map(|m| (m, *decision)))
+            .collect();
+        let Some(decision) = declenchees.iter().map(|(_, decision)| *decision).max() else {
+            return Ok(());
+        };
+
+        let motifs: Vec<String> = declenchees.into_iter().map(|(motif, _)| motif).collect();
+        let resume = motifs.join(" ; ");
+        let signalement = Signalement {
+            date: operation.date,
+            numero: numero.clone(),
+            operation: evenement.to_string(),
+            decision,
+            motifs,
+            confirmee: confirmee && decision == Decision::Confirmer,
+            examine: false,
+        };
+        // Une opération retentée, par exemple pour la confirmer, met à jour son
+        // signalement encore en attente au lieu d'en ajouter un second.
+        match self.signalements.iter_mut().find(|s| {
+            !s.examine
+                && s.numero == signalement.numero
+                && s.operation == signalement.operation
+                && s.motifs == signalement.motifs
+        }) {
+            Some(existant) => {
+                existant.confirmee |= signalement.confirmee;
+                existant.date = signalement.date;
+                existant.decision = signalement.decision;
+            }
+            None => self.signalements.push(signalement),
+        }
+        self.modifier(Partie::Signalements);
+        match decision {
+            Decision::Bloquer => Err(ErreurBancaire::OperationBloquee(resume)),
+            Decision::Confirmer if !confirmee => Err(ErreurBancaire::ConfirmationRequise(resume)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Ajoute une règle d'alerte évaluée après chaque mouvement du compte.
+    pub fn ajouter_regle_alerte(
+        &mut self,
+        numero: &str,
+        regle: RegleAlerte,
+    ) -> Result<(), ErreurBancaire> {
+        let evenement = Evenement::RegleAlerteAjoutee {
+            numero: numero.to_string(),
+            regle,
+        };
+        self.executer(evenement, |g| {
+            g.compte(numero)?;
+            g.regles_alertes
+                .entry(numero.to_string())
+                .or_default()
+                .push(regle);
+            g.modifier(Partie::ReglesAlertes(numero.to_string()));
+            Ok(())
+        })
+    }
+
+    /// Règles d'alerte du compte ; vide si le compte n'en a aucune.
+    pub fn regles_alerte(&self, numero: &str) -> &[RegleAlerte] {
+        self.regles_alertes
+            .get(numero)
+            .map(|regles| regles.as_slice())
+            .unwrap_or(&[])
+    }
+
+    /// Retire toutes les règles d'alerte du compte.
+    pub fn supprimer_regles_alerte(&mut self, numero: &str) -> Result<(), ErreurBancaire> {
+        let evenement = Evenement::ReglesAlerteSupprimees {
+            numero: numero.to_string(),
+        };
+        self.executer(evenement, |g| {
+            g.compte(numero)?;
+            g.regles_alertes.remove(numero);
+            g.modifier(Partie::ReglesAlertes(numero.to_string()));
+            Ok(())
+        })
+    }
+
+    /// Ajoute une destination qui recevra chaque alerte déclenchée.
+    pub fn ajouter_sortie_alerte(
+        &mut self,
+        sortie: Box<dyn SortieAlerte>,
+    ) -> Result<(), ErreurBancaire> {
+        let evenement = Evenement::SortieAlerteAjoutee {
+            sortie: sortie.code(),
+        };
+        self.executer(evenement, |g| {
+            g.sorties_alertes.push(sortie);
+            Ok(())
+        })
+    }
+
+    /// Alertes déclenchées depuis la création du gestionnaire, dans l'ordre.
+    pub fn alertes(&self) -> &[Alerte] {
+        &self.alertes
+    }
+
+    // Évalue les règles d'alerte du compte sur son dernier mouvement
+    // et diffuse les alertes déclenchées vers toutes les sorties.
+    fn verifier_alertes(&mut self, numero: &str) {
+        let (Some(compte), Some(regles)) =
+            (self.comptes.get(numero), self.regles_alertes.get(numero))
+        else {
+            return;
+        };
+        let Some(dernier) = compte.historique.last() else {
+            return;
+        };
+
+        let declenchees: Vec<Alerte> = regles
+            .iter()
+            .filter_map(|regle| regle.evaluer(dernier.montant, compte.solde))
+            .map(|message| Alerte {
+                date: dernier.date,
+                numero: numero.to_string(),
+                message,
+            })
+            .collect();
+
+        for alerte in declenchees {
+            let echecs: Vec<io::Error> = self
+                .sorties_alertes
+                .iter_mut()
+                .filter_map(|sortie| sortie.envoyer(&alerte).err())
+                .collect();
+            for e in echecs {
+                self.signaler_incident(tr!("alerte.envoi_impossible", e));
+            }
+            self.alertes.push(alerte);
+        }
+    }
+
+    // Traitements communs après chaque mouvement enregistré sur un compte.
+    fn apres_mouvement(&mut self, numero: &str) {
+        self.categoriser_derniere(numero);
+        self.verifier_alertes(numero);
+    }
+
+    /// Attache un objectif d'épargne au compte, avec son virement permanent éventuel.
+    pub fn ajouter_objectif(
+        &mut self,
+        numero: &str,
+        objectif: ObjectifEpargne,
+    ) -> Result<(), ErreurBancaire> {
+        let evenement = Evenement::ObjectifAjoute {
+            numero: numero.to_string(),
+            nom: objectif.nom.clone(),
+            montant_cible: objectif.montant_cible,
+            date_limite: objectif.date_limite,
+        };
+        self.executer(evenement, |g| {
+            if g.compte(numero)?.pret().is_some() {
+                return Err(ErreurBancaire::OperationSurPret);
+            }
+            if !objectif.montant_cible.is_finite() || objectif.montant_cible <= 0.0 {
+                return Err(ErreurBancaire::MontantNonPositif);
+            }
+            if g.objectifs(numero).iter().any(|o| o.nom == objectif.nom) {
+                return Err(ErreurBancaire::ObjectifExistant(objectif.nom));
+            }
+            if let Some(virement) = &objectif.virement {
+                if !virement.montant.is_finite() || virement.montant <= 0.0 {
+                    return Err(ErreurBancaire::MontantNonPositif);
+                }
+                if virement.source == numero {
+                    return Err(ErreurBancaire::VirementMemeCompte);
+                }
+                if g.compte(&virement.source)?.pret().is_some() {
+                    return Err(ErreurBancaire::OperationSurPret);
+                }
+            }
+            g.objectifs
+                .entry(numero.to_string())
+                .or_default()
+                .push(objectif);
+            g.modifier(Partie::Objectifs(numero.to_string()));
+            Ok(())
+        })
+    }
+
+    /// Objectifs d'épargne du compte ; vide si le compte n'en a aucun.
+    pub fn objectifs(&self, numero: &str) -> &[ObjectifEpargne] {
+        self.objectifs
+            .get(numero)
+            .map(|objectifs| objectifs.as_slice())
+            .unwrap_or(&[])
+    }
+
+    /// Retire l'objectif `nom` du compte, ainsi que son virement permanent.
+    pub fn supprimer_objectif(&mut self, numero: &str, nom: &str) -> Result<(), ErreurBancaire> {
+        let evenement = Evenement::ObjectifSupprime {
+            numero: numero.to_string(),
+            nom: nom.to_string(),
+        };
+        self.executer(evenement, |g| {
+            g.compte(numero)?;
+            let objectifs = g.objectifs.entry(numero.to_string()).or_default();
+            let index = objectifs
+                .iter()
+                .position(|o| o.nom == nom)
+                .ok_or_else(|| ErreurBancaire::ObjectifIntrouvable(nom.to_string()))?;
+            objectifs.remove(index);
+            g.modifier(Partie::Objectifs(numero.to_string()));
+            Ok(())
+        })
+    }
+
+    /// Exécute les virements permanents échus au plus tard le `date`, mois par mois
+    /// en cas de retard. Une échéance est sautée, sans virement, si l'objectif est
+    /// déjà atteint ou sa date limite dépassée ; un virement qui échoue n'est pas
+    /// retenté le mois suivant. Chaque échéance a sa propre clé d'idempotence,
+    /// qui remplace celle éventuellement associée à l'appel.
+    pub fn executer_virements_permanents(&mut self, date: NaiveDate) -> Vec<VirementExecute> {
+        let mut numeros: Vec<String> = self.objectifs.keys().cloned().collect();
+        numeros.sort();
+
+        let mut executes = Vec::new();
+        for numero in numeros {
+            for index in 0..self.objectifs(&numero).len() {
+                loop {
+                    let objectif = &self.objectifs[&numero][index];
+                    let Some(virement) = objectif.virement.clone() else {
+                        break;
+                    };
+                    if virement.prochaine_date > date {
+                        break;
+                    }
+
+                    let nom = objectif.nom.clone();
+                    let solde = self.comptes.get(&numero).map_or(0.0, |c| c.solde);
+                    let reste = objectif.reste(solde);
+                    if reste > 0.0 && virement.prochaine_date <= objectif.date_limite {
+                        let montant = virement.montant.min(reste);
+                        let cle = format!(
+                            "virement-permanent/{}/{}/{}",
+                            numero, nom, virement.prochaine_date
+                        );
+                        let resultat = self.avec_cle_idempotence(&cle).transferer(
+                            &virement.source,
+                            &numero,
+                            montant,
+                            Libelle::banque("libelle.objectif", &[&nom]).code(),
+                            [
+                                Libelle::banque("libelle.virement_objectif_vers", &[&numero, &nom]),
+                                Libelle::banque(
+                                    "libelle.virement_objectif_recu",
+                                    &[&virement.source, &nom],
+                                ),
+                            ],
+                        );
+                        executes.push(VirementExecute {
+                            numero: numero.clone(),
+                            objectif: nom,
+                            date: virement.prochaine_date,
+                            montant,
+                            resultat,
+                        });
+                    }
+
+                    if let Some(permanent) = self
+                        .objectifs
+                        .get_mut(&numero)
+                        .and_then(|objectifs| objectifs[index].virement.as_mut())
+                    {
+                        permanent.prochaine_date = virement.prochaine_date + Months::new(1);
+                        self.modifier(Partie::Objectifs(numero.clone()));
+                    }
+                }
+            }
+        }
+        // Les prochaines dates avancent hors des opérations.
+        self.journaliser();
+        executes
+    }
+
+    /// Change le nombre de jours ouvrés d'attente des chèques remis ensuite,
+    /// et les frais prélevés au rejet d'un chèque.
+    pub fn definir_conditions_cheques(
+        &mut self,
+        delai_jours_ouvres: u32,
+        frais_rejet: f32,
+    ) -> Result<(), ErreurBancaire> {
+        let evenement = Evenement::ConditionsChequesDefinies {
+            delai_jours_ouvres,
+            frais_rejet,
+        };
+        self.executer(evenement, |g| {
+            if !frais_rejet.is_finite() || frais_rejet < 0.0 {
+                return Err(ErreurBancaire::MontantNonPositif);
+            }
+            g.delai_encaissement = delai_jours_ouvres;
+            g.frais_rejet_cheque = frais_rejet;
+            Ok(())
+        })
+    }
+
+    /// Remet un chèque sur un compte courant. Le montant n'est pas crédité tout
+    /// de suite : il reste en attente jusqu'à la date retournée, à partir de
+    /// laquelle [`encaisser_cheques`](Self::encaisser_cheques) le crédite.
+    pub fn remise_cheque(
+        &mut self,
+        numero: &str,
+        numero_cheque: &str,
+        emetteur: &str,
+        montant: f32,
+    ) -> Result<NaiveDate, ErreurBancaire> {
+        let evenement = Evenement::RemiseCheque {
+            numero: numero.to_string(),
+            cheque: numero_cheque.to_string(),
+            emetteur: emetteur.to_string(),
+            montant,
+        };
+        self.executer(evenement, |g| {
+            if g.compte(numero)?.pret().is_some() {
+                return Err(ErreurBancaire::OperationSurPret);
+            }
+            if !montant.is_finite() || montant <= 0.0 {
+                return Err(ErreurBancaire::MontantNonPositif);
+            }
+            // Un chèque rejeté peut être présenté à nouveau.
+            if g.cheques.iter().any(|c| {
+                c.compte == numero && c.numero == numero_cheque && c.etat != EtatCheque::Rejete
+            }) {
+                return Err(ErreurBancaire::ChequeDejaRemis(numero_cheque.to_string()));
+            }
+
+            let remis_le = Local::now().date_naive();
+            let disponible_le = cheque::jours_ouvres_apres(remis_le, g.delai_encaissement);
+            g.cheques.push(Cheque {
+                compte: numero.to_string(),
+                numero: numero_cheque.to_string(),
+                emetteur: emetteur.to_string(),
+                montant,
+                remis_le,
+                disponible_le,
+                etat: EtatCheque::EnAttente,
+            });
+            g.modifier(Partie::Cheques);
+            Ok(disponible_le)
+        })
+    }
+
+    /// Chèques remis sur le compte, du plus ancien au plus récent.
+    pub fn cheques(&self, numero: &str) -> Vec<&Cheque> {
+        self.cheques.iter().filter(|c| c.compte == numero).collect()
+    }
+
+    /// Total des chèques remis sur le compte et pas encore encaissés ; il
+    /// s'ajoutera au solde, qui ne le comprend pas.
+    pub fn montant_en_attente(&self, numero: &str) -> f32 {
+        self.cheques
+            .iter()
+            .filter(|c| c.compte == numero && c.etat == EtatCheque::EnAttente)
+            .map(|c| c.montant)
+            .sum()
+    }
+
+    /// Crédite les chèques en attente disponibles au plus tard le `date` et
+    /// retourne ceux qui ont été encaissés. Un chèque n'est encaissé qu'une fois :
+    /// une clé d'idempotence associée à l'appel serait sans objet et est ignorée.
+    pub fn encaisser_cheques(&mut self, date: NaiveDate) -> Vec<Cheque> {
+        self.cle_idempotence = None;
+        let mut encaisses = Vec::new();
+        for index in 0..self.cheques.len() {
+            let cheque = self.cheques[index].clone();
+            if cheque.etat != EtatCheque::EnAttente || cheque.disponible_le > date {
+                continue;
+            }
+            let evenement = Evenement::EncaissementCheque {
+                numero: cheque.compte.clone(),
+                cheque: cheque.numero.clone(),
+                montant: cheque.montant,
+            };
+            let resultat = self.executer(evenement, |g| {
+                g.compte_mut(&cheque.compte)?.depot(
+                    cheque.montant,
+                    Libelle::banque("libelle.remise_cheque", &[&cheque.numero, &cheque.emetteur]),
+                )?;
+                g.apres_mouvement(&cheque.compte);
+                g.cheques[index].etat = EtatCheque::Encaisse;
+                g.modifier(Partie::Cheques);
+                Ok(())
+            });
+            if resultat.is_ok() {
+                encaisses.push(self.cheques[index].clone());
+            }
+        }
+        encaisses
+    }
+
+    /// Rejette un chèque remis sur le compte : un chèque en attente n'est jamais
+    /// crédité, un chèque déjà encaissé est débité. Les frais de rejet sont
+    /// prélevés dans les deux cas, quitte à rendre le solde négatif. Retourne le
+    /// nouveau solde.
+    pub fn rejeter_cheque(
+        &mut self,
+        numero: &str,
+        numero_cheque: &str,
+    ) -> Result<f32, ErreurBancaire> {
+        let evenement = Evenement::RejetCheque {
+            numero: numero.to_string(),
+            cheque: numero_cheque.to_string(),
+        };
+        self.executer(evenement, |g| {
+            g.compte(numero)?;
+            let index = g
+                .cheques
+                .iter()
+                .rposition(|c| c.compte == numero && c.numero == numero_cheque)
+                .ok_or_else(|| ErreurBancaire::ChequeIntrouvable(numero_cheque.to_string()))?;
+            let cheque = g.cheques[index].clone();
+            if cheque.etat == EtatCheque::Rejete {
+                return Err(ErreurBancaire::ChequeRejete(cheque.numero));
+            }
+
+            g.cheques[index].etat = EtatCheque::Rejete;
+            g.modifier(Partie::Cheques);
+            if cheque.etat == EtatCheque::Encaisse {
+                g.compte_mut(numero)?.enregistrer(
+                    -cheque.montant,
+                    Libelle::banque("libelle.rejet_cheque", &[&cheque.numero]),
+                );
+                g.apres_mouvement(numero);
+            }
+            if g.frais_rejet_cheque > 0.0 {
+                let frais = g.frais_rejet_cheque;
+                g.compte_mut(numero)?.enregistrer(
+                    -frais,
+                    Libelle::banque("libelle.frais_rejet_cheque", &[&cheque.numero]),
+                );
+                g.apres_mouvement(numero);
+            }
+            Ok(g.compte(numero)?.solde)
+        })
+    }
+
+    /// Change le nombre de jours pendant lesquels un prélèvement peut être remboursé.
+    pub fn definir_delai_remboursement(&mut self, jours: u32) -> Result<(), ErreurBancaire> {
+        self.executer(Evenement::DelaiRemboursementDefini { jours }, |g| {
+            g.delai_remboursement = jours;
+            Ok(())
+        })
+    }
+
+    /// Enregistre le mandat signé le `signe_le` par le titulaire d'un compte
+    /// courant, qui autorise le créancier à y prélever jusqu'à `montant_max` à la fois.
+    pub fn ajouter_mandat(
+        &mut self,
+        numero: &str,
+        creancier: &str,
+        reference: &str,
+        signe_le: NaiveDate,
+        montant_max: f32,
+    ) -> Result<(), ErreurBancaire> {
+        let evenement = Evenement::MandatSigne {
+            numero: numero.to_string(),
+            creancier: creancier.to_string(),
+            reference: reference.to_string(),
+            montant_max,
+        };
+        self.executer(evenement, |g| {
+            if g.compte(numero)?.pret().is_some() {
+                return Err(ErreurBancaire::OperationSurPret);
+            }
+            if !montant_max.is_finite() || montant_max <= 0.0 {
+                return Err(ErreurBancaire::MontantNonPositif);
+            }
+            if g.mandats
+                .iter()
+                .any(|m| m.creancier == creancier && m.reference == reference)
+            {
+                return Err(ErreurBancaire::MandatExistant(reference.to_string()));
+            }
+            g.mandats.push(Mandat {
+                compte: numero.to_string(),
+                creancier: creancier.to_string(),
+                reference: reference.to_string(),
+                signe_le,
+                montant_max,
+                revoque_le: None,
+            });
+            g.modifier(Partie::Mandats);
+            Ok(())
+        })
+    }
+
+    /// Mandats du compte, révoqués ou non, dans l'ordre de leur signature.
+    pub fn mandats(&self, numero: &str) -> Vec<&Mandat> {
+        self.mandats.iter().filter(|m| m.compte == numero).collect()
+    }
+
+    // Position du mandat du créancier sur le compte.
+    fn index_mandat(
+        &self,
+        numero: &str,
+        creancier: &str,
+        reference: &str,
+    ) -> Result<usize, ErreurBancaire> {
+        self.mandats
+            .iter()
+            .position(|m| {
+                m.compte == numero && m.creancier == creancier && m.reference == reference
+            })
+            .ok_or_else(|| ErreurBancaire::MandatIntrouvable(reference.to_string()))
+    }
+
+    /// Prélève `montant` sur le compte pour le créancier. Le mandat doit être
+    /// valide aujourd'hui et le montant ne pas dépasser son plafond. Retourne le
+    /// nouveau solde.
+    pub fn prelever(
+        &mut self,
+        numero: &str,
+        creancier: &str,
+        reference: &str,
+        montant: f32,
+    ) -> Result<f32, ErreurBancaire> {
+        let evenement = Evenement::PrelevementMandat {
+            numero: numero.to_string(),
+            creancier: creancier.to_string(),
+            reference: reference.to_string(),
+            montant,
+        };
+        self.executer(evenement, |g| {
+            g.compte(numero)?;
+            let mandat = &g.mandats[g.index_mandat(numero, creancier, reference)?];
+            let aujourd_hui = Local::now().date_naive();
+            if !mandat.est_valide(aujourd_hui) {
+                return Err(ErreurBancaire::MandatInactif(reference.to_string()));
+            }
+            if montant > mandat.montant_max {
+                return Err(ErreurBancaire::PlafondMandatDepasse(reference.to_string()));
+            }
+
+            let compte = g.compte_mut(numero)?;
+            compte.retrait(
+                montant,
+                Libelle::banque("libelle.prelevement", &[&creancier, &reference]),
+            )?;
+            let solde = compte.solde;
+            g.apres_mouvement(numero);
+            g.prelevements.push(Prelevement {
+                compte: numero.to_string(),
+                creancier: creancier.to_string(),
+                reference: reference.to_string(),
+                montant,
+                date: aujourd_hui,
+                rembourse: false,
+            });
+            g.modifier(Partie::Prelevements);
+            Ok(solde)
+        })
+    }
+
+    /// Prélèvements effectués sur le compte, du plus ancien au plus récent.
+    pub fn prelevements(&self, numero: &str) -> Vec<&Prelevement> {
+        self.prelevements
+            .iter()
+            .filter(|p| p.compte == numero)
+            .collect()
+    }
+
+    /// Rembourse le prélèvement `index` (à partir de 1, parmi ceux du compte),
+    /// s'il date de moins que le délai de remboursement. Retourne le nouveau solde.
+    pub fn rembourser_prelevement(
+        &mut self,
+        numero: &str,
+        index: usize,
+    ) -> Result<f32, ErreurBancaire> {
+        let evenement = Evenement::RemboursementPrelevement {
+            numero: numero.to_string(),
+            index,
+        };
+        self.executer(evenement, |g| {
+            g.compte(numero)?;
+            let position = index
+                .checked_sub(1)
+                .and_then(|i| {
+                    g.prelevements
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, p)| p.compte == numero)
+                        .nth(i)
+                })
+                .map(|(position, _)| position)
+                .ok_or(ErreurBancaire::PrelevementIntrouvable(index))?;
+            let prelevement = g.prelevements[position].clone();
+            if prelevement.rembourse {
+                return Err(ErreurBancaire::PrelevementDejaRembourse(index));
+            }
+            if Local::now().date_naive() > prelevement.remboursable_jusqu_au(g.delai_remboursement)
+            {
+                return Err(ErreurBancaire::DelaiRemboursementDepasse(index));
+            }
+
+            let compte = g.compte_mut(numero)?;
+            compte.depot(
+                prelevement.montant,
+                Libelle::banque(
+                    "libelle.remboursement_prelevement",
+                    &[&prelevement.creancier, &prelevement.reference],
+                ),
+            )?;
+            let solde = compte.solde;
+            g.apres_mouvement(numero);
+            g.prelevements[position].rembourse = true;
+            g.modifier(Partie::Prelevements);
+            Ok(solde)
+        })
+    }
+
+    /// Révoque le mandat : le créancier ne peut plus prélever sur le compte à partir
+    /// d'aujourd'hui. Les prélèvements passés restent remboursables.
+    pub fn revoquer_mandat(
+        &mut self,
+        numero: &str,
+        creancier: &str,
+        reference: &str,
+    ) -> Result<(), ErreurBancaire> {
+        let evenement = Evenement::RevocationMandat {
+            numero: numero.to_string(),
+            creancier: creancier.to_string(),
+            reference: reference.to_string(),
+        };
+        self.executer(evenement, |g| {
+            let index = g.index_mandat(numero, creancier, reference)?;
+            let mandat = &mut g.mandats[index];
+            if mandat.revoque_le.is_some() {
+                return Err(ErreurBancaire::MandatInactif(reference.to_string()));
+            }
+            mandat.revoque_le = Some(Local::now().date_naive());
+            g.modifier(Partie::Mandats);
+            Ok(())
+        })
+    }
+
+    /// Remplace les règles de catégorisation par celles du fichier et retourne leur nombre.
+    pub fn charger_regles_categories(&mut self, chemin: &Path) -> Result<usize, ErreurBancaire> {
+        let evenement = Evenement::ReglesCategoriesChargees {
+            chemin: chemin.display().to_string(),
+        };
+        self.executer(evenement, |g| {
+            g.regles_categories = categorie::charger_regles(chemin)
+                .map_err(|e| ErreurBancaire::Fichier(e.to_string()))?;
+            Ok(g.regles_categories.len())
+        })
+    }
+
+    /// Ajoute les comptes d'un jeu de données (voir [`fixture`]), chiffré ou non
+    /// selon `coffre`, et retourne leur nombre.
+    /// Les mouvements sans catégorie sont catégorisés avec les règles en place.
+    /// Rien n'est ajouté si le fichier est invalide ou si un numéro est déjà utilisé.
+    /// Ces comptes existent déjà : leur chargement n'est pas notifié aux observateurs.
+    pub fn charger_fixture(
+        &mut self,
+        chemin: &Path,
+        coffre: &Coffre,
+    ) -> Result<usize, ErreurBancaire> {
+        let contenu = coffre
+            .lire(chemin)
+            .map_err(|e| ErreurBancaire::Fichier(e.to_string()))?;
+        self.charger_jeu(&contenu)
+    }
+
+    /// Comme [`charger_fixture`](Self::charger_fixture), puis rejoue les lignes
+    /// du journal des opérations tenu sur ce jeu de données ; les opérations
+    /// suivantes y sont inscrites. Retourne le nombre de comptes et le nombre de
+    /// lignes rejouées.
+    pub fn charger_fixture_journalisee(
+        &mut self,
+        chemin: &Path,
+        journal: &Path,
+        coffre: &Coffre,
+    ) -> Result<(usize, usize), ErreurBancaire> {
+        let jeu = coffre
+            .lire(chemin)
+            .map_err(|e| ErreurBancaire::Fichier(e.to_string()))?;
+        let journal = JournalOperations::ouvrir(journal.to_path_buf(), coffre.clone(), &jeu)?;
+        let nombre = self.charger_jeu(&sauvegarde::reconstituer(&jeu, journal.lignes()))?;
+        let rejouees = journal.lignes().len();
+        self.journal_operations = Some(journal);
+        self.comptes_modifies.clear();
+        self.parties_modifiees.clear();
+        self.historiques_journalises = self.longueurs_historiques();
+        self.etat_journalise = self.etat();
+        Ok((nombre, rejouees))
+    }
+
+    fn charger_jeu(&mut self, contenu: &str) -> Result<usize, ErreurBancaire> {
+        let comptes = fixture::lire(contenu)?;
+        let etat = etat::lire(contenu)?;
+        if let Some(existant) = comptes
+            .iter()
+            .find(|c| self.comptes.contains_key(&c.numero))
+        {
+            return Err(ErreurBancaire::CompteExistant(existant.numero.clone()));
+        }
+
+        self.restaurer_etat(&etat)?;
+
+        let nombre = comptes.len();
+        for mut compte in comptes {
+            for t in compte
+                .historique
+                .iter_mut()
+                .filter(|t| t.categorie.is_none())
+            {
+                t.categorie = categorie::categoriser(
+                    &self.regles_categories,
+                    &t.libelle.en(Langue::Francais),
+                )
+                .map(Libelle::Saisi);
+            }
+            self.inserer_compte(compte)?;
+        }
+        Ok(nombre)
+    }
+
+    // Applique les règles de catégorisation à la dernière transaction du compte,
+    // sauf si une catégorie lui a déjà été attribuée. Les mots-clés des règles
+    // sont en français : ils portent sur le libellé rendu en français, quelle
+    // que soit la langue d'affichage.
+    fn categoriser_derniere(&mut self, numero: &str) {
+        let regles = &self.regles_categories;
+        if let Some(compte) = self.comptes.get_mut(numero)
+            && let Some(t) = compte.historique.last_mut()
+            && t.categorie.is_none()
+        {
+            t.categorie =
+                categorie::categoriser(regles, &t.libelle.en(Langue::Francais)).map(Libelle::Saisi);
+        }
+    }
+
+    /// Crédite le compte d'un dépôt en espèces et retourne le nouveau solde.
+    /// Le dépôt est inscrit dans la caisse si elle est ouverte.
+    pub fn depot(
+        &mut self,
+        numero: &str,
+        montant: f32,
+        libelle: &str,
+    ) -> Result<f32, ErreurBancaire> {
+        let evenement = Evenement::Depot {
+            numero: numero.to_string(),
+            montant,
+            libelle: libelle.to_string(),
+        };
+        self.executer(evenement, |g| {
+            let compte = g.compte_mut(numero)?;
+            compte.depot(montant, libelle)?;
+            let solde = compte.solde;
+            g.apres_mouvement(numero);
+            g.mouvement_caisse(numero, montant);
+            Ok(solde)
+        })
+    }
+
+    /// Débite le compte d'un retrait en espèces et retourne le nouveau solde.
+    /// Le retrait est inscrit dans la caisse si elle est ouverte.
+    pub fn retrait(
+        &mut self,
+        numero: &str,
+        montant: f32,
+        libelle: &str,
+    ) -> Result<f32, ErreurBancaire> {
+        let evenement = Evenement::Retrait {
+            numero: numero.to_string(),
+            montant,
+            libelle: libelle.to_string(),
+        };
+        self.executer(evenement, |g| {
+            let compte = g.compte_mut(numero)?;
+            compte.retrait(montant, libelle)?;
+            let solde = compte.solde;
+            g.apres_mouvement(numero);
+            g.mouvement_caisse(numero, -montant);
+            Ok(solde)
+        })
+    }
+
+    /// Transfère `montant` d'un compte courant à un autre et retourne le nouveau solde
+    /// du compte source. Rien n'est débité si le compte destinataire n'existe pas.
+    pub fn virement(
+        &mut self,
+        source: &str,
+        destination: &str,
+        montant: f32,
+        libelle: &str,
+    ) -> Result<f32, ErreurBancaire> {
+        self.transferer(
+            source,
+            destination,
+            montant,
+            libelle.to_string(),
+            [
+                Libelle::banque("libelle.virement_vers", &[&destination, &libelle]),
+                Libelle::banque("libelle.virement_recu", &[&source, &libelle]),
+            ],
+        )
+    }
+
+    // Virement inscrit avec les libellés `[debit, credit]` des deux comptes.
+    fn transferer(
+        &mut self,
+        source: &str,
+        destination: &str,
+        montant: f32,
+        libelle: String,
+        [debit, credit]: [Libelle; 2],
+    ) -> Result<f32, ErreurBancaire> {
+        let evenement = Evenement::Virement {
+            source: source.to_string(),
+            destination: destination.to_string(),
+            montant,
+            libelle,
+        };
+        self.executer(evenement, |g| {
+            if source == destination {
+                return Err(ErreurBancaire::VirementMemeCompte);
+            }
+            if g.compte(destination)?.pret().is_some() {
+                return Err(ErreurBancaire::OperationSurPret);
+            }
+
+            let compte = g.compte_mut(source)?;
+            compte.retrait(montant, debit)?;
+            let solde = compte.solde;
+            g.apres_mouvement(source);
+
+            g.compte_mut(destination)?.depot(montant, credit)?;
+            g.apres_mouvement(destination);
+            Ok(solde)
+        })
+    }
+
+    /// Verse `montant` en `devise` (code ISO comme `EUR` ou `USD`) à un bénéficiaire
+    /// d'une autre banque. Le compte courant `source` est débité de la contre-valeur
+    /// en euros au cours défini par [`definir_cours_devise`](Self::definir_cours_devise).
+    /// Retourne le nouveau solde.
+    pub fn virement_externe(
+        &mut self,
+        source: &str,
+        beneficiaire: &str,
+        montant: f32,
+        devise: &str,
+        libelle: &str,
+    ) -> Result<f32, ErreurBancaire> {
+        let devise = devise.trim().to_uppercase();
+        let evenement = Evenement::VirementExterne {
+            source: source.to_string(),
+            beneficiaire: beneficiaire.to_string(),
+            montant,
+            devise: devise.clone(),
+            libelle: libelle.to_string(),
+        };
+        self.executer(evenement, |g| {
+            let debit = g.en_euros(montant, &devise)?;
+            let compte = g.compte_mut(source)?;
+            compte.retrait(
+                debit,
+                Libelle::banque(
+                    "libelle.virement_externe",
+                    &[&beneficiaire, &devise, &libelle],
+                ),
+            )?;
+            if devise != DEVISE_COMPTES
+                && let Some(transaction) = compte.historique.last_mut()
+            {
+                transaction.devise = Some(devise);
+            }
+            let solde = compte.solde;
+            g.apres_mouvement(source);
+            Ok(solde)
+        })
+    }
+
+    /// Remplace manuellement la catégorie de la transaction `index` (à partir de 1).
+    pub fn definir_categorie(
+        &mut self,
+        numero: &str,
+        index: usize,
+        categorie: String,
+    ) -> Result<(), ErreurBancaire> {
+        let evenement = Evenement::CategorieModifiee {
+            numero: numero.to_string(),
+            index,
+            categorie: categorie.clone(),
+        };
+        self.executer(evenement, |g| {
+            let compte = g.compte_mut(numero)?;
+            let transaction = index
+                .checked_sub(1)
+                .and_then(|i| compte.historique.get_mut(i))
+                .ok_or(ErreurBancaire::TransactionIntrouvable(index))?;
+            let categorie = Libelle::Saisi(categorie);
+            transaction.categorie = Some(categorie.clone());
+            g.lignes_journal
+                .push(fixture::ligne_categorie(numero, index, &categorie));
+            Ok(())
+        })
+    }
+
+    /// Transactions des comptes courants d'un client, identifié par le nom du titulaire.
+    pub fn transactions_client(&self, titulaire: &str) -> Vec<&Transaction> {
+        self.comptes
+            .values()
+            .filter(|c| matches!(c.type_compte, TypeCompte::Courant))
+            .filter(|c| c.titulaire.eq_ignore_ascii_case(titulaire))
+            .flat_map(|c| c.historique.iter())
+            .collect()
+    }
+
+    /// Récapitulatif fiscal de l'année pour tous les comptes du client, comptes
+    /// de prêt compris. Le client est désigné par son identifiant (voir
+    /// [`CompteBancaire::client`]), sans tenir compte de la casse.
+    pub fn recapitulatif_fiscal(
+        &self,
+        client: &str,
+        annee: i32,
+    ) -> Result<RecapitulatifFiscal, ErreurBancaire> {
+        let comptes: Vec<&CompteBancaire> = self
+            .comptes
+            .values()
+            .filter(|c| c.client.eq_ignore_ascii_case(client))
+            .collect();
+        let client = comptes
+            .first()
+            .map(|c| c.client.clone())
+            .ok_or_else(|| ErreurBancaire::ClientIntrouvable(client.to_string()))?;
+        Ok(fiscal::recapitulatif(&client, annee, comptes.into_iter()))
+    }
+
+    /// Ajoute un compte ; échoue si le numéro est déjà utilisé.
+    pub fn ajouter_compte(&mut self, compte: CompteBancaire) -> Result<(), ErreurBancaire> {
+        let evenement = Evenement::OuvertureCompte {
+            numero: compte.numero.clone(),
+            titulaire: compte.titulaire.clone(),
+            solde_initial: compte.solde,
+        };
+        self.executer(evenement, |g| g.inserer_compte(compte))
+    }
+
+    fn inserer_compte(&mut self, compte: CompteBancaire) -> Result<(), ErreurBancaire> {
+        if self.comptes.contains_key(&compte.numero) {
+            return Err(ErreurBancaire::CompteExistant(compte.numero));
+        }
+        self.comptes_modifies.insert(compte.numero.clone());
+        self.comptes.insert(compte.numero.clone(), compte);
+        Ok(())
+    }
+
+    /// Affiche le solde de chaque compte.
+    pub fn lister_comptes(&self, sortie: &mut impl Write) -> io::Result<()> {
+        if self.comptes.is_empty() {
+            return writeln!(sortie, "{}", tr!("liste.aucun_compte"));
+        }
+
+        writeln!(sortie, "\n=== {} ===", tr!("liste.titre"))?;
+        for compte in self.comptes() {
+            compte.afficher_solde(sortie)?;
+        }
+        writeln!(sortie, "========================")
+    }
+
+    /// Comptes gérés, triés par numéro.
+    pub fn comptes(&self) -> Vec<&CompteBancaire> {
+        let mut comptes: Vec<&CompteBancaire> = self.comptes.values().collect();
+        comptes.sort_by(|a, b| a.numero.cmp(&b.numero));
+        comptes
+    }
+
+    /// Comptes retenus par `filtre`, triés selon `tri` puis par numéro.
+    pub fn rechercher(
+        &self,
+        filtre: &FiltreComptes,
+        tri: TriComptes,
+        decroissant: bool,
+    ) -> Vec<&CompteBancaire> {
+        recherche::rechercher(self.comptes.values(), filtre, tri, decroissant)
+    }
+
+    /// Indique si un compte porte ce numéro.
+    pub fn contient(&self, numero: &str) -> bool {
+        self.comptes.contains_key(numero)
+    }
+
+    /// Compte de numéro `numero`, ou `CompteIntrouvable`.
+    pub fn compte(&self, numero: &str) -> Result<&CompteBancaire, ErreurBancaire> {
+        self.comptes
+            .get(numero)
+            .ok_or_else(|| ErreurBancaire::CompteIntrouvable(numero.to_string()))
+    }
+
+    /// Solde d'un compte à la fin de la journée `date`.
+    pub fn solde_au(&self, numero: &str, date: NaiveDate) -> Result<f32, ErreurBancaire> {
+        Ok(self.compte(numero)?.solde_au(date))
+    }
+
+    /// Soldes de tous les comptes à une date donnée, triés par numéro de compte.
+    pub fn soldes_au(&self, date: NaiveDate) -> Vec<(&CompteBancaire, f32)> {
+        let mut soldes: Vec<(&CompteBancaire, f32)> = self
+            .comptes
+            .values()
+            .map(|compte| (compte, compte.solde_au(date)))
+            .collect();
+        soldes.sort_by(|a, b| a.0.numero.cmp(&b.0.numero));
+        soldes
+    }
+
+    /// Prêt porté par le compte `numero`, ou `PasUnPret` s'il s'agit d'un compte courant.
+    pub fn pret(&self, numero: &str) -> Result<&Pret, ErreurBancaire> {
+        self.compte(numero)?
+            .pret()
+            .ok_or_else(|| ErreurBancaire::PasUnPret(numero.to_string()))
+    }
+
+    fn pret_mut(&mut self, numero: &str) -> Result<&mut Pret, ErreurBancaire> {
+        let compte = self.compte_mut(numero)?;
+        match &mut compte.type_compte {
+            TypeCompte::Pret(pret) => Ok(pret),
+            TypeCompte::Courant => Err(ErreurBancaire::PasUnPret(numero.to_string())),
+        }
+    }
+
+    // Le compte rendu est noté comme modifié, pour le journal des opérations.
+    fn compte_mut(&mut self, numero: &str) -> Result<&mut CompteBancaire, ErreurBancaire> {
+        let compte = self
+            .comptes
+            .get_mut(numero)
+            .ok_or_else(|| ErreurBancaire::CompteIntrouvable(numero.to_string()))?;
+        self.comptes_modifies.insert(numero.to_string());
+        Ok(compte)
+    }
+
+    /// Ouvre un compte de prêt et verse le capital sur le compte courant lié.
+    pub fn ouvrir_pret(
+        &mut self,
+        numero: String,
+        compte_lie: String,
+        capital: f32,
+        taux_annuel: f32,
+        duree_mois: u32,
+        mode: ModeAmortissement,
+    ) -> Result<(), ErreurBancaire> {
+        let evenement = Evenement::OuverturePret {
+            numero: numero.clone(),
+            compte_lie: compte_lie.clone(),
+            capital,
+            taux_annuel,
+            duree_mois,
+            mode,
+        };
+        self.executer(evenement, |g| {
+            if !capital.is_finite() || capital <= 0.0 {
+                return Err(ErreurBancaire::MontantNonPositif);
+            }
+            if duree_mois == 0 {
+                return Err(ErreurBancaire::DureePretNulle);
+            }
+            if !taux_annuel.is_finite() || taux_annuel < 0.0 {
+                return Err(ErreurBancaire::TauxInvalide);
+            }
+            if g.comptes.contains_key(&numero) {
+                return Err(ErreurBancaire::CompteExistant(numero));
+            }
+
+            let lie = g.compte_mut(&compte_lie)?;
+            lie.depot(
+                capital,
+                Libelle::banque("libelle.versement_pret", &[&numero]),
+            )?;
+            let (titulaire, client) = (lie.titulaire.clone(), lie.client.clone());
+            g.apres_mouvement(&compte_lie);
+
+            let ouverture = Local::now().naive_local();
+            let pret = Pret::new(
+                capital,
+                taux_annuel,
+                duree_mois,
+                mode,
+                compte_lie,
+                ouverture.date(),
+            );
+            g.inserer_compte(
+                CompteBancaire::nouveau_pret(numero, titulaire, pret, ouverture).du_client(client),
+            )
+        })
+    }
+
+    /// Prélève la prochaine échéance du prêt sur son compte courant lié.
+    pub fn prelever_echeance(&mut self, numero_pret: &str) -> Result<Echeance, ErreurBancaire> {
+        let evenement = Evenement::PrelevementEcheance {
+            numero_pret: numero_pret.to_string(),
+        };
+        self.executer(evenement, |g| {
+            let pret = g.pret_mut(numero_pret)?;
+            let echeance = pret
+                .prochaine_echeance()
+                .ok_or(ErreurBancaire::PretSolde)?
+                .clone();
+            let compte_lie = pret.compte_lie.clone();
+
+            g.compte_mut(&compte_lie)?.retrait(
+                echeance.mensualite,
+                Libelle::banque("libelle.echeance_pret", &[&echeance.numero, &numero_pret]),
+            )?;
+            g.apres_mouvement(&compte_lie);
+
+            let compte_pret = g.compte_mut(numero_pret)?;
+            compte_pret.enregistrer(
+                echeance.amortissement,
+                Libelle::banque("libelle.echeance", &[&echeance.numero]),
+            );
+            let date = Local::now().date_naive();
+            if let TypeCompte::Pret(pret) = &mut compte_pret.type_compte {
+                pret.marquer_payee(date);
+            }
+            g.lignes_journal
+                .push(fixture::ligne_echeance(numero_pret, date));
+            g.apres_mouvement(numero_pret);
+            Ok(echeance)
+        })
+    }
+
+    /// Rembourse une partie du capital par anticipation et recalcule les
+    /// échéances restantes. Retourne la nouvelle mensualité, ou `None` si le prêt est soldé.
+    pub fn remboursement_anticipe(
+        &mut self,
+        numero_pret: &str,
+        montant: f32,
+    ) -> Result<Option<f32>, ErreurBancaire> {
+        let evenement = Evenement::RemboursementAnticipe {
+            numero_pret: numero_pret.to_string(),
+            montant,
+        };
+        self.executer(evenement, |g| {
+            if !montant.is_finite() || montant <= 0.0 {
+                return Err(ErreurBancaire::MontantNonPositif);
+            }
+
+            let pret = g.pret_mut(numero_pret)?;
+            if pret.est_solde() {
+                return Err(ErreurBancaire::PretSolde);
+            }
+            let montant = montant.min(pret.capital_restant);
+            let compte_lie = pret.compte_lie.clone();
+
+            g.compte_mut(&compte_lie)?.retrait(
+                montant,
+                Libelle::banque("libelle.remboursement_anticipe_pret", &[&numero_pret]),
+            )?;
+            g.apres_mouvement(&compte_lie);
+
+            let compte_pret = g.compte_mut(numero_pret)?;
+            compte_pret.enregistrer(
+                montant,
+                Libelle::banque("libelle.remboursement_anticipe", &[]),
+            );
+            let mut nouvelle_mensualite = None;
+            if let TypeCompte::Pret(pret) = &mut compte_pret.type_compte {
+                nouvelle_mensualite = pret.rembourser_par_anticipation(montant);
+            }
+            g.lignes_journal
+                .push(fixture::ligne_anticipation(numero_pret, montant));
+            g.apres_mouvement(numero_pret);
+            Ok(nouvelle_mensualite)
+        })
+    }
+}
+
+impl Default for GestionnaireComptes {
+    fn default() -> Self {
+        GestionnaireComptes::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::objectif::VirementPermanent;
+    use crate::observateur::Evenement;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    fn gestionnaire_demo() -> GestionnaireComptes {
+        let mut gestionnaire = GestionnaireComptes::new();
+        gestionnaire
+            .ajouter_compte(CompteBancaire::new(
+                "12345".to_string(),
+                "Alice Dupont".to_string(),
+                1500.0,
+            ))
+            .unwrap();
+        gestionnaire
+            .ajouter_compte(CompteBancaire::new(
+                "67890".to_string(),
+                "Bob Martin".to_string(),
+                2300.0,
+            ))
+            .unwrap();
+        gestionnaire
+    }
+
+    #[test]
+    fn ajouter_compte_refuse_un_numero_existant() {
+        let mut gestionnaire = gestionnaire_demo();
+        let doublon = CompteBancaire::new("12345".to_string(), "Autre".to_string(), 10.0);
+        assert_eq!(
+            gestionnaire.ajouter_compte(doublon),
+            Err(ErreurBancaire::CompteExistant("12345".to_string()))
+        );
+        assert_eq!(
+            gestionnaire.compte("12345").unwrap().titulaire(),
+            "Alice Dupont"
+        );
+    }
+
+    #[test]
+    fn depot_credite_le_compte() {
+        let mut gestionnaire = gestionnaire_demo();
+        assert_eq!(gestionnaire.depot("12345", 100.0, "Dépôt"), Ok(1600.0));
+        assert_eq!(
+            gestionnaire.depot("12345", 0.0, "Dépôt"),
+            Err(ErreurBancaire::MontantNonPositif)
+        );
+        assert_eq!(
+            gestionnaire.depot("00000", 10.0, "Dépôt"),
+            Err(ErreurBancaire::CompteIntrouvable("00000".to_string()))
+        );
+    }
+
+    #[test]
+    fn montants_non_finis_refuses() {
+        let mut gestionnaire = gestionnaire_demo();
+        for montant in [f32::NAN, f32::INFINITY, f32::NEG_INFINITY] {
+            assert_eq!(
+                gestionnaire.depot("12345", montant, "Dépôt"),
+                Err(ErreurBancaire::MontantNonPositif)
+            );
+            assert_eq!(
+                gestionnaire.retrait("12345", montant, "Retrait"),
+                Err(ErreurBancaire::MontantNonPositif)
+            );
+            assert_eq!(
+                gestionnaire.virement("12345", "67890", montant, "Virement"),
+                Err(ErreurBancaire::MontantNonPositif)
+            );
+        }
+        assert_eq!(gestionnaire.compte("12345").unwrap().solde(), 1500.0);
+        assert_eq!(gestionnaire.compte("12345").unwrap().historique().len(), 1);
+    }
+
+    #[test]
+    fn retrait_debite_le_compte_si_le_solde_suffit() {
+        let mut gestionnaire = gestionnaire_demo();
+        assert_eq!(gestionnaire.retrait("12345", 500.0, "Retrait"), Ok(1000.0));
+        assert_eq!(
+            gestionnaire.retrait("12345", 1000.01, "Retrait"),
+            Err(ErreurBancaire::SoldeInsuffisant)
+        );
+        assert_eq!(gestionnaire.compte("12345").unwrap().solde(), 1000.0);
+    }
+
+    #[test]
+    fn virement_entre_deux_comptes() {
+        let mut gestionnaire = gestionnaire_demo();
+        assert_eq!(
+            gestionnaire.virement("12345", "67890", 200.0, "Loyer"),
+            Ok(1300.0)
+        );
+        assert_eq!(gestionnaire.compte("67890").unwrap().solde(), 2500.0);
+        assert_eq!(
+            gestionnaire.virement("12345", "00000", 10.0, "x"),
+            Err(ErreurBancaire::CompteIntrouvable("00000".to_string()))
+        );
+        assert_eq!(
+            gestionnaire.virement("12345", "12345", 10.0, "x"),
+            Err(ErreurBancaire::VirementMemeCompte)
+        );
+        assert_eq!(gestionnaire.compte("12345").unwrap().solde(), 1300.0);
+    }
+
+    #[test]
+    fn solde_au_recalcule_depuis_l_historique() {
+        let mut gestionnaire = gestionnaire_demo();
+        gestionnaire.depot("12345", 100.0, "Dépôt").unwrap();
+        let aujourd_hui = Local::now().date_naive();
+        let hier = aujourd_hui.pred_opt().unwrap();
+
+        assert_eq!(gestionnaire.solde_au("12345", aujourd_hui), Ok(1600.0));
+        assert_eq!(gestionnaire.solde_au("12345", hier), Ok(0.0));
+
+        let soldes = gestionnaire.soldes_au(aujourd_hui);
+        let numeros: Vec<&str> = soldes.iter().map(|(c, _)| c.numero()).collect();
+        assert_eq!(numeros, ["12345", "67890"]);
+        assert_eq!(soldes.iter().map(|(_, s)| s).sum::<f32>(), 3900.0);
+    }
+
+    #[test]
+    fn solde_au_avant_ouverture_et_compte_inconnu() {
+        let mut gestionnaire = GestionnaireComptes::new();
+        let ouverture = Local::now().naive_local() - chrono::TimeDelta::days(10);
+        gestionnaire
+            .ajouter_compte(CompteBancaire::ouvert_le(
+                "1".to_string(),
+                "A".to_string(),
+                100.0,
+                ouverture,
+            ))
+            .unwrap();
+        gestionnaire.depot("1", 50.0, "Dépôt").unwrap();
+        let veille = ouverture.date().pred_opt().unwrap();
+
+        assert_eq!(gestionnaire.solde_au("1", veille), Ok(0.0));
+        assert_eq!(gestionnaire.solde_au("1", ouverture.date()), Ok(100.0));
+        assert_eq!(
+            gestionnaire.solde_au("1", Local::now().date_naive()),
+            Ok(150.0)
+        );
+        assert_eq!(
+            gestionnaire.solde_au("9", veille),
+            Err(ErreurBancaire::CompteIntrouvable("9".to_string()))
+        );
+        assert_eq!(gestionnaire.soldes_au(veille)[0].1, 0.0);
+    }
+
+    #[test]
+    fn categorisation_automatique_et_manuelle() {
+        let mut gestionnaire = gestionnaire_demo();
+        gestionnaire.regles_categories = vec![RegleCategorie {
+            mot_cle: "carrefour".to_string(),
+            categorie: "Alimentation".to_string(),
+        }];
+        gestionnaire
+            .retrait("12345", 30.0, "Courses CARREFOUR")
+            .unwrap();
+        gestionnaire.retrait("12345", 20.0, "Retrait").unwrap();
+
+        let historique = gestionnaire.compte("12345").unwrap().historique();
+        assert_eq!(historique[1].categorie, Some(Libelle::from("Alimentation")));
+        assert_eq!(historique[2].categorie, None);
+
+        gestionnaire
+            .definir_categorie("12345", 3, "Loisirs".to_string())
+            .unwrap();
+        let historique = gestionnaire.compte("12345").unwrap().historique();
+        assert_eq!(historique[2].categorie, Some(Libelle::from("Loisirs")));
+        assert_eq!(
+            gestionnaire.definir_categorie("12345", 0, "X".to_string()),
+            Err(ErreurBancaire::TransactionIntrouvable(0))
+        );
+    }
+
+    #[test]
+    fn libelles_traduits_a_l_affichage_et_categorises_en_francais() {
+        let mut gestionnaire = gestionnaire_demo();
+        gestionnaire.regles_categories = vec![RegleCategorie {
+            mot_cle: "versement prêt".to_string(),
+            categorie: "Prêt".to_string(),
+        }];
+        crate::langue::definir(Langue::Anglais);
+        let resultat = gestionnaire.ouvrir_pret(
+            "P1".to_string(),
+            "12345".to_string(),
+            1200.0,
+            0.0,
+            12,
+            ModeAmortissement::AnnuiteConstante,
+        );
+        let versement = gestionnaire.compte("12345").unwrap().historique()[1].clone();
+        let affiche = versement.libelle.to_string();
+        crate::langue::definir(Langue::Francais);
+
+        assert_eq!(resultat, Ok(()));
+        assert_eq!(affiche, "Loan payout P1");
+        assert_eq!(versement.libelle.to_string(), "Versement prêt P1");
+        assert_eq!(versement.categorie, Some(Libelle::from("Prêt")));
+    }
+
+    #[test]
+    fn regles_de_categories_chargees_puis_fichier_absent() {
+        let mut gestionnaire = gestionnaire_demo();
+        let chemin =
+            std::env::temp_dir().join(format!("tp1-categories-{}.txt", std::process::id()));
+        std::fs::write(&chemin, "loyer=Logement\n").unwrap();
+        assert_eq!(gestionnaire.charger_regles_categories(&chemin), Ok(1));
+        std::fs::remove_file(&chemin).unwrap();
+
+        assert!(matches!(
+            gestionnaire.charger_regles_categories(&chemin),
+            Err(ErreurBancaire::Fichier(_))
+        ));
+        // Les règles déjà chargées restent en place.
+        gestionnaire.retrait("12345", 700.0, "Loyer mars").unwrap();
+        let historique = gestionnaire.compte("12345").unwrap().historique();
+        assert_eq!(historique[1].categorie, Some(Libelle::from("Logement")));
+
+        assert_eq!(
+            gestionnaire.definir_categorie("12345", 3, "X".to_string()),
+            Err(ErreurBancaire::TransactionIntrouvable(3))
+        );
+        assert_eq!(
+            gestionnaire.definir_categorie("99999", 1, "X".to_string()),
+            Err(ErreurBancaire::CompteIntrouvable("99999".to_string()))
+        );
+    }
+
+    #[test]
+    fn transactions_client_regroupe_ses_comptes_courants() {
+        let mut gestionnaire = gestionnaire_demo();
+        gestionnaire
+            .ajouter_compte(CompteBancaire::new(
+                "22222".to_string(),
+                "Alice Dupont".to_string(),
+                50.0,
+            ))
+            .unwrap();
+        assert_eq!(gestionnaire.transactions_client("alice dupont").len(), 2);
+    }
+
+    #[test]
+    fn alertes_declenchees_apres_un_retrait() {
+        let mut gestionnaire = gestionnaire_demo();
+        gestionnaire
+            .ajouter_regle_alerte("12345", RegleAlerte::SoldeBas(1000.0))
+            .unwrap();
+        gestionnaire
+            .ajouter_regle_alerte("12345", RegleAlerte::RetraitImportant(200.0))
+            .unwrap();
+
+        gestionnaire.retrait("12345", 100.0, "Retrait").unwrap();
+        assert!(gestionnaire.alertes().is_empty());
+
+        gestionnaire.retrait("12345", 450.0, "Retrait").unwrap();
+        assert_eq!(gestionnaire.alertes().len(), 2);
+
+        gestionnaire.supprimer_regles_alerte("12345").unwrap();
+        gestionnaire.retrait("12345", 450.0, "Retrait").unwrap();
+        assert_eq!(gestionnaire.alertes().len(), 2);
+    }
+
+    // Retient les incidents transmis aux observateurs.
+    struct Incidents(Rc<RefCell<Vec<String>>>);
+
+    impl Observateur for Incidents {
+        fn incident(&mut self, message: &str) {
+            self.0.borrow_mut().push(message.to_string());
+        }
+    }
+
+    struct SortieEnPanne(Rc<RefCell<usize>>);
+
+    impl SortieAlerte for SortieEnPanne {
+        fn envoyer(&mut self, _alerte: &Alerte) -> io::Result<()> {
+            *self.0.borrow_mut() += 1;
+            Err(io::Error::other("collecteur injoignable"))
+        }
+
+        fn code(&self) -> String {
+            "en_panne".to_string()
+        }
+    }
+
+    #[test]
+    fn alertes_sur_compte_inconnu_et_sortie_en_panne() {
+        let mut gestionnaire = gestionnaire_demo();
+        assert_eq!(
+            gestionnaire.ajouter_regle_alerte("99999", RegleAlerte::SoldeBas(10.0)),
+            Err(ErreurBancaire::CompteIntrouvable("99999".to_string()))
+        );
+        assert_eq!(
+            gestionnaire.supprimer_regles_alerte("99999"),
+            Err(ErreurBancaire::CompteIntrouvable("99999".to_string()))
+        );
+
+        // Une sortie en panne n'empêche ni l'opération ni l'enregistrement de
+        // l'alerte ; l'échec de l'envoi est signalé aux observateurs.
+        let incidents = Rc::new(RefCell::new(Vec::new()));
+        gestionnaire
+            .observateurs
+            .push(Box::new(Incidents(Rc::clone(&incidents))));
+        let envois = Rc::new(RefCell::new(0));
+        gestionnaire
+            .ajouter_sortie_alerte(Box::new(SortieEnPanne(Rc::clone(&envois))))
+            .unwrap();
+        gestionnaire
+            .ajouter_regle_alerte("67890", RegleAlerte::SoldeBas(2000.0))
+            .unwrap();
+        assert_eq!(
+            gestionnaire.virement("12345", "67890", 100.0, "Virement"),
+            Ok(1400.0)
+        );
+        assert!(gestionnaire.alertes().is_empty());
+        assert_eq!(
+            gestionnaire.virement("67890", "12345", 500.0, "Virement"),
+            Ok(1900.0)
+        );
+        assert_eq!(*envois.borrow(), 1);
+        assert_eq!(gestionnaire.alertes().len(), 1);
+        assert_eq!(gestionnaire.alertes()[0].numero, "67890");
+        assert_eq!(
+            *incidents.borrow(),
+            [tr!("alerte.envoi_impossible", "collecteur injoignable")]
+        );
+    }
+
+    #[test]
+    fn pret_verse_le_capital_et_preleve_les_echeances() {
+        let mut gestionnaire = gestionnaire_demo();
+        gestionnaire
+            .ouvrir_pret(
+                "P1".to_string(),
+                "12345".to_string(),
+                1200.0,
+                0.0,
+                12,
+                ModeAmortissement::AnnuiteConstante,
+            )
+            .unwrap();
+        assert_eq!(gestionnaire.compte("12345").unwrap().solde(), 2700.0);
+        assert_eq!(gestionnaire.compte("P1").unwrap().solde(), -1200.0);
+        assert_eq!(
+            gestionnaire.compte("P1").unwrap().titulaire(),
+            "Alice Dupont"
+        );
+
+        let echeance = gestionnaire.prelever_echeance("P1").unwrap();
+        assert_eq!(echeance.mensualite, 100.0);
+        assert_eq!(gestionnaire.compte("12345").unwrap().solde(), 2600.0);
+        assert_eq!(gestionnaire.compte("P1").unwrap().solde(), -1100.0);
+
+        assert_eq!(
+            gestionnaire.depot("P1", 10.0, "Dépôt"),
+            Err(ErreurBancaire::OperationSurPret)
+        );
+        assert_eq!(
+            gestionnaire.prelever_echeance("12345"),
+            Err(ErreurBancaire::PasUnPret("12345".to_string()))
+        );
+    }
+
+    #[test]
+    fn pret_refuse_un_capital_une_duree_ou_un_taux_invalides() {
+        let mut gestionnaire = gestionnaire_demo();
+        let mut ouvrir = |capital, taux, duree| {
+            gestionnaire.ouvrir_pret(
+                "P1".to_string(),
+                "12345".to_string(),
+                capital,
+                taux,
+                duree,
+                ModeAmortissement::AnnuiteConstante,
+            )
+        };
+        assert_eq!(ouvrir(0.0, 3.0, 12), Err(ErreurBancaire::MontantNonPositif));
+        assert_eq!(ouvrir(1000.0, 3.0, 0), Err(ErreurBancaire::DureePretNulle));
+        assert_eq!(ouvrir(1000.0, -1.0, 12), Err(ErreurBancaire::TauxInvalide));
+        assert_eq!(
+            ouvrir(1000.0, f32::NAN, 12),
+            Err(ErreurBancaire::TauxInvalide)
+        );
+        assert!(gestionnaire.compte("P1").is_err());
+        assert_eq!(gestionnaire.compte("12345").unwrap().solde(), 1500.0);
+    }
+
+    #[test]
+    fn tableau_d_amortissement_du_pret_ouvert() {
+        let mut gestionnaire = gestionnaire_demo();
+        gestionnaire
+            .ouvrir_pret(
+                "P1".to_string(),
+                "12345".to_string(),
+                10_000.0,
+                3.6,
+                12,
+                ModeAmortissement::AnnuiteConstante,
+            )
+            .unwrap();
+        let pret = gestionnaire.compte("P1").unwrap().pret().unwrap();
+        let debut = Local::now().date_naive();
+        assert_eq!(pret.echeances.len(), 12);
+        assert_eq!(pret.echeances[0].date, debut + Months::new(1));
+        assert_eq!(pret.echeances[11].date, debut + Months::new(12));
+        assert_eq!(pret.echeances[0].interets, 30.0);
+        assert_eq!(pret.echeances[11].capital_restant, 0.0);
+        assert!(
+            pret.echeances
+                .windows(2)
+                .all(|w| w[1].capital_restant < w[0].capital_restant)
+        );
+
+        let premiere = gestionnaire.prelever_echeance("P1").unwrap();
+        let seconde = gestionnaire.prelever_echeance("P1").unwrap();
+        assert_eq!((premiere.numero, seconde.numero), (1, 2));
+        assert_eq!(
+            gestionnaire.compte("P1").unwrap().solde(),
+            -seconde.capital_restant
+        );
+        assert_eq!(
+            gestionnaire.compte("12345").unwrap().solde(),
+            11_500.0 - premiere.mensualite - seconde.mensualite
+        );
+    }
+
+    #[test]
+    fn remboursement_anticipe_reduit_la_mensualite_puis_solde_le_pret() {
+        let mut gestionnaire = gestionnaire_demo();
+        gestionnaire
+            .ouvrir_pret(
+                "P1".to_string(),
+                "12345".to_string(),
+                1200.0,
+                0.0,
+                12,
+                ModeAmortissement::AmortissementConstant,
+            )
+            .unwrap();
+
+        assert_eq!(
+            gestionnaire.remboursement_anticipe("P1", 600.0),
+            Ok(Some(50.0))
+        );
+        assert_eq!(gestionnaire.compte("P1").unwrap().solde(), -600.0);
+
+        assert_eq!(
+            gestionnaire.remboursement_anticipe("P1", 10_000.0),
+            Ok(None)
+        );
+        assert_eq!(gestionnaire.compte("P1").unwrap().solde(), 0.0);
+        assert_eq!(gestionnaire.compte("12345").unwrap().solde(), 1500.0);
+        assert_eq!(
+            gestionnaire.prelever_echeance("P1"),
+            Err(ErreurBancaire::PretSolde)
+        );
+    }
+
+    #[test]
+    fn virements_permanents_alimentent_l_objectif_jusqu_a_la_cible() {
+        let mut gestionnaire = gestionnaire_demo();
+        let date = |jour, mois| NaiveDate::from_ymd_opt(2027, mois, jour).unwrap();
+        let mut objectif = ObjectifEpargne::new("Vacances".to_string(), 1800.0, date(30, 6));
+        objectif.virement = Some(VirementPermanent {
+            source: "67890".to_string(),
+            montant: 200.0,
+            prochaine_date: date(5, 1),
+        });
+        gestionnaire
+            .ajouter_objectif("12345", objectif.clone())
+            .unwrap();
+        assert_eq!(
+            gestionnaire.ajouter_objectif("12345", objectif),
+            Err(ErreurBancaire::ObjectifExistant("Vacances".to_string()))
+        );
+
+        assert!(
+            gestionnaire
+                .executer_virements_permanents(date(4, 1))
+                .is_empty()
+        );
+        let executes = gestionnaire.executer_virements_permanents(date(10, 3));
+        let montants: Vec<f32> = executes.iter().map(|e| e.montant).collect();
+        assert_eq!(montants, [200.0, 100.0]);
+        assert_eq!(executes[1].date, date(5, 2));
+        assert_eq!(gestionnaire.compte("12345").unwrap().solde(), 1800.0);
+        assert_eq!(gestionnaire.compte("67890").unwrap().solde(), 2000.0);
+        assert_eq!(
+            gestionnaire.objectifs("12345")[0]
+                .virement
+                .as_ref()
+                .unwrap()
+                .prochaine_date,
+            date(5, 4)
+        );
+
+        gestionnaire
+            .supprimer_objectif("12345", "Vacances")
+            .unwrap();
+        assert!(gestionnaire.objectifs("12345").is_empty());
+    }
+
+    #[test]
+    fn cheques_en_attente_encaisses_puis_rejetes() {
+        let mut gestionnaire = gestionnaire_demo();
+        gestionnaire.definir_conditions_cheques(2, 20.0).unwrap();
+        let aujourd_hui = Local::now().date_naive();
+
+        let disponible = gestionnaire
+            .remise_cheque("12345", "0000123", "Bob Martin", 200.0)
+            .unwrap();
+        assert_eq!(disponible, cheque::jours_ouvres_apres(aujourd_hui, 2));
+        gestionnaire
+            .remise_cheque("12345", "0000124", "Claire Petit", 50.0)
+            .unwrap();
+        assert_eq!(
+            gestionnaire.remise_cheque("12345", "0000123", "Bob Martin", 200.0),
+            Err(ErreurBancaire::ChequeDejaRemis("0000123".to_string()))
+        );
+        assert_eq!(gestionnaire.compte("12345").unwrap().solde(), 1500.0);
+        assert_eq!(gestionnaire.montant_en_attente("12345"), 250.0);
+        // Le montant en attente ne peut pas encore être retiré.
+        assert_eq!(
+            gestionnaire.retrait("12345", 1600.0, "Retrait"),
+            Err(ErreurBancaire::SoldeInsuffisant)
+        );
+
+        assert!(gestionnaire.encaisser_cheques(aujourd_hui).is_empty());
+        assert_eq!(gestionnaire.rejeter_cheque("12345", "0000124"), Ok(1480.0));
+        let encaisses = gestionnaire.encaisser_cheques(disponible);
+        assert_eq!(encaisses.len(), 1);
+        assert_eq!(encaisses[0].etat, EtatCheque::Encaisse);
+        assert_eq!(gestionnaire.compte("12345").unwrap().solde(), 1680.0);
+        assert_eq!(gestionnaire.montant_en_attente("12345"), 0.0);
+        assert!(gestionnaire.encaisser_cheques(disponible).is_empty());
+
+        // Rejeté après encaissement : le montant est repris, frais en plus.
+        assert_eq!(gestionnaire.rejeter_cheque("12345", "0000123"), Ok(1460.0));
+        assert_eq!(
+            gestionnaire.rejeter_cheque("12345", "0000123"),
+            Err(ErreurBancaire::ChequeRejete("0000123".to_string()))
+        );
+        assert_eq!(
+            gestionnaire.rejeter_cheque("67890", "0000123"),
+            Err(ErreurBancaire::ChequeIntrouvable("0000123".to_string()))
+        );
+        let libelles: Vec<String> = gestionnaire.compte("12345").unwrap().historique()[1..]
+            .iter()
+            .map(|t| t.libelle.to_string())
+            .collect();
+        assert_eq!(
+            libelles,
+            [
+                "Frais de rejet chèque n°0000124",
+                "Remise chèque n°0000123 de Bob Martin",
+                "Rejet chèque n°0000123",
+                "Frais de rejet chèque n°0000123"
+            ]
+        );
+    }
+
+    #[test]
+    fn prelevements_sur_mandat_remboursement_et_revocation() {
+        use chrono::Days;
+
+        let mut gestionnaire = gestionnaire_demo();
+        let aujourd_hui = Local::now().date_naive();
+        let creancier = "FR12ZZZ123456";
+        gestionnaire
+            .ajouter_mandat("12345", creancier, "EDF-001", aujourd_hui, 150.0)
+            .unwrap();
+        assert_eq!(
+            gestionnaire.ajouter_mandat("67890", creancier, "EDF-001", aujourd_hui, 80.0),
+            Err(ErreurBancaire::MandatExistant("EDF-001".to_string()))
+        );
+        gestionnaire
+            .ajouter_mandat(
+                "67890",
+                creancier,
+                "EDF-002",
+                aujourd_hui + Days::new(5),
+                80.0,
+            )
+            .unwrap();
+
+        assert_eq!(
+            gestionnaire.prelever("12345", creancier, "EDF-001", 120.0),
+            Ok(1380.0)
+        );
+        assert_eq!(
+            gestionnaire.prelever("12345", creancier, "EDF-001", 200.0),
+            Err(ErreurBancaire::PlafondMandatDepasse("EDF-001".to_string()))
+        );
+        assert_eq!(
+            gestionnaire.prelever("12345", "AUTRE", "EDF-001", 10.0),
+            Err(ErreurBancaire::MandatIntrouvable("EDF-001".to_string()))
+        );
+        // Un mandat signé à une date future n'est pas encore en vigueur.
+        assert_eq!(
+            gestionnaire.prelever("67890", creancier, "EDF-002", 10.0),
+            Err(ErreurBancaire::MandatInactif("EDF-002".to_string()))
+        );
+        gestionnaire
+            .prelever("12345", creancier, "EDF-001", 40.0)
+            .unwrap();
+
+        gestionnaire
+            .revoquer_mandat("12345", creancier, "EDF-001")
+            .unwrap();
+        assert_eq!(
+            gestionnaire.prelever("12345", creancier, "EDF-001", 10.0),
+            Err(ErreurBancaire::MandatInactif("EDF-001".to_string()))
+        );
+        assert_eq!(gestionnaire.compte("12345").unwrap().solde(), 1340.0);
+
+        // Remboursable après la révocation, mais une seule fois et dans le délai.
+        assert_eq!(gestionnaire.rembourser_prelevement("12345", 1), Ok(1460.0));
+        assert_eq!(
+            gestionnaire.rembourser_prelevement("12345", 1),
+            Err(ErreurBancaire::PrelevementDejaRembourse(1))
+        );
+        gestionnaire.prelevements[1].date = aujourd_hui - Days::new(57);
+        assert_eq!(
+            gestionnaire.rembourser_prelevement("12345", 2),
+            Err(ErreurBancaire::DelaiRemboursementDepasse(2))
+        );
+        gestionnaire.definir_delai_remboursement(60).unwrap();
+        assert_eq!(gestionnaire.rembourser_prelevement("12345", 2), Ok(1500.0));
+        assert_eq!(
+            gestionnaire.rembourser_prelevement("67890", 1),
+            Err(ErreurBancaire::PrelevementIntrouvable(1))
+        );
+    }
+
+    #[test]
+    fn recapitulatif_fiscal_du_client_avec_son_pret() {
+        use chrono::Datelike;
+
+        let mut gestionnaire = gestionnaire_demo();
+        gestionnaire
+            .ouvrir_pret(
+                "P1".to_string(),
+                "12345".to_string(),
+                1200.0,
+                6.0,
+                12,
+                ModeAmortissement::AmortissementConstant,
+            )
+            .unwrap();
+        gestionnaire.prelever_echeance("P1").unwrap();
+        // Sans fichier de règles, rien n'est catégorisé : les frais sont
+        // reconnus à leur libellé.
+        assert!(
+            gestionnaire
+                .charger_regles_categories(Path::new("absent/categories.txt"))
+                .is_err()
+        );
+        gestionnaire
+            .remise_cheque("12345", "0000123", "Bob Martin", 250.0)
+            .unwrap();
+        gestionnaire.rejeter_cheque("12345", "0000123").unwrap();
+
+        // L'échéance compte pour l'année où elle a été prélevée.
+        let recapitulatif = gestionnaire
+            .recapitulatif_fiscal("alice dupont", Local::now().year())
+            .unwrap();
+        assert_eq!(recapitulatif.titulaire, "Alice Dupont");
+        let numeros: Vec<&str> = recapitulatif
+            .lignes
+            .iter()
+            .map(|l| l.numero.as_str())
+            .collect();
+        assert_eq!(numeros, ["12345", "P1"]);
+        assert_eq!(recapitulatif.lignes[0].montants.frais, 30.0);
+        assert_eq!(recapitulatif.lignes[1].montants.interets_payes, 6.0);
+        assert_eq!(recapitulatif.lignes[1].montants.capital_restant_du, 1100.0);
+        assert_eq!(recapitulatif.totaux().solde_fin_annee, 2564.0);
+        assert_eq!(
+            gestionnaire.recapitulatif_fiscal("Denis Petit", 2026),
+            Err(ErreurBancaire::ClientIntrouvable("Denis Petit".to_string()))
+        );
+
+        // Deux clients homonymes ont chacun leur récapitulatif.
+        let mut homonymes = GestionnaireComptes::new();
+        homonymes
+            .charger_jeu(
+                "client;C1;Alice Dupont\nclient;C2;Alice Dupont\n\
+                 compte;1;C1;02/01/2026;10\ncompte;2;C2;02/01/2026;20\n",
+            )
+            .unwrap();
+        let c2 = homonymes.recapitulatif_fiscal("c2", 2026).unwrap();
+        assert_eq!(
+            (c2.client.as_str(), c2.titulaire.as_str()),
+            ("C2", "Alice Dupont")
+        );
+        assert_eq!(c2.lignes.len(), 1);
+        assert_eq!(c2.totaux().solde_fin_annee, 20.0);
+    }
+
+    #[test]
+    fn comptes_inactifs_mis_en_dormance_puis_reveilles_sur_confirmation() {
+        let mut gestionnaire = gestionnaire_demo();
+        let aujourd_hui = Local::now().date_naive();
+        let classements = gestionnaire.classer_comptes_inactifs(aujourd_hui);
+        assert!(
+            classements
+                .iter()
+                .all(|c| c.classe == ClasseInactivite::Actif)
+        );
+        assert!(!gestionnaire.est_dormant("12345"));
+
+        let dans_treize_mois = aujourd_hui + Months::new(13);
+        let classements = gestionnaire.classer_comptes_inactifs(dans_treize_mois);
+        assert_eq!(classements.len(), 2);
+        assert_eq!(classements[0].classe, ClasseInactivite::Dormant);
+        assert!(gestionnaire.est_dormant("12345") && gestionnaire.est_dormant("67890"));
+
+        // La première opération doit être confirmée, puis le compte redevient actif.
+        assert_eq!(
+            gestionnaire.retrait("12345", 100.0, "Retrait"),
+            Err(ErreurBancaire::ConfirmationRequise(tr!(
+                "dormance.a_confirmer",
+                "12345",
+                Local::now().date_naive().format("%d/%m/%Y")
+            )))
+        );
+        assert_eq!(gestionnaire.compte("12345").unwrap().solde(), 1500.0);
+        assert_eq!(
+            gestionnaire
+                .avec_confirmation()
+                .retrait("12345", 100.0, "Retrait"),
+            Ok(1400.0)
+        );
+        assert!(!gestionnaire.est_dormant("12345"));
+        assert!(gestionnaire.est_dormant("67890"));
+        assert_eq!(gestionnaire.retrait("12345", 100.0, "Retrait"), Ok(1300.0));
+
+        let classements = gestionnaire.classer_comptes_inactifs(aujourd_hui + Months::new(120));
+        assert!(
+            classements
+                .iter()
+                .all(|c| c.classe == ClasseInactivite::SeuilAtteint)
+        );
+        assert!(gestionnaire.est_dormant("12345"));
+
+        // Un compte inactif pas encore classé demande aussi une confirmation.
+        gestionnaire
+            .ajouter_compte(CompteBancaire::ouvert_le(
+                "24680".to_string(),
+                "Chloé Bernard".to_string(),
+                500.0,
+                Local::now().naive_local() - chrono::TimeDelta::days(400),
+            ))
+            .unwrap();
+        assert!(matches!(
+            gestionnaire.depot("24680", 10.0, "Dépôt"),
+            Err(ErreurBancaire::ConfirmationRequise(_))
+        ));
+        assert_eq!(
+            gestionnaire
+                .avec_confirmation()
+                .depot("24680", 10.0, "Dépôt"),
+            Ok(510.0)
+        );
+        assert_eq!(gestionnaire.depot("24680", 10.0, "Dépôt"), Ok(520.0));
+    }
+
+    #[test]
+    fn journal_des_operations_rejoue_au_chargement() {
+        let dossier = std::env::temp_dir();
+        let jeu = dossier.join(format!("tp1_jeu_{}.txt", std::process::id()));
+        let journal = dossier.join(format!("tp1_operations_{}.log", std::process::id()));
+        let _ = std::fs::remove_file(&journal);
+        std::fs::write(
+            &jeu,
+            "client;C1;Alice Dupont\ncompte;12345;C1;02/01/2026;800.00\n",
+        )
+        .unwrap();
+        let coffre = Coffre::en_clair();
+
+        let mut gestionnaire = GestionnaireComptes::new();
+        assert_eq!(
+            gestionnaire.charger_fixture_journalisee(&jeu, &journal, &coffre),
+            Ok((1, 0))
+        );
+        gestionnaire.depot("12345", 200.0, "Dépôt").unwrap();
+        gestionnaire
+            .ajouter_compte(CompteBancaire::new(
+                "67890".to_string(),
+                "Bob Martin".to_string(),
+                50.0,
+            ))
+            .unwrap();
+        gestionnaire
+            .virement("12345", "67890", 100.0, "Remboursement")
+            .unwrap();
+        assert!(gestionnaire.retrait("12345", 5000.0, "Refusé").is_err());
+
+        // Le rechargement n'ouvre aucun compte aux yeux des observateurs.
+        let notifies = Rc::new(RefCell::new(Vec::new()));
+        let mut relu = GestionnaireComptes::avec_observateurs(vec![Box::new(Espion {
+            journal: Rc::clone(&notifies),
+            plafond: f32::MAX,
+        })]);
+        assert_eq!(
+            relu.charger_fixture_journalisee(&jeu, &journal, &coffre),
+            Ok((2, 5))
+        );
+        assert_eq!(relu.compte("12345").unwrap().solde(), 900.0);
+        assert_eq!(relu.compte("67890").unwrap().solde(), 150.0);
+        assert!(notifies.borrow().is_empty());
+
+        // Le journal est refusé sur un autre jeu de données.
+        std::fs::write(&jeu, "client;C1;Alice Dupont\n").unwrap();
+        assert!(
+            GestionnaireComptes::new()
+                .charger_fixture_journalisee(&jeu, &journal, &coffre)
+                .is_err()
+        );
+        let _ = std::fs::remove_file(&jeu);
+        let _ = std::fs::remove_file(&journal);
+    }
+
+    #[test]
+    fn pret_journalise_retrouve_au_redemarrage() {
+        let dossier = std::env::temp_dir();
+        let jeu = dossier.join(format!("tp1_jeu_pret_{}.txt", std::process::id()));
+        let journal = dossier.join(format!("tp1_operations_pret_{}.log", std::process::id()));
+        let _ = std::fs::remove_file(&journal);
+        std::fs::write(
+            &jeu,
+            "client;C1;Alice Dupont\ncompte;12345;C1;02/01/2026;800.00\n",
+        )
+        .unwrap();
+        let coffre = Coffre::en_clair();
+
+        let mut gestionnaire = GestionnaireComptes::new();
+        gestionnaire
+            .charger_fixture_journalisee(&jeu, &journal, &coffre)
+            .unwrap();
+        gestionnaire
+            .ouvrir_pret(
+                "P1".to_string(),
+                "12345".to_string(),
+                1200.0,
+                3.5,
+                12,
+                ModeAmortissement::AnnuiteConstante,
+            )
+            .unwrap();
+        gestionnaire.prelever_echeance("P1").unwrap();
+        gestionnaire.remboursement_anticipe("P1", 300.0).unwrap();
+        gestionnaire
+            .definir_categorie("12345", 2, "Crédit".to_string())
+            .unwrap();
+
+        let mut relu = GestionnaireComptes::new();
+        assert_eq!(
+            relu.charger_fixture_journalisee(&jeu, &journal, &coffre)
+                .map(|(comptes, _)| comptes),
+            Ok(2)
+        );
+        let (avant, apres) = (gestionnaire.pret("P1").unwrap(), relu.pret("P1").unwrap());
+        assert_eq!(apres.echeances_payees(), 1);
+        assert_eq!(apres.paiements, avant.paiements);
+        assert_eq!(apres.echeances, avant.echeances);
+        assert_eq!(apres.capital_restant, avant.capital_restant);
+        for numero in ["12345", "P1"] {
+            assert_eq!(
+                relu.compte(numero).unwrap().solde(),
+                gestionnaire.compte(numero).unwrap().solde()
+            );
+        }
+        assert_eq!(
+            relu.compte("12345").unwrap().historique[1].categorie,
+            Some(Libelle::from("Crédit"))
+        );
+
+        // Les opérations suivantes partent de l'état relu.
+        relu.prelever_echeance("P1").unwrap();
+        let mut encore = GestionnaireComptes::new();
+        encore
+            .charger_fixture_journalisee(&jeu, &journal, &coffre)
+            .unwrap();
+        assert_eq!(encore.pret("P1").unwrap().echeances_payees(), 2);
+        assert_eq!(
+            encore.compte("12345").unwrap().solde(),
+            relu.compte("12345").unwrap().solde()
+        );
+        let _ = std::fs::remove_file(&jeu);
+        let _ = std::fs::remove_file(&journal);
+    }
+
+    #[test]
+    fn journal_non_ecrit_signale_aux_observateurs() {
+        let dossier = std::env::temp_dir();
+        let jeu = dossier.join(format!("tp1_jeu_incident_{}.txt", std::process::id()));
+        let journal = dossier.join(format!(
+            "tp1_operations_incident_{}.log",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&journal);
+        std::fs::write(
+            &jeu,
+            "client;C1;Alice Dupont\ncompte;12345;C1;02/01/2026;800.00\n",
+        )
+        .unwrap();
+        let incidents = Rc::new(RefCell::new(Vec::new()));
+        let mut gestionnaire = GestionnaireComptes::avec_observateurs(vec![Box::new(Incidents(
+            Rc::clone(&incidents),
+        ))]);
+        gestionnaire
+            .charger_fixture_journalisee(&jeu, &journal, &Coffre::en_clair())
+            .unwrap();
+        // Un dossier à la place du journal empêche d'y écrire.
+        std::fs::remove_file(&journal).unwrap();
+        std::fs::create_dir(&journal).unwrap();
+
+        assert_eq!(gestionnaire.depot("12345", 200.0, "Dépôt"), Ok(1000.0));
+        assert_eq!(incidents.borrow().len(), 1);
+        assert!(incidents.borrow()[0].starts_with(&tr!("sauvegarde.ecriture_impossible", "")));
+        std::fs::remove_dir(&journal).unwrap();
+        let _ = std::fs::remove_file(&jeu);
+    }
+
+    #[test]
+    fn etat_hors_comptes_journalise_retrouve_au_redemarrage() {
+        let dossier = std::env::temp_dir();
+        let jeu = dossier.join(format!("tp1_jeu_etat_{}.txt", std::process::id()));
+        let journal = dossier.join(format!("tp1_operations_etat_{}.log", std::process::id()));
+        let _ = std::fs::remove_file(&journal);
+        std::fs::write(
+            &jeu,
+            "client;C1;Alice Dupont\ncompte;12345;C1;02/01/2026;800.00\n\
+             client;C2;Bob Martin\ncompte;67890;C2;02/01/2026;2000.00\n",
+        )
+        .unwrap();
+        let coffre = Coffre::en_clair();
+        let aujourd_hui = Local::now().date_naive();
+
+        let mut gestionnaire = GestionnaireComptes::new();
+        gestionnaire
+            .charger_fixture_journalisee(&jeu, &journal, &coffre)
+            .unwrap();
+        gestionnaire
+            .remise_cheque("12345", "0000123", "Bob Martin", 250.0)
+            .unwrap();
+        gestionnaire
+            .ajouter_mandat("12345", "EDF", "M1", aujourd_hui, 100.0)
+            .unwrap();
+        gestionnaire.prelever("12345", "EDF", "M1", 60.0).unwrap();
+        gestionnaire
+            .ajouter_regle_alerte("12345", RegleAlerte::SoldeBas(100.0))
+            .unwrap();
+        for nom in ["Vacances", "Voiture"] {
+            gestionnaire
+                .ajouter_objectif(
+                    "12345",
+                    ObjectifEpargne {
+                        nom: nom.to_string(),
+                        montant_cible: 5000.0,
+                        date_limite: aujourd_hui + Months::new(12),
+                        virement: Some(VirementPermanent {
+                            source: "67890".to_string(),
+                            montant: 50.0,
+                            prochaine_date: aujourd_hui,
+                        }),
+                    },
+                )
+                .unwrap();
+        }
+        gestionnaire
+            .supprimer_objectif("12345", "Vacances")
+            .unwrap();
+        // La prochaine date avance hors des opérations.
+        gestionnaire.executer_virements_permanents(aujourd_hui);
+        gestionnaire.ouvrir_caisse(100.0).unwrap();
+        gestionnaire.depot("67890", 20.0, "Dépôt").unwrap();
+        // Seules les parties modifiées sont relues à chaque opération ; l'état
+        // inscrit au journal reste celui de toute la banque.
+        assert_eq!(gestionnaire.etat_journalise, gestionnaire.etat());
+
+        let mut relu = GestionnaireComptes::new();
+        relu.charger_fixture_journalisee(&jeu, &journal, &coffre)
+            .unwrap();
+        assert_eq!(relu.etat(), gestionnaire.etat());
+        assert_eq!(relu.cheques("12345"), gestionnaire.cheques("12345"));
+        assert_eq!(relu.mandats("12345"), gestionnaire.mandats("12345"));
+        assert_eq!(
+            relu.prelevements("12345"),
+            gestionnaire.prelevements("12345")
+        );
+        assert_eq!(relu.regles_alerte("12345").len(), 1);
+        assert_eq!(relu.objectifs("12345"), gestionnaire.objectifs("12345"));
+        assert_eq!(
+            relu.objectifs("12345")[0]
+                .virement
+                .as_ref()
+                .unwrap()
+                .prochaine_date,
+            aujourd_hui + Months::new(1)
+        );
+        assert_eq!(relu.montant_en_attente("12345"), 250.0);
+        assert_eq!(
+            relu.compte("12345").unwrap().solde(),
+            gestionnaire.compte("12345").unwrap().solde()
+        );
+        let _ = std::fs::remove_file(&jeu);
+        let _ = std::fs::remove_file(&journal);
+    }
+
+    #[test]
+    fn caisse_du_guichet_puis_cloture_de_la_journee() {
+        let mut gestionnaire = gestionnaire_demo();
+        gestionnaire
+            .depot("12345", 10.0, "Avant ouverture")
+            .unwrap();
+        assert_eq!(
+            gestionnaire.cloturer_caisse(0.0),
+            Err(ErreurBancaire::CaisseFermee)
+        );
+
+        gestionnaire.definir_acteur("alice").unwrap();
+        gestionnaire.ouvrir_caisse(500.0).unwrap();
+        assert_eq!(
+            gestionnaire.ouvrir_caisse(100.0),
+            Err(ErreurBancaire::CaisseDejaOuverte("alice".to_string()))
+        );
+        gestionnaire.depot("12345", 200.0, "Dépôt").unwrap();
+        gestionnaire.definir_acteur("bob").unwrap();
+        gestionnaire.retrait("67890", 120.0, "Retrait").unwrap();
+        assert!(gestionnaire.retrait("67890", 9000.0, "Retrait").is_err());
+        gestionnaire
+            .virement("12345", "67890", 50.0, "Loyer")
+            .unwrap();
+
+        let caisse = gestionnaire.caisse().unwrap();
+        assert_eq!(caisse.guichetier, "alice");
+        let guichetiers: Vec<&str> = caisse
+            .mouvements
+            .iter()
+            .map(|m| m.guichetier.as_str())
+            .collect();
+        assert_eq!(guichetiers, ["alice", "bob"]);
+        assert_eq!(caisse.especes_attendues(), 580.0);
+
+        let rapport = gestionnaire.cloturer_caisse(575.0).unwrap();
+        assert_eq!(rapport.ecart(), -5.0);
+        assert!(gestionnaire.caisse().is_none());
+        assert!(gestionnaire.journee_cloturee());
+        assert_eq!(
+            gestionnaire.depot("12345", 10.0, "Dépôt"),
+            Err(ErreurBancaire::JourneeCloturee)
+        );
+        assert_eq!(
+            gestionnaire.ouvrir_caisse(500.0),
+            Err(ErreurBancaire::JourneeCloturee)
+        );
+        gestionnaire.definir_acteur("alice").unwrap();
+    }
+
+    #[test]
+    fn droits_des_roles_et_approbation_des_gros_retraits() {
+        use crate::habilitation::Utilisateur;
+
+        let mut gestionnaire = gestionnaire_demo();
+        let mut annuaire = Annuaire::new();
+        for (identifiant, role) in [
+            ("alice", Role::Guichetier),
+            ("sam", Role::Superviseur),
+            ("paul", Role::Auditeur),
+        ] {
+            annuaire
+                .ajouter(Utilisateur::avec_iterations(
+                    identifiant,
+                    role,
+                    identifiant,
+                    10,
+                ))
+                .unwrap();
+        }
+        gestionnaire.definir_seuil_approbation(1000.0).unwrap();
+        gestionnaire.definir_utilisateurs(annuaire).unwrap();
+
+        assert_eq!(
+            gestionnaire.depot("12345", 10.0, "Dépôt"),
+            Err(ErreurBancaire::NonConnecte)
+        );
+        assert_eq!(
+            gestionnaire.connecter("alice", "faux"),
+            Err(ErreurBancaire::IdentifiantsInvalides)
+        );
+        assert_eq!(
+            gestionnaire.connecter("alice", "alice"),
+            Ok(Role::Guichetier)
+        );
+        assert_eq!(gestionnaire.acteur(), "alice");
+        assert!(matches!(
+            gestionnaire.definir_acteur("sam"),
+            Err(ErreurBancaire::NonAutorise(_))
+        ));
+        assert_eq!(gestionnaire.depot("12345", 10.0, "Dépôt"), Ok(1510.0));
+        // Le guichetier ne peut pas relever le seuil pour échapper à l'approbation.
+        assert!(matches!(
+            gestionnaire.definir_seuil_approbation(5000.0),
+            Err(ErreurBancaire::NonAutorise(_))
+        ));
+        assert!(matches!(
+            gestionnaire.ouvrir_pret(
+                "P1".to_string(),
+                "12345".to_string(),
+                1000.0,
+                3.0,
+                12,
+                ModeAmortissement::AnnuiteConstante
+            ),
+            Err(ErreurBancaire::NonAutorise(_))
+        ));
+
+        assert!(matches!(
+            gestionnaire.retrait("67890", 1200.0, "Retrait"),
+            Err(ErreurBancaire::ApprobationRequise(_))
+        ));
+        assert!(matches!(
+            gestionnaire.avec_approbation("paul", "paul"),
+            Err(ErreurBancaire::NonAutorise(_))
+        ));
+        assert_eq!(
+            gestionnaire
+                .avec_approbation("sam", "sam")
+                .unwrap()
+                .retrait("67890", 1200.0, "Retrait"),
+            Ok(1100.0)
+        );
+        // L'approbation ne vaut que pour l'opération qui suit.
+        assert!(matches!(
+            gestionnaire.retrait("67890", 1050.0, "Retrait"),
+            Err(ErreurBancaire::ApprobationRequise(_))
+        ));
+        assert_eq!(gestionnaire.retrait("67890", 1000.0, "Retrait"), Ok(100.0));
+
+        gestionnaire.connecter("sam", "sam").unwrap();
+        assert_eq!(
+            gestionnaire.definir_seuil_approbation(f32::NAN),
+            Err(ErreurBancaire::MontantNonPositif)
+        );
+        gestionnaire.definir_seuil_approbation(50.0).unwrap();
+        gestionnaire.definir_cours_devise("USD", 0.9).unwrap();
+        gestionnaire.connecter("alice", "alice").unwrap();
+        assert!(matches!(
+            gestionnaire.definir_cours_devise("USD", 0.1),
+            Err(ErreurBancaire::NonAutorise(_))
+        ));
+        assert!(matches!(
+            gestionnaire.retrait("67890", 60.0, "Retrait"),
+            Err(ErreurBancaire::ApprobationRequise(_))
+        ));
+        // Tout débit au-delà du seuil est concerné, virements compris, au montant
+        // débité en euros.
+        assert!(matches!(
+            gestionnaire.virement("12345", "67890", 60.0, "Virement"),
+            Err(ErreurBancaire::ApprobationRequise(_))
+        ));
+        assert!(matches!(
+            gestionnaire.virement_externe("12345", "ACME Inc", 60.0, "USD", "Facture"),
+            Err(ErreurBancaire::ApprobationRequise(_))
+        ));
+        assert_eq!(
+            gestionnaire.virement_externe("12345", "ACME Inc", 50.0, "USD", "Facture"),
+            Ok(1465.0)
+        );
+
+        gestionnaire.connecter("paul", "paul").unwrap();
+        assert!(matches!(
+            gestionnaire.depot("12345", 10.0, "Dépôt"),
+            Err(ErreurBancaire::NonAutorise(_))
+        ));
+        assert_eq!(
+            gestionnaire.examiner_signalement(1),
+            Err(ErreurBancaire::SignalementIntrouvable(1))
+        );
+        assert_eq!(gestionnaire.compte("12345").unwrap().solde(), 1465.0);
+    }
+
+    #[test]
+    fn cle_idempotence_rejouee_retourne_le_resultat_d_origine() {
+        let mut gestionnaire = gestionnaire_demo();
+        assert_eq!(
+            gestionnaire
+                .avec_cle_idempotence("import-1")
+                .depot("12345", 100.0, "Import"),
+            Ok(1600.0)
+        );
+        gestionnaire.retrait("12345", 50.0, "Retrait").unwrap();
+        assert_eq!(
+            gestionnaire
+                .avec_cle_idempotence("import-1")
+                .depot("12345", 100.0, "Import"),
+            Ok(1600.0)
+        );
+        assert_eq!(gestionnaire.compte("12345").unwrap().solde(), 1550.0);
+        assert_eq!(
+            gestionnaire
+                .avec_cle_idempotence("import-1")
+                .depot("12345", 200.0, "Import"),
+            Err(ErreurBancaire::CleIdempotenceReutilisee(
+                "import-1".to_string()
+            ))
+        );
+
+        // Une opération en échec ne retient pas sa clé.
+        assert_eq!(
+            gestionnaire
+                .avec_cle_idempotence("import-2")
+                .retrait("11111", 10.0, "x"),
+            Err(ErreurBancaire::CompteIntrouvable("11111".to_string()))
+        );
+        assert_eq!(
+            gestionnaire
+                .avec_cle_idempotence("import-2")
+                .retrait("12345", 10.0, "x"),
+            Ok(1540.0)
+        );
+        // La clé ne vaut que pour l'opération qui suit.
+        assert_eq!(gestionnaire.retrait("12345", 10.0, "x"), Ok(1530.0));
+    }
+
+    #[test]
+    fn cle_idempotence_non_enregistree_signalee_aux_observateurs() {
+        let chemin = std::env::temp_dir().join(format!("tp1_idempotence_{}", std::process::id()));
+        let _ = std::fs::remove_dir(&chemin);
+        let incidents = Rc::new(RefCell::new(Vec::new()));
+        let mut gestionnaire = GestionnaireComptes::avec_observateurs(vec![Box::new(Incidents(
+            Rc::clone(&incidents),
+        ))]);
+        gestionnaire
+            .ajouter_compte(CompteBancaire::new(
+                "12345".to_string(),
+                "Alice Dupont".to_string(),
+                1500.0,
+            ))
+            .unwrap();
+        gestionnaire.definir_registre_idempotence(
+            RegistreIdempotence::ouvrir(
+                chemin.clone(),
+                idempotence::RETENTION_PAR_DEFAUT,
+                Coffre::en_clair(),
+            )
+            .unwrap(),
+        );
+        // Un dossier à la place du registre empêche d'y écrire.
+        std::fs::create_dir(&chemin).unwrap();
+
+        assert_eq!(
+            gestionnaire
+                .avec_cle_idempotence("import-1")
+                .depot("12345", 100.0, "Import"),
+            Ok(1600.0)
+        );
+        let incidents = incidents.borrow();
+        assert_eq!(incidents.len(), 1);
+        assert!(incidents[0].contains("import-1"));
+        std::fs::remove_dir(&chemin).unwrap();
+    }
+
+    #[test]
+    fn regles_de_fraude_signalent_confirment_ou_bloquent() {
+        use crate::fraude::{DeviseEtrangere, JusteSousLePlafond};
+        use chrono::TimeDelta;
+
+        let mut gestionnaire = gestionnaire_demo();
+        gestionnaire
+            .ajouter_regle_fraude(
+                Box::new(JusteSousLePlafond {
+                    plafond: 1000.0,
+                    marge: 0.05,
+                }),
+                Decision::Signaler,
+            )
+            .unwrap();
+        gestionnaire
+            .ajouter_regle_fraude(
+                Box::new(DeviseEtrangere {
+                    fenetre: TimeDelta::days(365),
+                }),
+                Decision::Confirmer,
+            )
+            .unwrap();
+
+        assert_eq!(gestionnaire.retrait("67890", 990.0, "Retrait"), Ok(1310.0));
+        assert_eq!(gestionnaire.signalements().len(), 1);
+
+        assert_eq!(
+            gestionnaire.virement_externe("12345", "ACME Inc", 100.0, "jpy", "Facture"),
+            Err(ErreurBancaire::DeviseInconnue("JPY".to_string()))
+        );
+        gestionnaire.definir_cours_devise("usd", 0.9).unwrap();
+        let virement = |g: &mut GestionnaireComptes| {
+            g.virement_externe("12345", "ACME Inc", 100.0, "usd", "Facture")
+        };
+        for _ in 0..2 {
+            assert!(matches!(
+                virement(&mut gestionnaire),
+                Err(ErreurBancaire::ConfirmationRequise(_))
+            ));
+        }
+        assert_eq!(gestionnaire.compte("12345").unwrap().solde(), 1500.0);
+        assert_eq!(gestionnaire.signalements().len(), 2);
+        assert!(!gestionnaire.signalements()[1].confirmee);
+        // La tentative confirmée complète le signalement de la tentative refusée.
+        // 100 dollars débités 90 euros au cours défini.
+        assert_eq!(virement(gestionnaire.avec_confirmation()), Ok(1410.0));
+        let derniere = gestionnaire
+            .compte("12345")
+            .unwrap()
+            .historique()
+            .last()
+            .unwrap();
+        assert_eq!(derniere.devise.as_deref(), Some("USD"));
+        // Un second virement en dollars n'est plus inhabituel.
+        assert_eq!(virement(&mut gestionnaire), Ok(1320.0));
+        assert_eq!(
+            gestionnaire.virement_externe("12345", "Dupont", 50.0, "EUR", "Loyer"),
+            Ok(1270.0)
+        );
+
+        let signalements = gestionnaire.signalements();
+        assert_eq!(signalements.len(), 2);
+        assert!(signalements[1].confirmee);
+
+        gestionnaire
+            .ajouter_regle_fraude(
+                Box::new(JusteSousLePlafond {
+                    plafond: 100.0,
+                    marge: 0.5,
+                }),
+                Decision::Bloquer,
+            )
+            .unwrap();
+        assert!(matches!(
+            gestionnaire
+                .avec_confirmation()
+                .retrait("12345", 60.0, "Retrait"),
+            Err(ErreurBancaire::OperationBloquee(_))
+        ));
+        gestionnaire.examiner_signalement(1).unwrap();
+        assert!(gestionnaire.signalements()[0].examine);
+        assert_eq!(
+            gestionnaire.examiner_signalement(9),
+            Err(ErreurBancaire::SignalementIntrouvable(9))
+        );
+    }
+
+    struct Espion {
+        journal: Rc<RefCell<Vec<String>>>,
+        plafond: f32,
+    }
+
+    impl Observateur for Espion {
+        fn avant(&mut self, evenement: &Evenement) -> Result<(), String> {
+            match evenement {
+                Evenement::Retrait { montant, .. } if *montant > self.plafond => {
+                    Err("plafond dépassé".to_string())
+                }
+                _ => Ok(()),
+            }
+        }
+
+        fn apres(&mut self, evenement: &Evenement, resultat: Result<(), &ErreurBancaire>) {
+            let issue = if resultat.is_ok() { "ok" } else { "échec" };
+            self.journal
+                .borrow_mut()
+                .push(format!("{} : {}", evenement, issue));
+        }
+    }
+
+    #[test]
+    fn observateurs_notifies_et_veto() {
+        let journal = Rc::new(RefCell::new(Vec::new()));
+        let espion = Espion {
+            journal: Rc::clone(&journal),
+            plafond: 100.0,
+        };
+        let mut gestionnaire = GestionnaireComptes::avec_observateurs(vec![Box::new(espion)]);
+        gestionnaire
+            .ajouter_compte(CompteBancaire::new("1".to_string(), "A".to_string(), 500.0))
+            .unwrap();
+
+        assert_eq!(gestionnaire.retrait("1", 50.0, "Retrait"), Ok(450.0));
+        assert_eq!(
+            gestionnaire.retrait("1", 200.0, "Retrait"),
+            Err(ErreurBancaire::Refusee("plafond dépassé".to_string()))
+        );
+        assert_eq!(gestionnaire.compte("1").unwrap().solde(), 450.0);
+
+        let journal = journal.borrow();
+        assert_eq!(journal.len(), 3);
+        assert!(journal[0].starts_with("Ouverture du compte 1"));
+        assert!(journal[2].ends_with("échec"));
+    }
+
+    #[test]
+    fn parametrage_notifie_et_reserve_au_superviseur() {
+        use crate::habilitation::Utilisateur;
+
+        let journal = Rc::new(RefCell::new(Vec::new()));
+        let mut gestionnaire = GestionnaireComptes::avec_observateurs(vec![Box::new(Espion {
+            journal: Rc::clone(&journal),
+            plafond: 100.0,
+        })]);
+        gestionnaire.definir_delai_remboursement(60).unwrap();
+        assert_eq!(
+            gestionnaire.definir_conditions_cheques(2, -1.0),
+            Err(ErreurBancaire::MontantNonPositif)
+        );
+        let mut annuaire = Annuaire::new();
+        annuaire
+            .ajouter(Utilisateur::avec_iterations(
+                "alice",
+                Role::Guichetier,
+                "a1",
+                10,
+            ))
+            .unwrap();
+        gestionnaire.definir_utilisateurs(annuaire).unwrap();
+        gestionnaire.connecter("alice", "a1").unwrap();
+        assert!(matches!(
+            gestionnaire.definir_delai_remboursement(30),
+            Err(ErreurBancaire::NonAutorise(_))
+        ));
+        assert!(matches!(
+            gestionnaire.ajouter_sortie_alerte(Box::new(SortieEnPanne(Rc::default()))),
+            Err(ErreurBancaire::NonAutorise(_))
+        ));
+        assert!(gestionnaire.sorties_alertes.is_empty());
+
+        let journal = journal.borrow();
+        assert_eq!(
+            journal[..3],
+            [
+                "Prélèvements remboursables pendant 60 jours : ok",
+                "Chèques disponibles après 2 jours ouvrés, frais de rejet de -1,00€ : échec",
+                "Utilisateurs autorisés : alice : ok",
+            ]
+        );
+        // Connexion, puis les deux changements refusés au guichetier.
+        assert_eq!(journal.len(), 6);
+        assert!(journal[4..].iter().all(|ligne| ligne.ends_with("échec")));
+    }
+
+    #[test]
+    fn premier_veto_retenu_et_echecs_notifies_a_tous() {
+        let premier = Rc::new(RefCell::new(Vec::new()));
+        let second = Rc::new(RefCell::new(Vec::new()));
+        let mut gestionnaire = GestionnaireComptes::avec_observateurs(vec![
+            Box::new(Espion {
+                journal: Rc::clone(&premier),
+                plafond: 100.0,
+            }),
+            Box::new(Espion {
+                journal: Rc::clone(&second),
+                plafond: 10.0,
+            }),
+        ]);
+        gestionnaire
+            .ajouter_compte(CompteBancaire::new("1".to_string(), "A".to_string(), 50.0))
+            .unwrap();
+
+        // Les deux refusent : seul le motif du premier est retourné.
+        assert_eq!(
+            gestionnaire.retrait("1", 200.0, "Retrait"),
+            Err(ErreurBancaire::Refusee("plafond dépassé".to_string()))
+        );
+        // Le second refuse seul.
+        assert!(matches!(
+            gestionnaire.retrait("1", 20.0, "Retrait"),
+            Err(ErreurBancaire::Refusee(_))
+        ));
+        // Aucun refus, mais l'opération échoue.
+        assert_eq!(
+            gestionnaire.depot("1", -5.0, "Dépôt"),
+            Err(ErreurBancaire::MontantNonPositif)
+        );
+        assert_eq!(gestionnaire.compte("1").unwrap().solde(), 50.0);
+
+        for journal in [&premier, &second] {
+            let journal = journal.borrow();
+            assert_eq!(journal.len(), 4);
+            assert!(journal[1..].iter().all(|ligne| ligne.ends_with("échec")));
+        }
+    }
+}
@@ -0,0 +1,340 @@
+//! Utilisateurs, rôles et droits : qui peut faire quelle opération.
+//!
+//! Le fichier des utilisateurs contient une ligne par utilisateur, champs séparés
+//! par `;` : identifiant, rôle, puis itérations, sel et empreinte PBKDF2 du mot de passe.
+
+use crate::chiffrement::{depuis_hex, hex};
+use crate::erreur::ErreurBancaire;
+use crate::observateur::Evenement;
+use crate::{langue, tr};
+use chacha20poly1305::aead::OsRng;
+use chacha20poly1305::aead::rand_core::RngCore;
+use sha2::Sha256;
+
+/// Itérations de la dérivation des mots de passe des nouveaux utilisateurs.
+pub const ITERATIONS_MOT_DE_PASSE: u32 = 100_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    Guichetier,
+    Superviseur,
+    Auditeur,
+}
+
+/// Familles d'opérations soumises à autorisation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Droit {
+    /// Dépôts, retraits, virements, remises de chèques, prélèvements et caisse.
+    Guichet,
+    /// Ouverture de comptes, mandats de prélèvement, objectifs d'épargne et
+    /// catégories des mouvements.
+    Comptes,
+    /// Prêts : ouverture, échéances et remboursements.
+    Credit,
+    /// Encaissement et rejet des chèques, mise en dormance des comptes inactifs,
+    /// règles d'alerte, de catégorisation et de fraude, sorties des alertes,
+    /// délais et frais, seuil d'approbation, cours des devises et utilisateurs.
+    Parametrage,
+    /// Examen des opérations signalées par le contrôle anti-fraude.
+    Controle,
+}
+
+/// Droits de chaque rôle ; la consultation des comptes est ouverte à tous.
+const MATRICE: [(Role, &[Droit]); 3] = [
+    (Role::Guichetier, &[Droit::Guichet, Droit::Comptes]),
+    (
+        Role::Superviseur,
+        &[
+            Droit::Guichet,
+            Droit::Comptes,
+            Droit::Credit,
+            Droit::Parametrage,
+            Droit::Controle,
+        ],
+    ),
+    (Role::Auditeur, &[Droit::Controle]),
+];
+
+impl Role {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Role::Guichetier => "guichetier",
+            Role::Superviseur => "superviseur",
+            Role::Auditeur => "auditeur",
+        }
+    }
+
+    /// Rôle désigné par son code ou par son libellé dans la langue courante.
+    pub fn depuis_code(code: &str) -> Option<Role> {
+        let code = code.trim().to_lowercase();
+        [Role::Guichetier, Role::Superviseur, Role::Auditeur]
+            .into_iter()
+            .find(|role| role.code() == code || role.libelle() == code)
+    }
+
+    pub fn libelle(&self) -> &'static str {
+        match self {
+            Role::Guichetier => langue::texte("role.guichetier"),
+            Role::Superviseur => langue::texte("role.superviseur"),
+            Role::Auditeur => langue::texte("role.auditeur"),
+        }
+    }
+
+    pub fn a_le_droit(&self, droit: Droit) -> bool {
+        MATRICE
+            .iter()
+            .any(|(role, droits)| role == self && droits.contains(&droit))
+    }
+}
+
+/// Droit nécessaire pour soumettre l'opération ; `None` si elle est ouverte à tous.
+pub fn droit_requis(evenement: &Evenement) -> Option<Droit> {
+    match evenement {
+        Evenement::Depot { .. }
+        | Evenement::Retrait { .. }
+        | Evenement::Virement { .. }
+        | Evenement::VirementExterne { .. }
+        | Evenement::RemiseCheque { .. }
+        | Evenement::PrelevementMandat { .. }
+        | Evenement::RemboursementPrelevement { .. }
+        | Evenement::OuvertureCaisse { .. }
+        | Evenement::ClotureCaisse { .. } => Some(Droit::Guichet),
+        Evenement::OuvertureCompte { .. }
+        | Evenement::CategorieModifiee { .. }
+        | Evenement::ObjectifAjoute { .. }
+        | Evenement::ObjectifSupprime { .. }
+        | Evenement::MandatSigne { .. }
+        | Evenement::RevocationMandat { .. } => Some(Droit::Comptes),
+        Evenement::OuverturePret { .. }
+        | Evenement::PrelevementEcheance { .. }
+        | Evenement::RemboursementAnticipe { .. } => Some(Droit::Credit),
+        Evenement::EncaissementCheque { .. }
+        | Evenement::RejetCheque { .. }
+        | Evenement::RegleAlerteAjoutee { .. }
+        | Evenement::ReglesAlerteSupprimees { .. }
+        | Evenement::ReglesCategoriesChargees { .. }
+        | Evenement::ConditionsChequesDefinies { .. }
+        | Evenement::DelaiRemboursementDefini { .. }
+        | Evenement::ConditionsDormanceDefinies { .. }
+        | Evenement::SeuilApprobationDefini { .. }
+        | Evenement::CoursDeviseDefini { .. }
+        | Evenement::UtilisateursDefinis { .. }
+        | Evenement::RegleFraudeAjoutee { .. }
+        | Evenement::SortieAlerteAjoutee { .. }
+        | Evenement::MiseEnDormance { .. } => Some(Droit::Parametrage),
+        Evenement::SignalementExamine { .. } => Some(Droit::Controle),
+        Evenement::ChangementActeur { .. }
+        | Evenement::Connexion { .. }
+        | Evenement::Approbation { .. } => None,
+    }
+}
+
+/// Personne autorisée à se connecter, avec l'empreinte de son mot de passe.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Utilisateur {
+    pub identifiant: String,
+    pub role: Role,
+    iterations: u32,
+    sel: Vec<u8>,
+    empreinte: String,
+}
+
+fn empreinte(mot_de_passe: &str, sel: &[u8], iterations: u32) -> String {
+    let mut cle = [0u8; 32];
+    pbkdf2::pbkdf2_hmac::<Sha256>(mot_de_passe.as_bytes(), sel, iterations, &mut cle);
+    hex(&cle)
+}
+
+impl Utilisateur {
+    pub fn new(identifiant: &str, role: Role, mot_de_passe: &str) -> Self {
+        Utilisateur::avec_iterations(identifiant, role, mot_de_passe, ITERATIONS_MOT_DE_PASSE)
+    }
+
+    /// Comme `new`, avec un coût de dérivation choisi.
+    pub fn avec_iterations(
+        identifiant: &str,
+        role: Role,
+        mot_de_passe: &str,
+        iterations: u32,
+    ) -> Self {
+        let mut sel = vec![0; 16];
+        OsRng.fill_bytes(&mut sel);
+        Utilisateur {
+            identifiant: identifiant.trim().to_string(),
+            role,
+            iterations,
+            empreinte: empreinte(mot_de_passe, &sel, iterations),
+            sel,
+        }
+    }
+
+    pub fn verifier(&self, mot_de_passe: &str) -> bool {
+        empreinte(mot_de_passe, &self.sel, self.iterations) == self.empreinte
+    }
+}
+
+/// Utilisateurs connus, dans l'ordre de leur création.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Annuaire {
+    utilisateurs: Vec<Utilisateur>,
+}
+
+impl Annuaire {
+    pub fn new() -> Self {
+        Annuaire::default()
+    }
+
+    /// Lit un fichier d'utilisateurs ; une ligne invalide est signalée avec son numéro.
+    pub fn lire(contenu: &str) -> Result<Self, ErreurBancaire> {
+        let mut annuaire = Annuaire::new();
+        for (i, ligne) in contenu.lines().enumerate() {
+            let ligne = ligne.trim();
+            if ligne.is_empty() || ligne.starts_with('#') {
+                continue;
+            }
+            let champs: Vec<&str> = ligne.split(';').collect();
+            let utilisateur = match champs[..] {
+                [identifiant, role, iterations, sel, empreinte] => Role::depuis_code(role)
+                    .and_then(|role| {
+                        Some(Utilisateur {
+                            identifiant: identifiant.to_string(),
+                            role,
+                            iterations: iterations.parse().ok()?,
+                            sel: depuis_hex(sel)?,
+                            empreinte: empreinte.to_string(),
+                        })
+                    }),
+                _ => None,
+            };
+            let utilisateur = utilisateur.ok_or_else(|| {
+                ErreurBancaire::Fichier(tr!(
+                    "fixture.erreur_ligne",
+                    i + 1,
+                    tr!("fixture.ligne_invalide", ligne)
+                ))
+            })?;
+            annuaire.ajouter(utilisateur)?;
+        }
+        Ok(annuaire)
+    }
+
+    /// Contenu du fichier d'utilisateurs, relu par [`Annuaire::lire`].
+    pub fn contenu(&self) -> String {
+        self.utilisateurs
+            .iter()
+            .map(|u| {
+                format!(
+                    "{};{};{};{};{}\n",
+                    u.identifiant,
+                    u.role.code(),
+                    u.iterations,
+                    hex(&u.sel),
+                    u.empreinte
+                )
+            })
+            .collect()
+    }
+
+    pub fn ajouter(&mut self, utilisateur: Utilisateur) -> Result<(), ErreurBancaire> {
+        if utilisateur.identifiant.is_empty() || utilisateur.identifiant.contains(';') {
+            return Err(ErreurBancaire::IdentifiantsInvalides);
+        }
+        if self.utilisateur(&utilisateur.identifiant).is_some() {
+            return Err(ErreurBancaire::UtilisateurExistant(utilisateur.identifiant));
+        }
+        self.utilisateurs.push(utilisateur);
+        Ok(())
+    }
+
+    pub fn utilisateur(&self, identifiant: &str) -> Option<&Utilisateur> {
+        self.utilisateurs
+            .iter()
+            .find(|u| u.identifiant == identifiant)
+    }
+
+    /// Utilisateur dont l'identifiant et le mot de passe correspondent. L'erreur
+    /// ne dit pas lequel des deux est faux.
+    pub fn authentifier(
+        &self,
+        identifiant: &str,
+        mot_de_passe: &str,
+    ) -> Result<&Utilisateur, ErreurBancaire> {
+        self.utilisateur(identifiant)
+            .filter(|u| u.verifier(mot_de_passe))
+            .ok_or(ErreurBancaire::IdentifiantsInvalides)
+    }
+
+    pub fn identifiants(&self) -> impl Iterator<Item = &str> {
+        self.utilisateurs.iter().map(|u| u.identifiant.as_str())
+    }
+
+    pub fn len(&self) -> usize {
+        self.utilisateurs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.utilisateurs.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matrice_des_droits() {
+        assert!(Role::Guichetier.a_le_droit(Droit::Guichet));
+        assert!(!Role::Guichetier.a_le_droit(Droit::Credit));
+        assert!(!Role::Auditeur.a_le_droit(Droit::Guichet));
+        assert!(Role::Auditeur.a_le_droit(Droit::Controle));
+        assert!(Role::Superviseur.a_le_droit(Droit::Parametrage));
+        assert_eq!(Role::depuis_code(" Superviseur"), Some(Role::Superviseur));
+        assert_eq!(Role::depuis_code("directeur"), None);
+    }
+
+    #[test]
+    fn annuaire_relu_et_authentification() {
+        let mut annuaire = Annuaire::new();
+        annuaire
+            .ajouter(Utilisateur::avec_iterations(
+                "alice",
+                Role::Guichetier,
+                "a1",
+                10,
+            ))
+            .unwrap();
+        annuaire
+            .ajouter(Utilisateur::avec_iterations(
+                "sam",
+                Role::Superviseur,
+                "s1",
+                10,
+            ))
+            .unwrap();
+        assert_eq!(
+            annuaire.ajouter(Utilisateur::avec_iterations(
+                "alice",
+                Role::Auditeur,
+                "x",
+                10
+            )),
+            Err(ErreurBancaire::UtilisateurExistant("alice".to_string()))
+        );
+
+        let relu = Annuaire::lire(&annuaire.contenu()).unwrap();
+        assert_eq!(relu, annuaire);
+        assert_eq!(
+            relu.authentifier("sam", "s1").unwrap().role,
+            Role::Superviseur
+        );
+        assert_eq!(
+            relu.authentifier("sam", "a1"),
+            Err(ErreurBancaire::IdentifiantsInvalides)
+        );
+        assert_eq!(
+            relu.authentifier("inconnu", "a1"),
+            Err(ErreurBancaire::IdentifiantsInvalides)
+        );
+        assert!(Annuaire::lire("bob;directeur;10;00;00").is_err());
+    }
+}
@@ -0,0 +1,374 @@
+//! Clés d'idempotence : une opération rejouée avec la même clé n'est pas
+//! exécutée une seconde fois, son résultat d'origine est retourné.
+
+use crate::chiffrement::Coffre;
+use crate::observateur::Evenement;
+use crate::pret::Echeance;
+use chrono::{DateTime, Local, NaiveDate, TimeDelta};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::io;
+use std::path::PathBuf;
+
+/// Durée de conservation des clés par défaut.
+pub const RETENTION_PAR_DEFAUT: TimeDelta = TimeDelta::days(7);
+
+/// Résultat d'opération que le registre sait conserver sous forme de texte.
+pub(crate) trait Rejouable: Sized {
+    fn encoder(&self) -> String;
+    fn decoder(texte: &str) -> Option<Self>;
+}
+
+impl Rejouable for () {
+    fn encoder(&self) -> String {
+        String::new()
+    }
+
+    fn decoder(_texte: &str) -> Option<Self> {
+        Some(())
+    }
+}
+
+impl Rejouable for f32 {
+    fn encoder(&self) -> String {
+        self.to_string()
+    }
+
+    fn decoder(texte: &str) -> Option<Self> {
+        texte.parse().ok()
+    }
+}
+
+impl Rejouable for usize {
+    fn encoder(&self) -> String {
+        self.to_string()
+    }
+
+    fn decoder(texte: &str) -> Option<Self> {
+        texte.parse().ok()
+    }
+}
+
+impl Rejouable for Option<f32> {
+    fn encoder(&self) -> String {
+        self.map(|valeur| valeur.to_string()).unwrap_or_default()
+    }
+
+    fn decoder(texte: &str) -> Option<Self> {
+        if texte.is_empty() {
+            return Some(None);
+        }
+        texte.parse().ok().map(Some)
+    }
+}
+
+impl Rejouable for NaiveDate {
+    fn encoder(&self) -> String {
+        self.to_string()
+    }
+
+    fn decoder(texte: &str) -> Option<Self> {
+        texte.parse().ok()
+    }
+}
+
+impl Rejouable for Echeance {
+    fn encoder(&self) -> String {
+        format!(
+            "{};{};{};{};{};{}",
+            self.numero,
+            self.date,
+            self.mensualite,
+            self.interets,
+            self.amortissement,
+            self.capital_restant
+        )
+    }
+
+    fn decoder(texte: &str) -> Option<Self> {
+        let champs: Vec<&str> = texte.split(';').collect();
+        let [
+            numero,
+            date,
+            mensualite,
+            interets,
+            amortissement,
+            capital_restant,
+        ] = champs[..]
+        else {
+            return None;
+        };
+        Some(Echeance {
+            numero: numero.parse().ok()?,
+            date: date.parse::<NaiveDate>().ok()?,
+            mensualite: mensualite.parse().ok()?,
+            interets: interets.parse().ok()?,
+            amortissement: amortissement.parse().ok()?,
+            capital_restant: capital_restant.parse().ok()?,
+        })
+    }
+}
+
+/// Empreinte d'une opération, pour reconnaître une clé réutilisée sur une autre opération.
+pub(crate) fn empreinte(operation: &Evenement) -> String {
+    format!("{:x}", Sha256::digest(operation.forme_canonique()))
+}
+
+#[derive(Debug, Clone)]
+struct EntreeIdempotence {
+    date: DateTime<Local>,
+    empreinte: String,
+    resultat: String,
+}
+
+/// Ce que le registre sait d'une clé.
+#[derive(Debug, PartialEq)]
+pub(crate) enum Rejeu {
+    Inconnue,
+    Resultat(String),
+    AutreOperation,
+}
+
+/// Clés déjà traitées et résultat de leur opération, conservés pendant la durée
+/// de rétention. Seules les opérations réussies sont retenues : une opération
+/// en échec n'a rien modifié et peut être retentée avec la même clé.
+///
+/// Avec un fichier, chaque clé y est ajoutée sur une ligne ; les clés expirées
+/// sont retirées du fichier à l'ouverture. Le fichier est chiffré si le coffre
+/// l'est, car les clés et résultats décrivent les opérations des clients.
+pub struct RegistreIdempotence {
+    chemin: Option<PathBuf>,
+    coffre: Coffre,
+    retention: TimeDelta,
+    entrees: HashMap<String, EntreeIdempotence>,
+}
+
+// Les tabulations et retours à la ligne sont réservés au format du fichier.
+fn nettoyer(texte: &str) -> String {
+    texte.replace(['\t', '\n', '\r'], " ")
+}
+
+impl RegistreIdempotence {
+    /// Registre perdu à la fin du programme.
+    pub fn en_memoire(retention: TimeDelta) -> Self {
+        RegistreIdempotence {
+            chemin: None,
+            coffre: Coffre::en_clair(),
+            retention,
+            entrees: HashMap::new(),
+        }
+    }
+
+    /// Registre conservé dans `chemin`, créé à la première clé enregistrée.
+    pub fn ouvrir(chemin: PathBuf, retention: TimeDelta, coffre: Coffre) -> io::Result<Self> {
+        let mut registre = RegistreIdempotence::en_memoire(retention);
+        let contenu = match coffre.lire(&chemin) {
+            Ok(contenu) => contenu,
+            Err(e) if e.introuvable() => String::new(),
+            Err(e) => return Err(e.into()),
+        };
+        registre.coffre = coffre;
+
+        let mut lignes = 0;
+        for ligne in contenu.lines() {
+            lignes += 1;
+            let champs: Vec<&str> = ligne.split('\t').collect();
+            let [cle, date, empreinte, resultat] = champs[..] else {
+                continue;
+            };
+            let Ok(date) = DateTime::parse_from_rfc3339(date) else {
+                continue;
+            };
+            registre.entrees.insert(
+                cle.to_string(),
+                EntreeIdempotence {
+                    date: date.with_timezone(&Local),
+                    empreinte: empreinte.to_string(),
+                    resultat: resultat.to_string(),
+                },
+            );
+        }
+
+        registre.purger(Local::now());
+        registre.chemin = Some(chemin);
+        if registre.entrees.len() < lignes {
+            registre.reecrire()?;
+        }
+        Ok(registre)
+    }
+
+    fn purger(&mut self, maintenant: DateTime<Local>) {
+        let limite = maintenant - self.retention;
+        self.entrees.retain(|_, entree| entree.date >= limite);
+    }
+
+    fn ligne(cle: &str, entree: &EntreeIdempotence) -> String {
+        format!(
+            "{}\t{}\t{}\t{}",
+            cle,
+            entree.date.to_rfc3339(),
+            entree.empreinte,
+            entree.resultat
+        )
+    }
+
+    fn reecrire(&self) -> io::Result<()> {
+        let Some(chemin) = &self.chemin else {
+            return Ok(());
+        };
+        let mut cles: Vec<&String> = self.entrees.keys().collect();
+        cles.sort_by_key(|cle| self.entrees[*cle].date);
+        let contenu: String = cles
+            .into_iter()
+            .map(|cle| RegistreIdempotence::ligne(cle, &self.entrees[cle]) + "\n")
+            .collect();
+        Ok(self.coffre.ecrire(chemin, &contenu)?)
+    }
+
+    /// Retire les clés enregistrées après `date`, dont les opérations ont été
+    /// annulées par un retour à cet instant, et retourne leur nombre.
+    pub fn oublier_apres(&mut self, date: DateTime<Local>) -> io::Result<usize> {
+        let avant = self.entrees.len();
+        self.entrees.retain(|_, entree| entree.date <= date);
+        let oubliees = avant - self.entrees.len();
+        if oubliees > 0 {
+            self.reecrire()?;
+        }
+        Ok(oubliees)
+    }
+
+    /// Nombre de clés encore retenues.
+    pub fn len(&self) -> usize {
+        self.entrees.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entrees.is_empty()
+    }
+
+    pub(crate) fn rechercher(&self, cle: &str, empreinte: &str) -> Rejeu {
+        let cle = nettoyer(cle);
+        match self.entrees.get(&cle) {
+            Some(entree) if entree.date < Local::now() - self.retention => Rejeu::Inconnue,
+            Some(entree) if entree.empreinte == empreinte => {
+                Rejeu::Resultat(entree.resultat.clone())
+            }
+            Some(_) => Rejeu::AutreOperation,
+            None => Rejeu::Inconnue,
+        }
+    }
+
+    pub(crate) fn enregistrer(
+        &mut self,
+        cle: &str,
+        empreinte: String,
+        resultat: String,
+    ) -> io::Result<()> {
+        let cle = nettoyer(cle);
+        let entree = EntreeIdempotence {
+            date: Local::now(),
+            empreinte,
+            resultat: nettoyer(&resultat),
+        };
+        if let Some(chemin) = &self.chemin {
+            self.coffre
+                .ajouter_ligne(chemin, &RegistreIdempotence::ligne(&cle, &entree))?;
+        }
+        self.entrees.insert(cle, entree);
+        Ok(())
+    }
+}
+
+impl Default for RegistreIdempotence {
+    fn default() -> Self {
+        RegistreIdempotence::en_memoire(RETENTION_PAR_DEFAUT)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alerte::RegleAlerte;
+    use crate::langue::{self, Langue};
+    use std::fs::{self, OpenOptions};
+    use std::io::Write;
+
+    #[test]
+    fn empreinte_sur_une_forme_canonique() {
+        let depot = |numero: &str, libelle: &str| Evenement::Depot {
+            numero: numero.to_string(),
+            montant: 10.5,
+            libelle: libelle.to_string(),
+        };
+        assert_eq!(depot("1", "x").forme_canonique(), "depot;1:1;4:10.5;1:x");
+        assert_ne!(
+            empreinte(&depot("1;1:2", "x")),
+            empreinte(&depot("1", "2;1:x"))
+        );
+
+        // La langue de l'interface ne change pas l'empreinte.
+        let regle = Evenement::RegleAlerteAjoutee {
+            numero: "1".to_string(),
+            regle: RegleAlerte::SoldeBas(100.0),
+        };
+        let en_francais = empreinte(&regle);
+        langue::definir(Langue::Anglais);
+        let en_anglais = empreinte(&regle);
+        langue::definir(Langue::Francais);
+        assert_eq!(en_francais, en_anglais);
+        assert_eq!(
+            regle.forme_canonique(),
+            "regle_alerte_ajoutee;1:1;13:solde_bas:100"
+        );
+    }
+
+    #[test]
+    fn registre_persistant_et_retention() {
+        let chemin =
+            std::env::temp_dir().join(format!("tp1_idempotence_{}.txt", std::process::id()));
+        let _ = fs::remove_file(&chemin);
+
+        let mut registre =
+            RegistreIdempotence::ouvrir(chemin.clone(), TimeDelta::hours(1), Coffre::en_clair())
+                .unwrap();
+        registre
+            .enregistrer("import-1", "abc".to_string(), 42.5.encoder())
+            .unwrap();
+        let ancienne = (Local::now() - TimeDelta::hours(2)).to_rfc3339();
+        let mut fichier = OpenOptions::new().append(true).open(&chemin).unwrap();
+        writeln!(fichier, "import-0\t{}\tabc\t1", ancienne).unwrap();
+
+        let mut registre =
+            RegistreIdempotence::ouvrir(chemin.clone(), TimeDelta::hours(1), Coffre::en_clair())
+                .unwrap();
+        assert_eq!(registre.len(), 1);
+        assert_eq!(
+            registre.rechercher("import-1", "abc"),
+            Rejeu::Resultat("42.5".to_string())
+        );
+        assert_eq!(
+            registre.rechercher("import-1", "def"),
+            Rejeu::AutreOperation
+        );
+        assert_eq!(registre.rechercher("import-0", "abc"), Rejeu::Inconnue);
+        assert!(!fs::read_to_string(&chemin).unwrap().contains("import-0"));
+
+        // Un retour à un instant passé oublie les clés enregistrées ensuite.
+        let oubliees = registre
+            .oublier_apres(Local::now() - TimeDelta::minutes(5))
+            .unwrap();
+        assert_eq!(oubliees, 1);
+        assert!(!fs::read_to_string(&chemin).unwrap().contains("import-1"));
+
+        let echeance = Echeance {
+            numero: 3,
+            date: NaiveDate::from_ymd_opt(2027, 1, 5).unwrap(),
+            mensualite: 100.25,
+            interets: 0.1,
+            amortissement: 100.15,
+            capital_restant: 899.85,
+        };
+        assert_eq!(Echeance::decoder(&echeance.encoder()), Some(echeance));
+        assert_eq!(Option::<f32>::decoder(&None::<f32>.encoder()), Some(None));
+    }
+}
@@ -0,0 +1,217 @@
+//! Catalogues de messages français et anglais, et format des nombres.
+
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::sync::OnceLock;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Langue {
+    Francais,
+    Anglais,
+}
+
+impl Langue {
+    /// Reconnaît `fr`, `en` ou une locale comme `en_US.UTF-8`.
+    pub fn depuis_code(code: &str) -> Option<Self> {
+        let code = code.trim().to_lowercase();
+        if code.starts_with("fr") {
+            Some(Langue::Francais)
+        } else if code.starts_with("en") {
+            Some(Langue::Anglais)
+        } else {
+            None
+        }
+    }
+
+    /// Langue demandée par `TP1_LANG`, sinon par `LANG`, sinon le français.
+    pub fn depuis_environnement() -> Self {
+        ["TP1_LANG", "LANG"]
+            .iter()
+            .filter_map(|variable| std::env::var(variable).ok())
+            .find_map(|code| Langue::depuis_code(&code))
+            .unwrap_or(Langue::Francais)
+    }
+
+    fn separateur_decimal(&self) -> char {
+        match self {
+            Langue::Francais => ',',
+            Langue::Anglais => '.',
+        }
+    }
+
+    fn catalogue(&self) -> &'static HashMap<&'static str, String> {
+        static FRANCAIS: OnceLock<HashMap<&'static str, String>> = OnceLock::new();
+        static ANGLAIS: OnceLock<HashMap<&'static str, String>> = OnceLock::new();
+        match self {
+            Langue::Francais => {
+                FRANCAIS.get_or_init(|| charger_catalogue(include_str!("../langues/fr.txt")))
+            }
+            Langue::Anglais => {
+                ANGLAIS.get_or_init(|| charger_catalogue(include_str!("../langues/en.txt")))
+            }
+        }
+    }
+}
+
+// La langue est propre au fil d'exécution : le programme n'en utilise qu'un,
+// et les tests peuvent changer de langue sans se gêner les uns les autres.
+thread_local! {
+    static LANGUE: Cell<Langue> = const { Cell::new(Langue::Francais) };
+}
+
+pub fn definir(langue: Langue) {
+    LANGUE.with(|courante| courante.set(langue));
+}
+
+pub fn courante() -> Langue {
+    LANGUE.with(Cell::get)
+}
+
+/// Lit les lignes `cle=texte` d'un catalogue. Le texte est pris tel quel
+/// (espaces compris) ; `\n` y désigne un retour à la ligne.
+fn charger_catalogue(contenu: &'static str) -> HashMap<&'static str, String> {
+    contenu
+        .lines()
+        .filter(|ligne| !ligne.trim().is_empty() && !ligne.starts_with('#'))
+        .filter_map(|ligne| ligne.split_once('='))
+        .map(|(cle, texte)| (cle.trim(), texte.replace("\\n", "\n")))
+        .collect()
+}
+
+/// Message `cle` dans la langue courante, à défaut en français, à défaut la clé elle-même.
+pub fn texte(cle: &'static str) -> &'static str {
+    texte_en(courante(), cle)
+}
+
+fn texte_en(langue: Langue, cle: &'static str) -> &'static str {
+    langue
+        .catalogue()
+        .get(cle)
+        .or_else(|| Langue::Francais.catalogue().get(cle))
+        .map_or(cle, String::as_str)
+}
+
+/// Clé du catalogue égale à `ecrite`, pour relire un message enregistré sous forme de code.
+pub fn cle(ecrite: &str) -> Option<&'static str> {
+    Langue::Francais
+        .catalogue()
+        .get_key_value(ecrite)
+        .map(|(cle, _)| *cle)
+}
+
+/// Remplace dans l'ordre chaque `{}` du message `cle` par un argument.
+pub fn formater(cle: &'static str, arguments: &[&dyn Display]) -> String {
+    formater_en(courante(), cle, arguments)
+}
+
+/// Comme [`formater`], dans la langue `langue` plutôt que dans la langue courante.
+pub fn formater_en(langue: Langue, cle: &'static str, arguments: &[&dyn Display]) -> String {
+    let mut morceaux = texte_en(langue, cle).split("{}");
+    let mut resultat = morceaux.next().unwrap_or("").to_string();
+    for (i, morceau) in morceaux.enumerate() {
+        if let Some(argument) = arguments.get(i) {
+            resultat.push_str(&argument.to_string());
+        }
+        resultat.push_str(morceau);
+    }
+    resultat
+}
+
+/// Nombre à deux décimales avec le séparateur décimal de la langue courante.
+pub fn nombre(valeur: f32) -> String {
+    format!("{:.2}", valeur).replace('.', &courante().separateur_decimal().to_string())
+}
+
+/// Montant en euros, par exemple `1500,00€` en français et `1500.00€` en anglais.
+pub fn montant(valeur: f32) -> String {
+    format!("{}€", nombre(valeur))
+}
+
+/// Lit un nombre saisi avec le séparateur décimal de la langue courante.
+/// Le point est toujours accepté ; en anglais la virgule sépare les milliers.
+pub fn lire_nombre(saisie: &str) -> Option<f32> {
+    let saisie = saisie.trim();
+    let normalisee = match courante() {
+        Langue::Francais => saisie.replace(',', "."),
+        Langue::Anglais => sans_separateurs_milliers(saisie)?,
+    };
+    normalisee
+        .parse::<f32>()
+        .ok()
+        .filter(|nombre| nombre.is_finite())
+}
+
+// Une virgule mal placée est ambiguë (« 12,50 » saisi à la française) :
+// elle n'est acceptée que par groupes de trois chiffres avant le point.
+fn sans_separateurs_milliers(saisie: &str) -> Option<String> {
+    let (entier, decimales) = saisie.split_once('.').unwrap_or((saisie, ""));
+    if !entier.contains(',') {
+        return (!decimales.contains(',')).then(|| saisie.to_string());
+    }
+    let mut groupes = entier.trim_start_matches(['-', '+']).split(',');
+    let premier = groupes.next().unwrap_or_default();
+    let bien_places = (1..=3).contains(&premier.len()) && groupes.all(|groupe| groupe.len() == 3);
+    (bien_places && !decimales.contains(',')).then(|| saisie.replace(',', ""))
+}
+
+/// Message traduit : `tr!("cle")` ou `tr!("cle", argument, ...)` pour remplir les `{}`.
+#[macro_export]
+macro_rules! tr {
+    ($cle:expr) => {
+        $crate::langue::texte($cle).to_string()
+    };
+    ($cle:expr, $($argument:expr),+ $(,)?) => {
+        $crate::langue::formater($cle, &[$(&$argument as &dyn std::fmt::Display),+])
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn catalogues_complets_et_coherents() {
+        let francais = Langue::Francais.catalogue();
+        let anglais = Langue::Anglais.catalogue();
+        for (cle, texte) in francais {
+            let traduction = anglais
+                .get(cle)
+                .unwrap_or_else(|| panic!("{} manque en anglais", cle));
+            assert_eq!(
+                texte.matches("{}").count(),
+                traduction.matches("{}").count(),
+                "nombre d'arguments différent pour {}",
+                cle
+            );
+        }
+        for cle in anglais.keys() {
+            assert!(francais.contains_key(cle), "{} manque en français", cle);
+        }
+    }
+
+    #[test]
+    fn nombres_selon_la_langue() {
+        definir(Langue::Anglais);
+        assert_eq!(montant(1500.0), "1500.00€");
+        assert_eq!(lire_nombre("1,500.5"), Some(1500.5));
+        assert_eq!(lire_nombre("-1,234,567"), Some(-1234567.0));
+        for ambigue in ["12,50", "1,5000", ",500", "1,500.5,0", "1,,500"] {
+            assert_eq!(lire_nombre(ambigue), None, "{}", ambigue);
+        }
+        assert_eq!(tr!("erreur.solde_insuffisant"), "Insufficient balance");
+
+        definir(Langue::Francais);
+        assert_eq!(montant(-12.5), "-12,50€");
+        assert_eq!(lire_nombre("12,5"), Some(12.5));
+        assert_eq!(lire_nombre("12.5"), Some(12.5));
+        for saisie in ["NaN", "inf", "-Infinity", "1e39"] {
+            assert_eq!(lire_nombre(saisie), None);
+        }
+        assert_eq!(
+            tr!("erreur.compte_introuvable", "42"),
+            "Compte 42 non trouvé"
+        );
+        assert_eq!(Langue::depuis_code("en_US.UTF-8"), Some(Langue::Anglais));
+    }
+}
@@ -0,0 +1,52 @@
+//! Gestion de comptes bancaires : comptes courants, prêts amortissables
+//! et historique des mouvements.
+//!
+//! Autour des comptes : caisse du guichet, rôles des utilisateurs, chèques,
+//! prélèvements, catégorisation, rapports, récapitulatifs fiscaux, comptes
+//! dormants, sauvegardes, alertes et objectifs d'épargne.
+//!
+//! Toutes les opérations passent par [`GestionnaireComptes`] et retournent un
+//! `Result` dont l'erreur est une [`ErreurBancaire`].
+//!
+//! ```
+//! use tp1::{CompteBancaire, GestionnaireComptes};
+//!
+//! let mut gestionnaire = GestionnaireComptes::new();
+//! gestionnaire
+//!     .ajouter_compte(CompteBancaire::new("12345".to_string(), "Alice Dupont".to_string(), 100.0))
+//!     .unwrap();
+//! let solde = gestionnaire.depot("12345", 50.0, "Dépôt").unwrap();
+//! assert_eq!(solde, 150.0);
+//! ```
+
+pub mod alerte;
+pub mod audit;
+pub mod caisse;
+pub mod categorie;
+pub mod cheque;
+pub mod chiffrement;
+pub mod compte;
+pub mod dormance;
+pub mod erreur;
+pub mod etat;
+pub mod fiscal;
+pub mod fixture;
+pub mod fraude;
+pub mod gestionnaire;
+pub mod habilitation;
+pub mod idempotence;
+pub mod langue;
+pub mod objectif;
+pub mod observateur;
+pub mod prelevement;
+pub mod pret;
+pub mod rapport;
+pub mod recherche;
+pub mod releve;
+pub mod sauvegarde;
+pub mod transaction;
+
+pub use compte::{CompteBancaire, TypeCompte};
+pub use erreur::ErreurBancaire;
+pub use gestionnaire::GestionnaireComptes;
+pub use transaction::Transaction;
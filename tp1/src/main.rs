@@ -1,220 +1,2063 @@
-use std::collections::HashMap;
-use std::io::{self, Write};
+mod console;
+mod repl;
+mod tui;
 
-struct CompteBancaire {
-    numero: String,
-    titulaire: String,
-    solde: f32,
-}
+use chrono::{Datelike, Local, NaiveDate, NaiveDateTime, TimeDelta, TimeZone};
+use console::Console;
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use tp1::alerte::{RegleAlerte, SortieConsole, SortieFichier, SortieTcp};
+use tp1::audit::{self, JournalAudit};
+use tp1::chiffrement::{self, Coffre};
+use tp1::dormance;
+use tp1::fixture;
+use tp1::fraude::{Decision, DeviseEtrangere, JusteSousLePlafond, RetraitsRapproches};
+use tp1::gestionnaire::DEVISE_COMPTES;
+use tp1::habilitation::{Annuaire, Role, Utilisateur};
+use tp1::idempotence::{RETENTION_PAR_DEFAUT, RegistreIdempotence};
+use tp1::langue::{self, Langue};
+use tp1::objectif::{ObjectifEpargne, VirementPermanent};
+use tp1::observateur::{IncidentsConsole, Observateur, TraceConsole};
+use tp1::pret::ModeAmortissement;
+use tp1::rapport;
+use tp1::recherche::{self, EtatCompte, FiltreComptes, TriComptes};
+use tp1::releve::{self, ModeleReleve};
+use tp1::sauvegarde::{self, Archive};
+use tp1::{CompteBancaire, ErreurBancaire, GestionnaireComptes, tr};
 
-impl CompteBancaire {
-    fn new(numero: String, titulaire: String, solde_initial: f32) -> Self {
-        CompteBancaire {
-            numero,
-            titulaire,
-            solde: solde_initial,
-        }
+const COMPTES_PAR_PAGE: usize = 10;
+
+fn selectionner_compte<R: BufRead, W: Write>(
+    console: &mut Console<R, W>,
+    gestionnaire: &GestionnaireComptes,
+) -> io::Result<Option<String>> {
+    let comptes = gestionnaire.comptes();
+    if comptes.is_empty() {
+        writeln!(console.sortie, "{}", tr!("selection.aucun_compte"))?;
+        return Ok(None);
     }
 
-    fn afficher_solde(&self) {
-        println!(
-            "Compte: {} | Titulaire: {} | Solde: {:.2}€",
-            self.numero, self.titulaire, self.solde
-        );
+    writeln!(console.sortie, "\n{}", tr!("selection.titre"))?;
+    for (i, compte) in comptes.iter().enumerate() {
+        writeln!(
+            console.sortie,
+            "{}. {} - {}",
+            i + 1,
+            compte.numero(),
+            compte.titulaire()
+        )?;
     }
 
-    fn retrait(&mut self, montant: f32) {
-        if montant <= 0.0 {
-            println!("Le montant doit être positif");
-            return;
+    let saisie = console.lire_input(&tr!("selection.invite"))?;
+    match recherche::selectionner(&comptes, &saisie) {
+        Some(compte) => Ok(Some(compte.numero().to_string())),
+        None => {
+            writeln!(console.sortie, "{}", tr!("selection.introuvable"))?;
+            Ok(None)
         }
+    }
+}
 
-        if montant > self.solde {
-            println!("Solde insuffisant");
-            return;
+fn menu_liste_comptes<R: BufRead, W: Write>(
+    console: &mut Console<R, W>,
+    gestionnaire: &GestionnaireComptes,
+) -> io::Result<()> {
+    let mut filtre = FiltreComptes::default();
+    let mut tri = TriComptes::Numero;
+    let mut decroissant = false;
+    let mut numero_page = 1;
+
+    loop {
+        let comptes = gestionnaire.rechercher(&filtre, tri, decroissant);
+        let pages = recherche::nombre_pages(comptes.len(), COMPTES_PAR_PAGE);
+        numero_page = numero_page.min(pages);
+
+        let ordre = if decroissant {
+            tr!("liste.ordre_decroissant")
+        } else {
+            String::new()
+        };
+        writeln!(
+            console.sortie,
+            "\n=== {} ===",
+            tr!("liste.entete", tri.libelle(), ordre, numero_page, pages)
+        )?;
+        if !filtre.est_vide() {
+            writeln!(
+                console.sortie,
+                "{}",
+                tr!("liste.filtres", comptes.len(), gestionnaire.comptes().len())
+            )?;
+        }
+        if comptes.is_empty() {
+            writeln!(console.sortie, "{}", tr!("liste.vide"))?;
         }
+        for compte in recherche::page(&comptes, numero_page, COMPTES_PAR_PAGE) {
+            compte.afficher_solde(&mut console.sortie)?;
+        }
+        writeln!(console.sortie, "========================")?;
 
-        self.solde = self.solde - montant;
-        println!(
-            "Retrait de {:.2}€ effectué. Nouveau solde: {:.2}€",
-            montant, self.solde
-        );
+        // Les touches de commande dépendent de la langue (« s » pour suivante ou « n » pour next).
+        let commande = console.lire_input(&tr!("liste.commandes"))?;
+        let touche = |cle| commande == langue::texte(cle);
+        if commande.is_empty() {
+            return Ok(());
+        } else if touche("liste.touche_suivante") && numero_page < pages {
+            numero_page += 1;
+        } else if touche("liste.touche_precedente") && numero_page > 1 {
+            numero_page -= 1;
+        } else if touche("liste.touche_suivante") || touche("liste.touche_precedente") {
+            writeln!(console.sortie, "{}", tr!("liste.pas_d_autre_page"))?;
+        } else if touche("liste.touche_trier") {
+            tri = match console.lire_input(&tr!("liste.trier_par"))?.as_str() {
+                "2" => TriComptes::Titulaire,
+                "3" => TriComptes::Solde,
+                _ => TriComptes::Numero,
+            };
+            decroissant = console.confirmer(&tr!("liste.decroissant"))?;
+            numero_page = 1;
+        } else if touche("liste.touche_filtrer") {
+            let titulaire = console.lire_input(&tr!("liste.filtre_titulaire"))?;
+            filtre.titulaire = Some(titulaire).filter(|t| !t.is_empty());
+            filtre.solde_min = console.lire_montant_optionnel(&tr!("liste.filtre_solde_min"))?;
+            filtre.solde_max = console.lire_montant_optionnel(&tr!("liste.filtre_solde_max"))?;
+            filtre.etat = match console.lire_input(&tr!("liste.filtre_etat"))?.as_str() {
+                "1" => Some(EtatCompte::Courant),
+                "2" => Some(EtatCompte::PretEnCours),
+                "3" => Some(EtatCompte::PretSolde),
+                _ => None,
+            };
+            numero_page = 1;
+        } else if touche("liste.touche_effacer") {
+            filtre = FiltreComptes::default();
+            numero_page = 1;
+        } else {
+            writeln!(console.sortie, "{}", tr!("liste.commande_inconnue"))?;
+        }
     }
+}
 
-    fn depot(&mut self, montant: f32) {
-        if montant <= 0.0 {
-            println!("Le montant doit être positif");
-            return;
-        }
+fn afficher_menu(sortie: &mut impl Write) -> io::Result<()> {
+    let options = [
+        "menu.afficher_solde",
+        "menu.retrait",
+        "menu.depot",
+        "menu.liste_comptes",
+        "menu.prets",
+        "menu.soldes_date",
+        "menu.rapports",
+        "menu.alertes",
+        "menu.objectifs",
+        "menu.virement_externe",
+        "menu.fraude",
+        "menu.cheques",
+        "menu.caisse",
+        "menu.prelevements",
+        "menu.quitter",
+    ];
 
-        self.solde = self.solde + montant;
-        println!(
-            "Dépôt de {:.2}€ effectué. Nouveau solde: {:.2}€",
-            montant, self.solde
-        );
+    writeln!(sortie, "\n=== {} ===", tr!("menu.titre"))?;
+    for (i, option) in options.into_iter().enumerate() {
+        writeln!(sortie, "{}. {}", i + 1, langue::texte(option))?;
+    }
+    writeln!(sortie, "======================")
+}
+
+fn afficher_sous_menu(
+    sortie: &mut impl Write,
+    titre: &'static str,
+    options: &[&'static str],
+) -> io::Result<()> {
+    writeln!(sortie, "\n=== {} ===", langue::texte(titre))?;
+    for (i, option) in options.iter().enumerate() {
+        writeln!(sortie, "{}. {}", i + 1, langue::texte(option))?;
     }
+    Ok(())
 }
 
-struct GestionnaireComptes {
-    comptes: HashMap<String, CompteBancaire>,
+fn menu_prets<R: BufRead, W: Write>(
+    console: &mut Console<R, W>,
+    gestionnaire: &mut GestionnaireComptes,
+) -> io::Result<()> {
+    afficher_sous_menu(
+        &mut console.sortie,
+        "prets.titre",
+        &[
+            "prets.ouvrir",
+            "prets.tableau",
+            "prets.prelever",
+            "prets.remboursement_anticipe",
+            "menu.retour",
+        ],
+    )?;
+
+    match console.lire_input(&tr!("menu.choix"))?.as_str() {
+        "1" => {
+            let numero = console.lire_input(&tr!("prets.numero"))?;
+            let compte_lie = console.lire_input(&tr!("prets.compte_lie"))?;
+            let capital = console.lire_montant(&tr!("prets.capital"))?;
+            let taux = console.lire_montant(&tr!("prets.taux"))?;
+            let duree = console
+                .lire_input(&tr!("prets.duree"))?
+                .parse::<u32>()
+                .unwrap_or(0);
+            let mode = match console.lire_input(&tr!("prets.mode"))?.as_str() {
+                "2" => ModeAmortissement::AmortissementConstant,
+                _ => ModeAmortissement::AnnuiteConstante,
+            };
+
+            match gestionnaire.ouvrir_pret(numero, compte_lie, capital, taux, duree, mode) {
+                Ok(()) => writeln!(console.sortie, "{}", tr!("prets.ouvert"))?,
+                Err(e) => writeln!(console.sortie, "{}", e)?,
+            }
+        }
+        "2" => {
+            let numero = console.lire_input(&tr!("prets.numero"))?;
+            match gestionnaire.pret(&numero) {
+                Ok(pret) => pret.afficher_tableau(&mut console.sortie)?,
+                Err(e) => writeln!(console.sortie, "{}", e)?,
+            }
+        }
+        "3" => {
+            let numero = console.lire_input(&tr!("prets.numero"))?;
+            match gestionnaire.prelever_echeance(&numero) {
+                Ok(echeance) => writeln!(
+                    console.sortie,
+                    "{}",
+                    tr!(
+                        "prets.echeance_prelevee",
+                        echeance.numero,
+                        langue::montant(echeance.mensualite),
+                        langue::montant(echeance.interets),
+                        langue::montant(echeance.capital_restant)
+                    )
+                )?,
+                Err(e) => writeln!(console.sortie, "{}", e)?,
+            }
+        }
+        "4" => {
+            let numero = console.lire_input(&tr!("prets.numero"))?;
+            let montant = console.lire_montant(&tr!("prets.montant_rembourse"))?;
+            match gestionnaire.remboursement_anticipe(&numero, montant) {
+                Ok(Some(mensualite)) => writeln!(
+                    console.sortie,
+                    "{}",
+                    tr!("prets.nouvelle_mensualite", langue::montant(mensualite))
+                )?,
+                Ok(None) => writeln!(console.sortie, "{}", tr!("prets.solde"))?,
+                Err(e) => writeln!(console.sortie, "{}", e)?,
+            }
+        }
+        _ => {}
+    }
+    Ok(())
 }
 
-impl GestionnaireComptes {
-    fn new() -> Self {
-        GestionnaireComptes {
-            comptes: HashMap::new(),
+fn afficher_soldes_au<R: BufRead, W: Write>(
+    console: &mut Console<R, W>,
+    gestionnaire: &GestionnaireComptes,
+) -> io::Result<()> {
+    let date = console.lire_date(&tr!("soldes.date"))?;
+    let numero = console.lire_input(&tr!("soldes.numero"))?;
+    let date_affichee = date.format("%d/%m/%Y");
+
+    if !numero.is_empty() {
+        match gestionnaire.solde_au(&numero, date) {
+            Ok(solde) => {
+                writeln!(
+                    console.sortie,
+                    "\n{}",
+                    tr!("soldes.mouvements", date_affichee)
+                )?;
+                if let Ok(compte) = gestionnaire.compte(&numero) {
+                    compte.afficher_historique(date, &mut console.sortie)?;
+                }
+                writeln!(
+                    console.sortie,
+                    "{}",
+                    tr!(
+                        "soldes.solde_compte",
+                        numero,
+                        date_affichee,
+                        langue::montant(solde)
+                    )
+                )?;
+            }
+            Err(e) => writeln!(console.sortie, "{}", e)?,
         }
+        return Ok(());
     }
 
-    fn ajouter_compte(&mut self, compte: CompteBancaire) {
-        self.comptes.insert(compte.numero.clone(), compte);
+    writeln!(
+        console.sortie,
+        "\n=== {} ===",
+        tr!("soldes.titre", date_affichee)
+    )?;
+    let mut total = 0.0;
+    for (compte, solde) in gestionnaire.soldes_au(date) {
+        writeln!(
+            console.sortie,
+            "{}",
+            tr!(
+                "compte.solde",
+                compte.numero(),
+                compte.titulaire(),
+                langue::montant(solde)
+            )
+        )?;
+        total += solde;
     }
+    writeln!(
+        console.sortie,
+        "{}",
+        tr!("soldes.total", langue::montant(total))
+    )?;
+    writeln!(console.sortie, "========================")
+}
 
-    fn lister_comptes(&self) {
-        if self.comptes.is_empty() {
-            println!("Aucun compte enregistré.");
-            return;
-        }
+fn menu_rapports<R: BufRead, W: Write>(
+    console: &mut Console<R, W>,
+    gestionnaire: &mut GestionnaireComptes,
+) -> io::Result<()> {
+    afficher_sous_menu(
+        &mut console.sortie,
+        "rapports.titre",
+        &[
+            "rapports.modifier_categorie",
+            "rapports.rapport_compte",
+            "rapports.rapport_client",
+            "rapports.recapitulatif_fiscal",
+            "rapports.releve",
+            "menu.retour",
+        ],
+    )?;
 
-        println!("\n=== Liste des comptes ===");
-        for compte in self.comptes.values() {
-            compte.afficher_solde();
+    let lignes = match console.lire_input(&tr!("menu.choix"))?.as_str() {
+        "1" => {
+            let Some(numero) = selectionner_compte(console, gestionnaire)? else {
+                return Ok(());
+            };
+            if let Ok(compte) = gestionnaire.compte(&numero) {
+                compte.afficher_historique(NaiveDate::MAX, &mut console.sortie)?;
+            }
+            let index = console
+                .lire_input(&tr!("rapports.numero_transaction"))?
+                .parse::<usize>()
+                .unwrap_or(0);
+            let categorie = console.lire_input(&tr!("rapports.nouvelle_categorie"))?;
+            match gestionnaire.definir_categorie(&numero, index, categorie) {
+                Ok(()) => writeln!(console.sortie, "{}", tr!("rapports.categorie_modifiee"))?,
+                Err(e) => writeln!(console.sortie, "{}", e)?,
+            }
+            return Ok(());
         }
-        println!("========================");
+        "2" => {
+            let Some(numero) = selectionner_compte(console, gestionnaire)? else {
+                return Ok(());
+            };
+            match gestionnaire.compte(&numero) {
+                Ok(compte) => rapport::generer_rapport(compte.historique().iter()),
+                Err(e) => return writeln!(console.sortie, "{}", e),
+            }
+        }
+        "3" => {
+            let titulaire = console.lire_input(&tr!("rapports.nom_client"))?;
+            let transactions = gestionnaire.transactions_client(&titulaire);
+            rapport::generer_rapport(transactions.into_iter())
+        }
+        "4" => return recapitulatif_fiscal(console, gestionnaire),
+        "5" => return releve_de_compte(console, gestionnaire),
+        _ => return Ok(()),
+    };
+
+    writeln!(console.sortie)?;
+    rapport::afficher_rapport(&lignes, &mut console.sortie)?;
+    if lignes.is_empty() {
+        return Ok(());
     }
 
-    fn selectionner_compte(&mut self) -> Option<&mut CompteBancaire> {
-        if self.comptes.is_empty() {
-            println!("Aucun compte disponible.");
-            return None;
+    if console.confirmer(&tr!("rapports.exporter"))? {
+        let chemin = console.lire_input(&tr!("rapports.fichier"))?;
+        match rapport::exporter_csv(&lignes, Path::new(&chemin)) {
+            Ok(()) => writeln!(console.sortie, "{}", tr!("rapports.exporte", chemin))?,
+            Err(e) => writeln!(console.sortie, "{}", tr!("rapports.erreur_export", e))?,
         }
+    }
+    Ok(())
+}
 
-        println!("\nComptes disponibles:");
-        let mut i = 1;
-        for numero in self.comptes.keys() {
-            println!("{}. {}", i, numero);
-            i = i + 1;
+fn recapitulatif_fiscal<R: BufRead, W: Write>(
+    console: &mut Console<R, W>,
+    gestionnaire: &GestionnaireComptes,
+) -> io::Result<()> {
+    let client = console.lire_input(&tr!("rapports.identifiant_client"))?;
+    let annee_passee = Local::now().year() - 1;
+    let annee = console
+        .lire_input(&tr!("rapports.annee", annee_passee))?
+        .parse()
+        .unwrap_or(annee_passee);
+    let recapitulatif = match gestionnaire.recapitulatif_fiscal(&client, annee) {
+        Ok(recapitulatif) => recapitulatif,
+        Err(e) => return writeln!(console.sortie, "{}", e),
+    };
+
+    writeln!(console.sortie)?;
+    recapitulatif.afficher(&mut console.sortie)?;
+    if recapitulatif.lignes.is_empty() {
+        return Ok(());
+    }
+
+    if console.confirmer(&tr!("rapports.exporter"))? {
+        let chemin = console.lire_input(&tr!("rapports.fichier"))?;
+        match recapitulatif.exporter_csv(Path::new(&chemin)) {
+            Ok(()) => writeln!(console.sortie, "{}", tr!("rapports.exporte", chemin))?,
+            Err(e) => writeln!(console.sortie, "{}", tr!("rapports.erreur_export", e))?,
         }
+    }
+    Ok(())
+}
 
-        print!("Sélectionnez un compte (numéro): ");
-        io::stdout().flush().unwrap();
+fn releve_de_compte<R: BufRead, W: Write>(
+    console: &mut Console<R, W>,
+    gestionnaire: &GestionnaireComptes,
+) -> io::Result<()> {
+    let Some(numero) = selectionner_compte(console, gestionnaire)? else {
+        return Ok(());
+    };
+    let du = console.lire_date(&tr!("rapports.releve_du"))?;
+    let au = console.lire_date(&tr!("rapports.releve_au"))?;
+    let par_defaut = format!("releve-{}-{}.html", numero, au.format("%Y%m%d"));
+    let chemin = console.lire_input(&tr!("rapports.releve_fichier", par_defaut))?;
+    let chemin = if chemin.is_empty() {
+        par_defaut
+    } else {
+        chemin
+    };
 
-        let mut input = String::new();
-        io::stdin().read_line(&mut input).unwrap();
+    // Le modèle de `modeles/releve.html` peut être personnalisé sans recompiler.
+    let (releve, modele) = match (
+        gestionnaire.compte(&numero),
+        ModeleReleve::charger(Path::new(MODELE_RELEVE)),
+    ) {
+        (Ok(compte), Ok(modele)) => (releve::releve(compte, du, au), modele),
+        (Err(e), _) | (_, Err(e)) => return writeln!(console.sortie, "{}", e),
+    };
+    match releve.exporter_html(&modele, Path::new(&chemin)) {
+        Ok(()) => writeln!(
+            console.sortie,
+            "{}",
+            tr!("rapports.releve_exporte", releve.mouvements.len(), chemin)
+        ),
+        Err(e) => writeln!(console.sortie, "{}", tr!("rapports.erreur_export", e)),
+    }
+}
 
-        let numero = input.trim();
-        if self.comptes.contains_key(numero) {
-            return self.comptes.get_mut(numero);
-        } else {
-            println!("Compte non trouvé.");
-            return None;
+fn menu_alertes<R: BufRead, W: Write>(
+    console: &mut Console<R, W>,
+    gestionnaire: &mut GestionnaireComptes,
+) -> io::Result<()> {
+    afficher_sous_menu(
+        &mut console.sortie,
+        "alertes.titre",
+        &[
+            "alertes.ajouter_regle",
+            "alertes.voir_regles",
+            "alertes.supprimer_regles",
+            "alertes.declenchees",
+            "alertes.ajouter_sortie",
+            "menu.retour",
+        ],
+    )?;
+
+    match console.lire_input(&tr!("menu.choix"))?.as_str() {
+        "1" => {
+            let Some(numero) = selectionner_compte(console, gestionnaire)? else {
+                return Ok(());
+            };
+            let regle = match console.lire_input(&tr!("alertes.type"))?.as_str() {
+                "1" => RegleAlerte::SoldeBas(console.lire_montant(&tr!("alertes.seuil"))?),
+                "2" => {
+                    RegleAlerte::RetraitImportant(console.lire_montant(&tr!("alertes.plafond"))?)
+                }
+                _ => return writeln!(console.sortie, "{}", tr!("alertes.type_invalide")),
+            };
+            match gestionnaire.ajouter_regle_alerte(&numero, regle) {
+                Ok(()) => writeln!(console.sortie, "{}", tr!("alertes.regle_ajoutee", regle))?,
+                Err(e) => writeln!(console.sortie, "{}", e)?,
+            }
+        }
+        "2" => {
+            let Some(numero) = selectionner_compte(console, gestionnaire)? else {
+                return Ok(());
+            };
+            let regles = gestionnaire.regles_alerte(&numero);
+            if regles.is_empty() {
+                writeln!(console.sortie, "{}", tr!("alertes.aucune_regle"))?;
+            }
+            for regle in regles {
+                writeln!(console.sortie, "- {}", regle)?;
+            }
+        }
+        "3" => {
+            let Some(numero) = selectionner_compte(console, gestionnaire)? else {
+                return Ok(());
+            };
+            match gestionnaire.supprimer_regles_alerte(&numero) {
+                Ok(()) => writeln!(console.sortie, "{}", tr!("alertes.regles_supprimees"))?,
+                Err(e) => writeln!(console.sortie, "{}", e)?,
+            }
+        }
+        "4" => {
+            if gestionnaire.alertes().is_empty() {
+                writeln!(console.sortie, "{}", tr!("alertes.aucune_alerte"))?;
+            }
+            for alerte in gestionnaire.alertes() {
+                writeln!(console.sortie, "{}", alerte)?;
+            }
         }
+        "5" => match console.lire_input(&tr!("alertes.sortie"))?.as_str() {
+            "1" => {
+                let chemin = console.lire_input(&tr!("alertes.chemin"))?;
+                match gestionnaire
+                    .ajouter_sortie_alerte(Box::new(SortieFichier::new(PathBuf::from(chemin))))
+                {
+                    Ok(()) => {
+                        writeln!(console.sortie, "{}", tr!("alertes.sortie_fichier_ajoutee"))?
+                    }
+                    Err(e) => writeln!(console.sortie, "{}", e)?,
+                }
+            }
+            "2" => {
+                let adresse = console.lire_input(&tr!("alertes.adresse"))?;
+                match gestionnaire.ajouter_sortie_alerte(Box::new(SortieTcp::new(adresse))) {
+                    Ok(()) => writeln!(console.sortie, "{}", tr!("alertes.sortie_tcp_ajoutee"))?,
+                    Err(e) => writeln!(console.sortie, "{}", e)?,
+                }
+            }
+            _ => writeln!(console.sortie, "{}", tr!("menu.choix_invalide"))?,
+        },
+        _ => {}
     }
+    Ok(())
 }
 
-fn lire_input(prompt: &str) -> String {
-    print!("{}", prompt);
-    io::stdout().flush().unwrap();
-    let mut input = String::new();
-    io::stdin().read_line(&mut input).unwrap();
-    input.trim().to_string()
+fn menu_objectifs<R: BufRead, W: Write>(
+    console: &mut Console<R, W>,
+    gestionnaire: &mut GestionnaireComptes,
+) -> io::Result<()> {
+    afficher_sous_menu(
+        &mut console.sortie,
+        "objectifs.titre",
+        &[
+            "objectifs.ajouter",
+            "objectifs.voir",
+            "objectifs.supprimer",
+            "objectifs.executer",
+            "menu.retour",
+        ],
+    )?;
+
+    let aujourd_hui = Local::now().date_naive();
+    match console.lire_input(&tr!("menu.choix"))?.as_str() {
+        "1" => {
+            let Some(numero) = selectionner_compte(console, gestionnaire)? else {
+                return Ok(());
+            };
+            let nom = console.lire_input(&tr!("objectifs.nom"))?;
+            let montant = console.lire_montant(&tr!("objectifs.montant"))?;
+            let date_limite = console.lire_date(&tr!("objectifs.date_limite"))?;
+            let mut objectif = ObjectifEpargne::new(nom, montant, date_limite);
+            if console.confirmer(&tr!("objectifs.creer_virement"))? {
+                objectif.virement = Some(VirementPermanent {
+                    source: console.lire_input(&tr!("objectifs.source"))?,
+                    montant: console.lire_montant(&tr!("objectifs.montant_virement"))?,
+                    prochaine_date: console.lire_date(&tr!("objectifs.premiere_date"))?,
+                });
+            }
+            match gestionnaire.ajouter_objectif(&numero, objectif) {
+                Ok(()) => writeln!(console.sortie, "{}", tr!("objectifs.ajoute"))?,
+                Err(e) => writeln!(console.sortie, "{}", e)?,
+            }
+        }
+        "2" => {
+            let Some(numero) = selectionner_compte(console, gestionnaire)? else {
+                return Ok(());
+            };
+            let objectifs = gestionnaire.objectifs(&numero);
+            if objectifs.is_empty() {
+                writeln!(console.sortie, "{}", tr!("objectifs.aucun"))?;
+            }
+            let solde = gestionnaire.compte(&numero).map_or(0.0, |c| c.solde());
+            for objectif in objectifs {
+                objectif.afficher(solde, aujourd_hui, &mut console.sortie)?;
+            }
+        }
+        "3" => {
+            let Some(numero) = selectionner_compte(console, gestionnaire)? else {
+                return Ok(());
+            };
+            let nom = console.lire_input(&tr!("objectifs.nom"))?;
+            match gestionnaire.supprimer_objectif(&numero, &nom) {
+                Ok(()) => writeln!(console.sortie, "{}", tr!("objectifs.supprime"))?,
+                Err(e) => writeln!(console.sortie, "{}", e)?,
+            }
+        }
+        "4" => {
+            let executes = gestionnaire.executer_virements_permanents(aujourd_hui);
+            if executes.is_empty() {
+                writeln!(console.sortie, "{}", tr!("objectifs.aucun_virement"))?;
+            }
+            for execute in executes {
+                let date = execute.date.format("%d/%m/%Y");
+                match execute.resultat {
+                    Ok(_) => writeln!(
+                        console.sortie,
+                        "{}",
+                        tr!(
+                            "objectifs.virement_effectue",
+                            date,
+                            langue::montant(execute.montant),
+                            execute.numero,
+                            execute.objectif
+                        )
+                    )?,
+                    Err(e) => writeln!(
+                        console.sortie,
+                        "{}",
+                        tr!(
+                            "objectifs.virement_echoue",
+                            date,
+                            execute.numero,
+                            execute.objectif,
+                            e
+                        )
+                    )?,
+                }
+            }
+        }
+        _ => {}
+    }
+    Ok(())
 }
 
-fn lire_montant(prompt: &str) -> f32 {
+/// Exécute l'opération ; si une règle de fraude demande une confirmation, la
+/// demande au guichetier, et si l'opération dépasse le seuil d'approbation, fait
+/// s'identifier un superviseur, puis rejoue l'opération confirmée ou approuvée.
+fn confirmer_si_requis<R: BufRead, W: Write, T>(
+    console: &mut Console<R, W>,
+    gestionnaire: &mut GestionnaireComptes,
+    operation: impl Fn(&mut GestionnaireComptes) -> Result<T, ErreurBancaire>,
+) -> io::Result<Result<T, ErreurBancaire>> {
+    let mut confirmee = false;
+    let mut superviseur: Option<(String, String)> = None;
     loop {
-        let input = lire_input(prompt);
-        match input.parse::<f32>() {
-            Ok(montant) => return montant,
-            Err(_) => println!("Montant invalide. Essayez encore."),
+        // L'approbation et la confirmation ne valent que pour l'essai qui suit.
+        if let Some((identifiant, mot_de_passe)) = &superviseur
+            && let Err(e) = gestionnaire.avec_approbation(identifiant, mot_de_passe)
+        {
+            return Ok(Err(e));
+        }
+        if confirmee {
+            gestionnaire.avec_confirmation();
+        }
+        match operation(gestionnaire) {
+            Err(ErreurBancaire::ConfirmationRequise(motifs)) if !confirmee => {
+                writeln!(console.sortie, "{}", tr!("fraude.a_confirmer", motifs))?;
+                if !console.confirmer(&tr!("fraude.confirmer_operation"))? {
+                    return Ok(Err(ErreurBancaire::ConfirmationRequise(motifs)));
+                }
+                confirmee = true;
+            }
+            Err(ErreurBancaire::ApprobationRequise(operation)) if superviseur.is_none() => {
+                let erreur = ErreurBancaire::ApprobationRequise(operation);
+                writeln!(console.sortie, "{}", erreur)?;
+                let identifiant = console.lire_input(&tr!("habilitation.superviseur"))?;
+                if identifiant.is_empty() {
+                    return Ok(Err(erreur));
+                }
+                let mot_de_passe =
+                    console.lire_secret(&tr!("habilitation.mot_de_passe_superviseur"))?;
+                superviseur = Some((identifiant, mot_de_passe));
+            }
+            resultat => return Ok(resultat),
         }
     }
 }
 
-fn afficher_menu() {
-    let options = [
-        "Afficher solde",
-        "Retrait",
-        "Dépôt",
-        "Liste comptes",
-        "Quitter",
-    ];
+fn virement_externe<R: BufRead, W: Write>(
+    console: &mut Console<R, W>,
+    gestionnaire: &mut GestionnaireComptes,
+) -> io::Result<()> {
+    let Some(numero) = selectionner_compte(console, gestionnaire)? else {
+        return Ok(());
+    };
+    let beneficiaire = console.lire_input(&tr!("externe.beneficiaire"))?;
+    let devise = console.lire_input(&tr!("externe.devise", DEVISE_COMPTES))?;
+    let devise = if devise.is_empty() {
+        DEVISE_COMPTES.to_string()
+    } else {
+        devise.to_uppercase()
+    };
+    let montant = console.lire_montant(&tr!("externe.montant"))?;
+    let libelle = console.lire_libelle(&tr!("externe.libelle"))?;
 
-    println!("\n=== Menu Principal ===");
-    for (i, option) in options.iter().enumerate() {
-        println!("{}. {}", i + 1, option);
+    let resultat = confirmer_si_requis(console, gestionnaire, |g| {
+        g.virement_externe(&numero, &beneficiaire, montant, &devise, &libelle)
+    })?;
+    match resultat {
+        Ok(solde) => {
+            let debit = gestionnaire.en_euros(montant, &devise).unwrap_or(montant);
+            writeln!(
+                console.sortie,
+                "{}",
+                tr!(
+                    "externe.effectue",
+                    langue::nombre(montant),
+                    devise,
+                    beneficiaire,
+                    langue::montant(debit),
+                    langue::montant(solde)
+                )
+            )
+        }
+        Err(e) => writeln!(console.sortie, "{}", e),
+    }
+}
+
+fn menu_fraude<R: BufRead, W: Write>(
+    console: &mut Console<R, W>,
+    gestionnaire: &mut GestionnaireComptes,
+) -> io::Result<()> {
+    afficher_sous_menu(
+        &mut console.sortie,
+        "fraude.titre",
+        &[
+            "fraude.a_examiner",
+            "fraude.tous",
+            "fraude.examiner",
+            "fraude.comptes_inactifs",
+            "menu.retour",
+        ],
+    )?;
+
+    match console.lire_input(&tr!("menu.choix"))?.as_str() {
+        choix @ ("1" | "2") => {
+            let en_attente = choix == "1";
+            let mut aucun = true;
+            for (i, signalement) in gestionnaire.signalements().iter().enumerate() {
+                if en_attente && signalement.examine {
+                    continue;
+                }
+                let examine = if signalement.examine {
+                    tr!("fraude.examine")
+                } else {
+                    String::new()
+                };
+                writeln!(console.sortie, "{}. {}{}", i + 1, signalement, examine)?;
+                aucun = false;
+            }
+            if aucun {
+                writeln!(console.sortie, "{}", tr!("fraude.aucun"))?;
+            }
+        }
+        "3" => {
+            let index = console
+                .lire_input(&tr!("fraude.numero"))?
+                .parse::<usize>()
+                .unwrap_or(0);
+            match gestionnaire.examiner_signalement(index) {
+                Ok(()) => writeln!(console.sortie, "{}", tr!("fraude.marque_examine"))?,
+                Err(e) => writeln!(console.sortie, "{}", e)?,
+            }
+        }
+        "4" => {
+            let aujourd_hui = Local::now().date_naive();
+            let classements = gestionnaire.classer_comptes_inactifs(aujourd_hui);
+            dormance::afficher_rapport(&classements, aujourd_hui, &mut console.sortie)?;
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+fn menu_cheques<R: BufRead, W: Write>(
+    console: &mut Console<R, W>,
+    gestionnaire: &mut GestionnaireComptes,
+) -> io::Result<()> {
+    afficher_sous_menu(
+        &mut console.sortie,
+        "cheques.titre",
+        &[
+            "cheques.remettre",
+            "cheques.voir",
+            "cheques.encaisser",
+            "cheques.rejeter",
+            "menu.retour",
+        ],
+    )?;
+
+    match console.lire_input(&tr!("menu.choix"))?.as_str() {
+        "1" => {
+            let Some(numero) = selectionner_compte(console, gestionnaire)? else {
+                return Ok(());
+            };
+            let numero_cheque = console.lire_input(&tr!("cheques.numero"))?;
+            let emetteur = console.lire_input(&tr!("cheques.emetteur"))?;
+            let montant = console.lire_montant(&tr!("cheques.montant"))?;
+            let remise = confirmer_si_requis(console, gestionnaire, |g| {
+                g.remise_cheque(&numero, &numero_cheque, &emetteur, montant)
+            })?;
+            match remise {
+                Ok(disponible_le) => writeln!(
+                    console.sortie,
+                    "{}",
+                    tr!(
+                        "cheques.remis",
+                        langue::montant(montant),
+                        disponible_le.format("%d/%m/%Y")
+                    )
+                )?,
+                Err(e) => writeln!(console.sortie, "{}", e)?,
+            }
+        }
+        "2" => {
+            let Some(numero) = selectionner_compte(console, gestionnaire)? else {
+                return Ok(());
+            };
+            let cheques = gestionnaire.cheques(&numero);
+            if cheques.is_empty() {
+                writeln!(console.sortie, "{}", tr!("cheques.aucun"))?;
+            }
+            for cheque in cheques {
+                writeln!(console.sortie, "- {}", cheque)?;
+            }
+            writeln!(
+                console.sortie,
+                "{}",
+                tr!(
+                    "cheques.solde_en_attente",
+                    langue::montant(gestionnaire.montant_en_attente(&numero))
+                )
+            )?;
+        }
+        "3" => {
+            let encaisses = gestionnaire.encaisser_cheques(Local::now().date_naive());
+            if encaisses.is_empty() {
+                writeln!(console.sortie, "{}", tr!("cheques.aucun_disponible"))?;
+            }
+            for cheque in encaisses {
+                writeln!(
+                    console.sortie,
+                    "{}",
+                    tr!(
+                        "cheques.encaisse",
+                        cheque.numero,
+                        langue::montant(cheque.montant),
+                        cheque.compte
+                    )
+                )?;
+            }
+        }
+        "4" => {
+            let Some(numero) = selectionner_compte(console, gestionnaire)? else {
+                return Ok(());
+            };
+            let numero_cheque = console.lire_input(&tr!("cheques.numero"))?;
+            match gestionnaire.rejeter_cheque(&numero, &numero_cheque) {
+                Ok(solde) => writeln!(
+                    console.sortie,
+                    "{}",
+                    tr!("cheques.rejete", numero_cheque, langue::montant(solde))
+                )?,
+                Err(e) => writeln!(console.sortie, "{}", e)?,
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+fn menu_prelevements<R: BufRead, W: Write>(
+    console: &mut Console<R, W>,
+    gestionnaire: &mut GestionnaireComptes,
+) -> io::Result<()> {
+    afficher_sous_menu(
+        &mut console.sortie,
+        "prelevements.titre",
+        &[
+            "prelevements.signer",
+            "prelevements.voir",
+            "prelevements.prelever",
+            "prelevements.rembourser",
+            "prelevements.revoquer",
+            "menu.retour",
+        ],
+    )?;
+
+    let choix = console.lire_input(&tr!("menu.choix"))?;
+    if !["1", "2", "3", "4", "5"].contains(&choix.as_str()) {
+        return Ok(());
     }
-    println!("======================");
+    let Some(numero) = selectionner_compte(console, gestionnaire)? else {
+        return Ok(());
+    };
+    match choix.as_str() {
+        "1" => {
+            let creancier = console.lire_input(&tr!("prelevements.creancier"))?;
+            let reference = console.lire_input(&tr!("prelevements.reference"))?;
+            let signe_le = console.lire_date(&tr!("prelevements.date_signature"))?;
+            let plafond = console.lire_montant(&tr!("prelevements.plafond"))?;
+            match gestionnaire.ajouter_mandat(&numero, &creancier, &reference, signe_le, plafond) {
+                Ok(()) => writeln!(console.sortie, "{}", tr!("prelevements.mandat_ajoute"))?,
+                Err(e) => writeln!(console.sortie, "{}", e)?,
+            }
+        }
+        "2" => {
+            let mandats = gestionnaire.mandats(&numero);
+            if mandats.is_empty() {
+                writeln!(console.sortie, "{}", tr!("prelevements.aucun_mandat"))?;
+            }
+            for mandat in mandats {
+                writeln!(console.sortie, "- {}", mandat)?;
+            }
+            afficher_prelevements(console, gestionnaire, &numero)?;
+        }
+        "3" => {
+            let creancier = console.lire_input(&tr!("prelevements.creancier"))?;
+            let reference = console.lire_input(&tr!("prelevements.reference"))?;
+            let montant = console.lire_montant(&tr!("prelevements.montant"))?;
+            let prelevement = confirmer_si_requis(console, gestionnaire, |g| {
+                g.prelever(&numero, &creancier, &reference, montant)
+            })?;
+            match prelevement {
+                Ok(solde) => writeln!(
+                    console.sortie,
+                    "{}",
+                    tr!(
+                        "prelevements.preleve",
+                        langue::montant(montant),
+                        langue::montant(solde)
+                    )
+                )?,
+                Err(e) => writeln!(console.sortie, "{}", e)?,
+            }
+        }
+        "4" => {
+            if afficher_prelevements(console, gestionnaire, &numero)? {
+                let index = console
+                    .lire_input(&tr!("prelevements.numero"))?
+                    .parse::<usize>()
+                    .unwrap_or(0);
+                match gestionnaire.rembourser_prelevement(&numero, index) {
+                    Ok(solde) => writeln!(
+                        console.sortie,
+                        "{}",
+                        tr!("prelevements.rembourse_solde", langue::montant(solde))
+                    )?,
+                    Err(e) => writeln!(console.sortie, "{}", e)?,
+                }
+            }
+        }
+        _ => {
+            let creancier = console.lire_input(&tr!("prelevements.creancier"))?;
+            let reference = console.lire_input(&tr!("prelevements.reference"))?;
+            match gestionnaire.revoquer_mandat(&numero, &creancier, &reference) {
+                Ok(()) => writeln!(console.sortie, "{}", tr!("prelevements.mandat_revoque"))?,
+                Err(e) => writeln!(console.sortie, "{}", e)?,
+            }
+        }
+    }
+    Ok(())
 }
 
-fn creer_comptes_demo() -> GestionnaireComptes {
-    let mut gestionnaire = GestionnaireComptes::new();
+/// Liste numérotée des prélèvements du compte ; `false` s'il n'y en a aucun.
+fn afficher_prelevements<R: BufRead, W: Write>(
+    console: &mut Console<R, W>,
+    gestionnaire: &GestionnaireComptes,
+    numero: &str,
+) -> io::Result<bool> {
+    let prelevements = gestionnaire.prelevements(numero);
+    if prelevements.is_empty() {
+        writeln!(console.sortie, "{}", tr!("prelevements.aucun_prelevement"))?;
+    }
+    for (i, prelevement) in prelevements.iter().enumerate() {
+        writeln!(console.sortie, "{}. {}", i + 1, prelevement)?;
+    }
+    Ok(!prelevements.is_empty())
+}
 
-    let compte1 = CompteBancaire::new("12345".to_string(), "Alice Dupont".to_string(), 1500.0);
-    let compte2 = CompteBancaire::new("67890".to_string(), "Bob Martin".to_string(), 2300.0);
-    let compte3 = CompteBancaire::new("11111".to_string(), "Claire Bernard".to_string(), 800.0);
+fn menu_caisse<R: BufRead, W: Write>(
+    console: &mut Console<R, W>,
+    gestionnaire: &mut GestionnaireComptes,
+) -> io::Result<()> {
+    afficher_sous_menu(
+        &mut console.sortie,
+        "caisse.titre",
+        &[
+            "caisse.ouvrir",
+            "caisse.etat",
+            "caisse.cloturer",
+            "menu.retour",
+        ],
+    )?;
 
-    gestionnaire.ajouter_compte(compte1);
-    gestionnaire.ajouter_compte(compte2);
-    gestionnaire.ajouter_compte(compte3);
+    match console.lire_input(&tr!("menu.choix"))?.as_str() {
+        "1" => {
+            let fond = console.lire_montant(&tr!("caisse.saisir_fond"))?;
+            match gestionnaire.ouvrir_caisse(fond) {
+                Ok(()) => writeln!(console.sortie, "{}", tr!("caisse.ouverte"))?,
+                Err(e) => writeln!(console.sortie, "{}", e)?,
+            }
+        }
+        "2" => match gestionnaire.caisse() {
+            Some(caisse) => caisse.afficher(&mut console.sortie)?,
+            None => writeln!(console.sortie, "{}", tr!("caisse.aucune"))?,
+        },
+        "3" => {
+            if gestionnaire.caisse().is_none() {
+                writeln!(console.sortie, "{}", ErreurBancaire::CaisseFermee)?;
+                return Ok(());
+            }
+            let comptees = console.lire_montant(&tr!("caisse.saisir_comptees"))?;
+            match gestionnaire.cloturer_caisse(comptees) {
+                Ok(rapport) => rapport.afficher(&mut console.sortie)?,
+                Err(e) => writeln!(console.sortie, "{}", e)?,
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
 
-    gestionnaire
+/// Règles anti-fraude du guichet : signalement des débits juste sous le plafond
+/// de 1000€, confirmation des rafales de débits et des premiers virements en
+/// devise, blocage au-delà de 10 débits en une heure. Les comptes dormants sont
+/// confirmés par le gestionnaire lui-même, selon ses conditions de dormance.
+fn ajouter_regles_fraude(gestionnaire: &mut GestionnaireComptes) -> Result<(), ErreurBancaire> {
+    gestionnaire.ajouter_regle_fraude(
+        Box::new(JusteSousLePlafond {
+            plafond: 1000.0,
+            marge: 0.05,
+        }),
+        Decision::Signaler,
+    )?;
+    gestionnaire.ajouter_regle_fraude(
+        Box::new(RetraitsRapproches {
+            nombre: 5,
+            fenetre: TimeDelta::hours(1),
+        }),
+        Decision::Confirmer,
+    )?;
+    gestionnaire.ajouter_regle_fraude(
+        Box::new(RetraitsRapproches {
+            nombre: 10,
+            fenetre: TimeDelta::hours(1),
+        }),
+        Decision::Bloquer,
+    )?;
+    gestionnaire.ajouter_regle_fraude(
+        Box::new(DeviseEtrangere {
+            fenetre: TimeDelta::days(365),
+        }),
+        Decision::Confirmer,
+    )
 }
 
-fn main() {
-    println!("=== Gestionnaire de Comptes Bancaires ===\n");
+/// Cours des devises acceptées pour les virements externes.
+fn definir_cours_devises(gestionnaire: &mut GestionnaireComptes) -> Result<(), ErreurBancaire> {
+    COURS_DEVISES
+        .iter()
+        .try_for_each(|(devise, cours)| gestionnaire.definir_cours_devise(devise, *cours))
+}
 
-    let mut gestionnaire = creer_comptes_demo();
+/// Session interactive complète : création optionnelle d'un compte puis menu principal.
+/// Une fin d'entrée termine la session comme le choix « Quitter ».
+fn executer_session<R: BufRead, W: Write>(
+    console: &mut Console<R, W>,
+    gestionnaire: &mut GestionnaireComptes,
+) -> io::Result<()> {
+    match boucle_menu(console, gestionnaire) {
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => {
+            writeln!(console.sortie, "\n{}", tr!("menu.au_revoir"))
+        }
+        resultat => resultat,
+    }
+}
 
-    println!("Voulez-vous créer un nouveau compte ? (o/n)");
-    let reponse = lire_input("");
-    if reponse.to_lowercase() == "o" || reponse.to_lowercase() == "oui" {
-        let numero = lire_input("Numéro de compte: ");
-        let titulaire = lire_input("Nom du titulaire: ");
-        let solde_initial = lire_montant("Solde initial: ");
+fn boucle_menu<R: BufRead, W: Write>(
+    console: &mut Console<R, W>,
+    gestionnaire: &mut GestionnaireComptes,
+) -> io::Result<()> {
+    writeln!(console.sortie, "{}", tr!("session.creer_compte"))?;
+    if console.confirmer("")? {
+        let numero = console.lire_input(&tr!("session.numero"))?;
+        let titulaire = console.lire_input(&tr!("session.titulaire"))?;
+        let solde_initial = console.lire_montant(&tr!("session.solde_initial"))?;
 
         let nouveau_compte = CompteBancaire::new(numero, titulaire, solde_initial);
-        gestionnaire.ajouter_compte(nouveau_compte);
-        println!("Compte créé avec succès!");
+        match gestionnaire.ajouter_compte(nouveau_compte) {
+            Ok(()) => writeln!(console.sortie, "{}", tr!("session.compte_cree"))?,
+            Err(e) => writeln!(console.sortie, "{}", e)?,
+        }
     }
 
     loop {
-        afficher_menu();
+        afficher_menu(&mut console.sortie)?;
 
-        let choix = lire_input("Votre choix: ");
+        let choix = console.lire_input(&tr!("menu.choix"))?;
 
         match choix.as_str() {
             "1" => {
-                if let Some(compte) = gestionnaire.selectionner_compte() {
-                    println!("\n=== Solde du compte ===");
-                    compte.afficher_solde();
+                if let Some(numero) = selectionner_compte(console, gestionnaire)?
+                    && let Ok(compte) = gestionnaire.compte(&numero)
+                {
+                    writeln!(console.sortie, "\n=== {} ===", tr!("session.solde_compte"))?;
+                    compte.afficher_solde(&mut console.sortie)?;
+                    let en_attente = gestionnaire.montant_en_attente(&numero);
+                    if en_attente > 0.0 {
+                        writeln!(
+                            console.sortie,
+                            "{}",
+                            tr!("cheques.solde_en_attente", langue::montant(en_attente))
+                        )?;
+                    }
                 }
             }
             "2" => {
-                if let Some(compte) = gestionnaire.selectionner_compte() {
-                    let montant = lire_montant("Montant à retirer: ");
-                    compte.retrait(montant);
+                if let Some(numero) = selectionner_compte(console, gestionnaire)? {
+                    let montant = console.lire_montant(&tr!("session.montant_retrait"))?;
+                    let libelle = console.lire_libelle(&tr!("session.libelle_retrait"))?;
+                    let retrait = confirmer_si_requis(console, gestionnaire, |g| {
+                        g.retrait(&numero, montant, &libelle)
+                    })?;
+                    match retrait {
+                        Ok(solde) => writeln!(
+                            console.sortie,
+                            "{}",
+                            tr!(
+                                "session.retrait_effectue",
+                                langue::montant(montant),
+                                langue::montant(solde)
+                            )
+                        )?,
+                        Err(e) => writeln!(console.sortie, "{}", e)?,
+                    }
                 }
             }
             "3" => {
-                if let Some(compte) = gestionnaire.selectionner_compte() {
-                    let montant = lire_montant("Montant à déposer: ");
-                    compte.depot(montant);
+                if let Some(numero) = selectionner_compte(console, gestionnaire)? {
+                    let montant = console.lire_montant(&tr!("session.montant_depot"))?;
+                    let libelle = console.lire_libelle(&tr!("session.libelle_depot"))?;
+                    let depot = confirmer_si_requis(console, gestionnaire, |g| {
+                        g.depot(&numero, montant, &libelle)
+                    })?;
+                    match depot {
+                        Ok(solde) => writeln!(
+                            console.sortie,
+                            "{}",
+                            tr!(
+                                "session.depot_effectue",
+                                langue::montant(montant),
+                                langue::montant(solde)
+                            )
+                        )?,
+                        Err(e) => writeln!(console.sortie, "{}", e)?,
+                    }
                 }
             }
             "4" => {
-                gestionnaire.lister_comptes();
+                menu_liste_comptes(console, gestionnaire)?;
             }
             "5" => {
-                println!("Au revoir!");
-                break;
+                menu_prets(console, gestionnaire)?;
+            }
+            "6" => {
+                afficher_soldes_au(console, gestionnaire)?;
+            }
+            "7" => {
+                menu_rapports(console, gestionnaire)?;
+            }
+            "8" => {
+                menu_alertes(console, gestionnaire)?;
+            }
+            "9" => {
+                menu_objectifs(console, gestionnaire)?;
+            }
+            "10" => {
+                virement_externe(console, gestionnaire)?;
+            }
+            "11" => {
+                menu_fraude(console, gestionnaire)?;
+            }
+            "12" => {
+                menu_cheques(console, gestionnaire)?;
+            }
+            "13" => {
+                menu_caisse(console, gestionnaire)?;
+            }
+            "14" => {
+                menu_prelevements(console, gestionnaire)?;
+            }
+            "15" => {
+                return writeln!(console.sortie, "{}", tr!("menu.au_revoir"));
             }
             _ => {
-                println!("Choix invalide. Essayez encore.");
+                writeln!(console.sortie, "{}", tr!("menu.choix_invalide_reessayer"))?;
+            }
+        }
+
+        console.lire_input(&tr!("menu.continuer"))?;
+    }
+}
+
+const JOURNAL_AUDIT: &str = "audit.log";
+const REGISTRE_IDEMPOTENCE: &str = "idempotence.log";
+const JOURNAL_OPERATIONS: &str = "operations.log";
+const HISTORIQUE_REPL: &str = "historique_repl.txt";
+const REGLES_CATEGORIES: &str = "categories.txt";
+const MODELE_RELEVE: &str = "modeles/releve.html";
+const DOSSIER_SAUVEGARDES: &str = "sauvegardes";
+const FIXTURE_DEMO: &str = "fixtures/demo.txt";
+const VARIABLE_PHRASE: &str = "TP1_PHRASE";
+const VARIABLE_NOUVELLE_PHRASE: &str = "TP1_NOUVELLE_PHRASE";
+const UTILISATEURS: &str = "utilisateurs.txt";
+const VARIABLE_MOT_DE_PASSE: &str = "TP1_MOT_DE_PASSE";
+/// Montant au-delà duquel un débit demande l'approbation d'un superviseur.
+const SEUIL_APPROBATION: f32 = 1000.0;
+/// Cours en euros d'une unité de chaque devise étrangère.
+const COURS_DEVISES: [(&str, f32); 3] = [("USD", 0.92), ("GBP", 1.17), ("CHF", 1.05)];
+
+/// Valeur qui suit l'option `nom` sur la ligne de commande.
+fn valeur_option(nom: &str) -> Option<String> {
+    let mut args = std::env::args().skip_while(|arg| arg != nom);
+    args.next()?;
+    args.next()
+}
+
+/// Comme [`valeur_option`], pour une option dont la valeur peut être omise.
+fn option_facultative(nom: &str) -> Option<String> {
+    valeur_option(nom).filter(|valeur| !valeur.starts_with("--"))
+}
+
+/// Écrit un jeu de données aléatoire ; `--graine`, `--comptes` et `--annees`
+/// en fixent la graine et la taille.
+fn generer_fixture(chemin: &Path, coffre: &Coffre) -> ExitCode {
+    let option = |nom, defaut| {
+        valeur_option(nom)
+            .and_then(|valeur| valeur.parse().ok())
+            .unwrap_or(defaut)
+    };
+    let comptes = option("--comptes", 1000);
+    let jeu = fixture::generer(
+        option("--graine", 1),
+        comptes as usize,
+        option("--annees", 3) as u32,
+        Local::now().date_naive(),
+    );
+    match coffre.ecrire(chemin, &jeu) {
+        Ok(()) => {
+            println!("{}", tr!("fixture.generee", comptes, chemin.display()));
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!(
+                "{}",
+                tr!("fixture.ecriture_impossible", chemin.display(), e)
+            );
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn verifier_audit(chemin: &Path, coffre: &Coffre) -> ExitCode {
+    match audit::verifier(chemin, coffre) {
+        Ok((entrees, anomalies)) if anomalies.is_empty() => {
+            println!("{}", tr!("audit.intact", chemin.display(), entrees));
+            ExitCode::SUCCESS
+        }
+        Ok((entrees, anomalies)) => {
+            println!(
+                "{}",
+                tr!("audit.altere", chemin.display(), entrees, anomalies.len())
+            );
+            for anomalie in anomalies {
+                println!("- {}", anomalie);
+            }
+            ExitCode::FAILURE
+        }
+        Err(e) => {
+            eprintln!("{}", tr!("audit.lecture_impossible", chemin.display(), e));
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn sauvegarder(etat: &[&Path], dossier: &Path) -> ExitCode {
+    match sauvegarde::sauvegarder(etat, dossier, Local::now().naive_local()) {
+        Ok(archive) => {
+            println!("{}", tr!("sauvegarde.archivee", archive.display()));
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("{}", tr!("sauvegarde.impossible", e));
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Remet l'état de l'archive `archive`, par défaut celle que
+/// [`sauvegarde::archive_pour`] choisit pour `jusqu_au`. L'état courant est
+/// d'abord archivé, et le journal d'audit, qui n'est jamais réécrit, est
+/// conservé. Avec `jusqu_au`, le journal des opérations est arrêté à cet
+/// instant, en partant du journal courant s'il prolonge celui de l'archive, et
+/// les clés d'idempotence enregistrées ensuite sont oubliées ; le jeu de
+/// données `fixture` complété du journal est rechargé pour contrôle.
+fn restaurer(
+    etat: &[&Path],
+    fixture: &Path,
+    archive: Option<String>,
+    jusqu_au: Option<String>,
+    coffre: &Coffre,
+) -> Result<(), String> {
+    let jusqu_au = jusqu_au
+        .map(|texte| {
+            NaiveDateTime::parse_from_str(&texte, "%d/%m/%Y %H:%M")
+                .map_err(|_| tr!("sauvegarde.date_invalide", texte))
+        })
+        .transpose()?;
+    let dossier = Path::new(DOSSIER_SAUVEGARDES);
+    let chemin = match archive {
+        Some(chemin) => PathBuf::from(chemin),
+        None => sauvegarde::archive_pour(dossier, jusqu_au)
+            .map_err(|e| tr!("sauvegarde.restauration_impossible", e))?
+            .ok_or_else(|| tr!("sauvegarde.aucune_archive", dossier.display()))?,
+    };
+    let archive = Archive::lire(&chemin).map_err(|e| e.to_string())?;
+
+    let lire = |chemin: &str| match coffre.lire(Path::new(chemin)) {
+        Ok(contenu) => Ok(Some(contenu)),
+        Err(e) if e.introuvable() => Ok(None),
+        Err(e) => Err(e.to_string()),
+    };
+    let journal_courant = lire(JOURNAL_OPERATIONS)?;
+    let registre_courant = lire(REGISTRE_IDEMPOTENCE)?;
+
+    let filet = sauvegarde::sauvegarder(etat, dossier, Local::now().naive_local())
+        .map_err(|e| tr!("sauvegarde.impossible", e))?;
+    println!("{}", tr!("sauvegarde.etat_courant", filet.display()));
+    let tete_audit = audit::chemin_tete(Path::new(JOURNAL_AUDIT));
+    archive
+        .restaurer(etat, &[Path::new(JOURNAL_AUDIT), tete_audit.as_path()])
+        .map_err(|e| tr!("sauvegarde.restauration_impossible", e))?;
+    println!(
+        "{}",
+        tr!(
+            "sauvegarde.restauree",
+            archive.dossier.display(),
+            archive.date.format("%d/%m/%Y %H:%M:%S")
+        )
+    );
+    let Some(jusqu_au) = jusqu_au else {
+        return Ok(());
+    };
+
+    let journal_archive = lire(JOURNAL_OPERATIONS)?.unwrap_or_default();
+    let journal = match journal_courant {
+        Some(courant) if courant.starts_with(&journal_archive) => courant,
+        _ => journal_archive,
+    };
+    let (journal, annulees) =
+        sauvegarde::tronquer(&journal, jusqu_au).map_err(|e| e.to_string())?;
+    coffre
+        .ecrire(Path::new(JOURNAL_OPERATIONS), &journal)
+        .map_err(|e| tr!("sauvegarde.restauration_impossible", e))?;
+
+    if let Some(registre) = registre_courant {
+        coffre
+            .ecrire(Path::new(REGISTRE_IDEMPOTENCE), &registre)
+            .map_err(|e| tr!("sauvegarde.restauration_impossible", e))?;
+    }
+    let limite = Local
+        .from_local_datetime(&jusqu_au)
+        .earliest()
+        .ok_or_else(|| {
+            tr!(
+                "sauvegarde.date_invalide",
+                jusqu_au.format("%d/%m/%Y %H:%M")
+            )
+        })?;
+    let oubliees = RegistreIdempotence::ouvrir(
+        PathBuf::from(REGISTRE_IDEMPOTENCE),
+        RETENTION_PAR_DEFAUT,
+        coffre.clone(),
+    )
+    .and_then(|mut registre| registre.oublier_apres(limite))
+    .map_err(|e| tr!("sauvegarde.restauration_impossible", e))?;
+
+    let (comptes, rejouees) = GestionnaireComptes::new()
+        .charger_fixture_journalisee(fixture, Path::new(JOURNAL_OPERATIONS), coffre)
+        .map_err(|e| e.to_string())?;
+    println!(
+        "{}",
+        tr!(
+            "sauvegarde.retour_a",
+            jusqu_au.format("%d/%m/%Y %H:%M"),
+            comptes,
+            rejouees,
+            annulees,
+            oubliees
+        )
+    );
+    Ok(())
+}
+
+/// Phrase de passe prise dans la variable d'environnement, sinon saisie sans écho.
+fn phrase(variable: &str, invite: &str) -> io::Result<String> {
+    match std::env::var(variable) {
+        Ok(phrase) => Ok(phrase),
+        Err(_) => rpassword::prompt_password(invite),
+    }
+}
+
+fn coffre_pour(phrase: &str) -> Coffre {
+    if phrase.is_empty() {
+        Coffre::en_clair()
+    } else {
+        Coffre::avec_phrase(phrase)
+    }
+}
+
+/// Coffre des fichiers de la session. La phrase de passe est demandée avec
+/// `--chiffrer` ou si l'un des fichiers est déjà chiffré, puis contrôlée sur
+/// chacun : une phrase incorrecte arrête le programme avant toute opération.
+fn ouvrir_coffre(fichiers: &[&Path]) -> Result<Coffre, String> {
+    let chiffre = std::env::var_os(VARIABLE_PHRASE).is_some()
+        || std::env::args().any(|arg| arg == "--chiffrer")
+        || fichiers
+            .iter()
+            .any(|fichier| chiffrement::est_chiffre(fichier).unwrap_or(false));
+    let coffre = if chiffre {
+        phrase(VARIABLE_PHRASE, &tr!("chiffrement.saisir_phrase"))
+            .map(|phrase| coffre_pour(&phrase))
+            .map_err(|e| tr!("chiffrement.saisie_impossible", e))?
+    } else {
+        Coffre::en_clair()
+    };
+    for fichier in fichiers {
+        coffre.controler(fichier).map_err(|e| e.to_string())?;
+    }
+    Ok(coffre)
+}
+
+/// Rechiffre les fichiers avec une nouvelle phrase de passe, prise dans
+/// `TP1_NOUVELLE_PHRASE` ou saisie deux fois ; une phrase vide les remet en clair.
+/// Tous les fichiers sont contrôlés avec l'ancienne phrase avant d'en modifier un.
+fn changer_phrase(fichiers: &[&Path]) -> ExitCode {
+    let ancienne = match ouvrir_coffre(fichiers) {
+        Ok(coffre) => coffre,
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+    let nouvelle = match std::env::var(VARIABLE_NOUVELLE_PHRASE) {
+        Ok(phrase) => Ok(phrase),
+        Err(_) => {
+            rpassword::prompt_password(tr!("chiffrement.nouvelle_phrase")).and_then(|phrase| {
+                let confirmation = rpassword::prompt_password(tr!("chiffrement.confirmer_phrase"))?;
+                if confirmation == phrase {
+                    Ok(phrase)
+                } else {
+                    Err(io::Error::other(tr!("chiffrement.phrases_differentes")))
+                }
+            })
+        }
+    };
+    let nouvelle = match nouvelle {
+        Ok(phrase) => coffre_pour(&phrase),
+        Err(e) => {
+            eprintln!("{}", tr!("chiffrement.saisie_impossible", e));
+            return ExitCode::FAILURE;
+        }
+    };
+
+    for fichier in fichiers {
+        match ancienne.rechiffrer(fichier, &nouvelle) {
+            Ok(false) => {}
+            Ok(true) if nouvelle.chiffre() => {
+                println!("{}", tr!("chiffrement.rechiffre", fichier.display()))
+            }
+            Ok(true) => println!("{}", tr!("chiffrement.dechiffre", fichier.display())),
+            Err(e) => {
+                eprintln!("{}", tr!("chiffrement.rotation_interrompue", e));
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+    ExitCode::SUCCESS
+}
+
+/// Utilisateurs du fichier, aucun s'il n'existe pas encore.
+fn lire_utilisateurs(coffre: &Coffre) -> Result<Annuaire, String> {
+    let erreur =
+        |e: &dyn std::fmt::Display| tr!("habilitation.lecture_impossible", UTILISATEURS, e);
+    match coffre.lire(Path::new(UTILISATEURS)) {
+        Ok(contenu) => Annuaire::lire(&contenu).map_err(|e| erreur(&e)),
+        Err(e) if e.introuvable() => Ok(Annuaire::new()),
+        Err(e) => Err(erreur(&e)),
+    }
+}
+
+/// Ajoute un utilisateur au fichier ; son mot de passe est pris dans
+/// `TP1_MOT_DE_PASSE` ou saisi deux fois.
+fn ajouter_utilisateur(identifiant: &str, coffre: &Coffre) -> ExitCode {
+    let code = valeur_option("--role").unwrap_or_default();
+    let Some(role) = Role::depuis_code(&code) else {
+        eprintln!("{}", tr!("habilitation.role_inconnu", code));
+        return ExitCode::FAILURE;
+    };
+    let mut annuaire = match lire_utilisateurs(coffre) {
+        Ok(annuaire) => annuaire,
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+    let mot_de_passe = match std::env::var(VARIABLE_MOT_DE_PASSE) {
+        Ok(mot_de_passe) => Ok(mot_de_passe),
+        Err(_) => {
+            rpassword::prompt_password(tr!("habilitation.mot_de_passe")).and_then(|mot_de_passe| {
+                let confirmation =
+                    rpassword::prompt_password(tr!("habilitation.confirmer_mot_de_passe"))?;
+                if confirmation == mot_de_passe {
+                    Ok(mot_de_passe)
+                } else {
+                    Err(io::Error::other(tr!(
+                        "habilitation.mots_de_passe_differents"
+                    )))
+                }
+            })
+        }
+    };
+    let mot_de_passe = match mot_de_passe {
+        Ok(mot_de_passe) if mot_de_passe.is_empty() => {
+            eprintln!("{}", tr!("habilitation.mot_de_passe_vide"));
+            return ExitCode::FAILURE;
+        }
+        Ok(mot_de_passe) => mot_de_passe,
+        Err(e) => {
+            eprintln!("{}", tr!("habilitation.saisie_impossible", e));
+            return ExitCode::FAILURE;
+        }
+    };
+
+    if let Err(e) = annuaire.ajouter(Utilisateur::new(identifiant, role, &mot_de_passe)) {
+        eprintln!("{}", e);
+        return ExitCode::FAILURE;
+    }
+    match coffre.ecrire(Path::new(UTILISATEURS), &annuaire.contenu()) {
+        Ok(()) => {
+            println!(
+                "{}",
+                tr!(
+                    "habilitation.utilisateur_ajoute",
+                    identifiant,
+                    role.libelle()
+                )
+            );
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!(
+                "{}",
+                tr!("habilitation.ecriture_impossible", UTILISATEURS, e)
+            );
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Connecte un utilisateur : l'identifiant vient de `identifiant` ou est saisi, le
+/// mot de passe de `mot_de_passe` ou est saisi. Trois essais sont permis en saisie.
+fn connexion<R: BufRead, W: Write>(
+    console: &mut Console<R, W>,
+    gestionnaire: &mut GestionnaireComptes,
+    identifiant: Option<String>,
+    mot_de_passe: Option<String>,
+) -> io::Result<bool> {
+    let essais = if mot_de_passe.is_some() { 1 } else { 3 };
+    for _ in 0..essais {
+        let identifiant = match &identifiant {
+            Some(identifiant) => identifiant.clone(),
+            None => console.lire_input(&tr!("habilitation.identifiant"))?,
+        };
+        let mot_de_passe = match &mot_de_passe {
+            Some(mot_de_passe) => mot_de_passe.clone(),
+            None => console.lire_secret(&tr!("habilitation.mot_de_passe"))?,
+        };
+        match gestionnaire.connecter(&identifiant, &mot_de_passe) {
+            Ok(role) => {
+                writeln!(
+                    console.sortie,
+                    "{}",
+                    tr!("habilitation.connecte", identifiant, role.libelle())
+                )?;
+                return Ok(true);
+            }
+            Err(e) => writeln!(console.sortie, "{}", e)?,
+        }
+    }
+    writeln!(console.sortie, "{}", tr!("habilitation.connexion_refusee"))?;
+    Ok(false)
+}
+
+fn main() -> ExitCode {
+    let code_langue = valeur_option("--lang");
+    let langue = match code_langue.as_deref() {
+        Some(code) => Langue::depuis_code(code),
+        None => Some(Langue::depuis_environnement()),
+    };
+    // Le message d'une langue inconnue est écrit dans la langue par défaut.
+    langue::definir(langue.unwrap_or(Langue::Francais));
+    if let (None, Some(code)) = (langue, code_langue) {
+        eprintln!("{}", tr!("session.langue_inconnue", code));
+    }
+
+    let fixture = valeur_option("--fixture").unwrap_or(FIXTURE_DEMO.to_string());
+    let tete_audit = audit::chemin_tete(Path::new(JOURNAL_AUDIT));
+    let fichiers = [
+        Path::new(JOURNAL_AUDIT),
+        tete_audit.as_path(),
+        Path::new(REGISTRE_IDEMPOTENCE),
+        Path::new(&fixture),
+        Path::new(UTILISATEURS),
+        Path::new(JOURNAL_OPERATIONS),
+    ];
+    if std::env::args().any(|arg| arg == "--changer-phrase") {
+        return changer_phrase(&fichiers);
+    }
+
+    // Tout l'état de la banque : les fichiers chiffrables et les règles de
+    // catégorisation.
+    let mut etat = fichiers.to_vec();
+    etat.push(Path::new(REGLES_CATEGORIES));
+    // Les archives sont des copies telles quelles : la sauvegarde ne déchiffre rien.
+    if std::env::args().any(|arg| arg == "--sauvegarder") {
+        let dossier =
+            option_facultative("--sauvegarder").unwrap_or(DOSSIER_SAUVEGARDES.to_string());
+        return sauvegarder(&etat, Path::new(&dossier));
+    }
+
+    // Seuls les fichiers que la commande lit sont contrôlés.
+    let journal_a_verifier = valeur_option("--verifier-audit");
+    let fichiers_lus = match &journal_a_verifier {
+        Some(chemin) => vec![Path::new(chemin)],
+        None if std::env::args().any(|arg| arg == "--verifier-audit") => {
+            vec![Path::new(JOURNAL_AUDIT)]
+        }
+        None if valeur_option("--generer-fixture").is_some() => Vec::new(),
+        None => fichiers.to_vec(),
+    };
+    let coffre = match ouvrir_coffre(&fichiers_lus) {
+        Ok(coffre) => coffre,
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    if std::env::args().any(|arg| arg == "--verifier-audit") {
+        return verifier_audit(fichiers_lus[0], &coffre);
+    }
+
+    if std::env::args().any(|arg| arg == "--restaurer") {
+        let restauration = restaurer(
+            &etat,
+            Path::new(&fixture),
+            option_facultative("--restaurer"),
+            valeur_option("--jusqu-au"),
+            &coffre,
+        );
+        return match restauration {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(e) => {
+                eprintln!("{}", e);
+                ExitCode::FAILURE
             }
+        };
+    }
+
+    if let Some(chemin) = valeur_option("--generer-fixture") {
+        return generer_fixture(Path::new(&chemin), &coffre);
+    }
+
+    if let Some(identifiant) = valeur_option("--ajouter-utilisateur") {
+        return ajouter_utilisateur(&identifiant, &coffre);
+    }
+    let utilisateurs = match lire_utilisateurs(&coffre) {
+        Ok(utilisateurs) => utilisateurs,
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let mode_tui = std::env::args().any(|arg| arg == "--tui");
+    if !mode_tui {
+        println!("=== {} ===\n", tr!("session.titre"));
+    }
+
+    let acteur = valeur_option("--acteur")
+        .or_else(|| std::env::var("USER").ok())
+        .unwrap_or_else(|| tr!("audit.acteur_inconnu"));
+
+    let mut observateurs: Vec<Box<dyn Observateur>> = Vec::new();
+    match JournalAudit::ouvrir(PathBuf::from(JOURNAL_AUDIT), acteur.clone(), coffre.clone()) {
+        Ok(journal) => observateurs.push(Box::new(journal)),
+        Err(e) => eprintln!("{}", tr!("audit.indisponible", e)),
+    }
+    observateurs.push(Box::new(IncidentsConsole));
+    if std::env::args().any(|arg| arg == "--trace") {
+        observateurs.push(Box::new(TraceConsole));
+    }
+
+    let mut gestionnaire = GestionnaireComptes::avec_observateurs(observateurs);
+    match RegistreIdempotence::ouvrir(
+        PathBuf::from(REGISTRE_IDEMPOTENCE),
+        RETENTION_PAR_DEFAUT,
+        coffre.clone(),
+    ) {
+        Ok(registre) => gestionnaire.definir_registre_idempotence(registre),
+        Err(e) => eprintln!("{}", tr!("idempotence.indisponible", e)),
+    }
+    let _ = gestionnaire.definir_acteur(&acteur);
+    if let Err(e) = ajouter_regles_fraude(&mut gestionnaire)
+        .and_then(|()| definir_cours_devises(&mut gestionnaire))
+    {
+        eprintln!("{}", e);
+    }
+    let regles = gestionnaire.charger_regles_categories(Path::new(REGLES_CATEGORIES));
+    let comptes = gestionnaire.charger_fixture_journalisee(
+        Path::new(&fixture),
+        Path::new(JOURNAL_OPERATIONS),
+        &coffre,
+    );
+    // En plein écran, les alertes sont affichées dans la barre d'état
+    // plutôt qu'écrites sur la sortie standard.
+    if !mode_tui && let Err(e) = gestionnaire.ajouter_sortie_alerte(Box::new(SortieConsole)) {
+        eprintln!("{}", e);
+    }
+
+    // Sans fichier d'utilisateurs, tout est permis et l'acteur est celui de
+    // la ligne de commande.
+    if !utilisateurs.is_empty() {
+        // Le seuil est posé avant l'annuaire, qui réserve ensuite tout
+        // paramétrage à un superviseur connecté.
+        if let Err(e) = gestionnaire
+            .definir_seuil_approbation(SEUIL_APPROBATION)
+            .and_then(|()| gestionnaire.definir_utilisateurs(utilisateurs))
+        {
+            eprintln!("{}", e);
+            return ExitCode::FAILURE;
+        }
+        let mut console = Console::new(io::stdin().lock(), io::stdout())
+            .saisie_sans_echo(io::stdin().is_terminal());
+        match connexion(
+            &mut console,
+            &mut gestionnaire,
+            valeur_option("--utilisateur"),
+            std::env::var(VARIABLE_MOT_DE_PASSE).ok(),
+        ) {
+            Ok(true) => {}
+            Ok(false) => return ExitCode::FAILURE,
+            Err(e) => {
+                eprintln!("{}", tr!("session.erreur_es", e));
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+
+    if mode_tui {
+        if let Err(e) = tui::executer(&mut gestionnaire) {
+            eprintln!("{}", tr!("session.erreur_es", e));
+        }
+        return ExitCode::SUCCESS;
+    }
+
+    if let Ok(nombre) = regles {
+        println!("{}", tr!("session.regles_chargees", nombre));
+    }
+    match comptes {
+        Ok((nombre, 0)) => println!("{}", tr!("fixture.chargee", nombre, fixture)),
+        Ok((nombre, lignes)) => {
+            println!("{}", tr!("fixture.chargee", nombre, fixture));
+            println!(
+                "{}",
+                tr!("sauvegarde.journal_rejoue", lignes, JOURNAL_OPERATIONS)
+            );
+        }
+        Err(e) => eprintln!("{}", tr!("fixture.chargement_impossible", fixture, e)),
+    }
+
+    if std::env::args().any(|arg| arg == "--repl") {
+        if let Err(e) = repl::executer(&mut gestionnaire, Path::new(HISTORIQUE_REPL)) {
+            eprintln!("{}", tr!("session.erreur_es", e));
+        }
+        return ExitCode::SUCCESS;
+    }
+
+    let mut console =
+        Console::new(io::stdin().lock(), io::stdout()).saisie_sans_echo(io::stdin().is_terminal());
+    if let Err(e) = executer_session(&mut console, &mut gestionnaire) {
+        eprintln!("{}", tr!("session.erreur_es", e));
+    }
+    ExitCode::SUCCESS
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    type ConsoleScriptee = Console<Cursor<Vec<u8>>, Vec<u8>>;
+
+    fn demo() -> GestionnaireComptes {
+        let mut gestionnaire = GestionnaireComptes::new();
+        gestionnaire
+            .charger_fixture(Path::new(FIXTURE_DEMO), &Coffre::en_clair())
+            .unwrap();
+        gestionnaire
+    }
+
+    // Console qui lit `script` ; la session se termine à la fin du script,
+    // sans avoir à choisir « Quitter ».
+    fn console(script: &str) -> ConsoleScriptee {
+        Console::new(Cursor::new(script.as_bytes().to_vec()), Vec::new())
+    }
+
+    fn sortie(console: ConsoleScriptee) -> String {
+        String::from_utf8(console.sortie).unwrap()
+    }
+
+    // Joue `script` dans une session sur `gestionnaire` et retourne ce qui a été affiché.
+    fn jouer_sur(gestionnaire: &mut GestionnaireComptes, script: &str) -> String {
+        let mut console = console(script);
+        executer_session(&mut console, gestionnaire).unwrap();
+        sortie(console)
+    }
+
+    fn jouer(script: &str) -> (GestionnaireComptes, String) {
+        let mut gestionnaire = demo();
+        let sortie = jouer_sur(&mut gestionnaire, script);
+        (gestionnaire, sortie)
+    }
+
+    #[test]
+    fn entree_vide_termine_la_session() {
+        let (_, sortie) = jouer("");
+        assert!(sortie.ends_with("Au revoir!\n"));
+    }
+
+    #[test]
+    fn eof_pendant_la_saisie_du_montant_ne_boucle_pas() {
+        let (gestionnaire, sortie) = jouer("n\n3\n12345\nabc\n");
+        assert_eq!(sortie.matches("Montant invalide").count(), 1);
+        assert!(sortie.ends_with("Au revoir!\n"));
+        assert_eq!(gestionnaire.compte("12345").unwrap().solde(), 1500.0);
+    }
+
+    #[test]
+    fn depot_puis_retrait_via_le_menu() {
+        let (gestionnaire, sortie) = jouer("n\n3\n12345\n100\n\n\n2\n12345\n40\nCourses\n\n");
+        assert!(sortie.contains("Dépôt de 100,00€ effectué. Nouveau solde: 1600,00€"));
+        assert!(sortie.contains("Retrait de 40,00€ effectué. Nouveau solde: 1560,00€"));
+        let compte = gestionnaire.compte("12345").unwrap();
+        assert_eq!(
+            compte.historique().last().unwrap().libelle.to_string(),
+            "Courses"
+        );
+    }
+
+    #[test]
+    fn creation_de_compte_au_demarrage() {
+        let (gestionnaire, sortie) = jouer("o\n55555\nDenis Petit\n250\n4\n\n\n");
+        assert!(sortie.contains("Compte créé avec succès!"));
+        assert!(sortie.contains("Compte: 55555 | Titulaire: Denis Petit | Solde: 250,00€"));
+        assert_eq!(gestionnaire.comptes().len(), 4);
+    }
+
+    #[test]
+    fn erreur_metier_affichee_sans_interrompre_le_menu() {
+        // Ce script quitte par le menu plutôt qu'à la fin de la saisie.
+        let (_, sortie) = jouer("n\n2\n11111\n5000\n\n\n15\n");
+        assert!(sortie.contains("Solde insuffisant"));
+        assert!(sortie.ends_with("Au revoir!\n"));
+    }
+
+    #[test]
+    fn liste_triee_filtree_et_selection_par_position() {
+        let (gestionnaire, sortie) = jouer("n\n4\nt\n3\no\nf\nar\n\n2500\n\n\n\n3\n1\n50\n\n\n");
+        let liste = &sortie[sortie.find("tri par solde, décroissant").unwrap()..];
+        let bob = liste.find("Compte: 67890").unwrap();
+        let claire = liste.find("Compte: 11111").unwrap();
+        assert!(bob < claire);
+        assert!(liste.contains("2 compte(s) sur 3 correspondent aux filtres."));
+        assert!(sortie.contains("3. 67890 - Bob Martin"));
+        assert_eq!(gestionnaire.compte("11111").unwrap().solde(), 850.0);
+    }
+
+    #[test]
+    fn objectif_avec_virement_permanent_via_le_menu() {
+        let aujourd_hui = Local::now().date_naive();
+        let limite = (aujourd_hui + chrono::Months::new(12)).format("%d/%m/%Y");
+        let debut = aujourd_hui.format("%d/%m/%Y");
+        let script = format!(
+            "n\n9\n1\n11111\nVacances\n1600\n{limite}\no\n67890\n100\n{debut}\n\n\
+             9\n4\n\n9\n2\n11111\n\n"
+        );
+        let (gestionnaire, sortie) = jouer(&script);
+        assert!(sortie.contains("Objectif ajouté."));
+        assert!(sortie.contains("100,00€ vers 11111 pour « Vacances »"));
+        assert!(sortie.contains("Vacances : 900,00€ / 1600,00€ (56 %)"));
+        assert_eq!(gestionnaire.compte("67890").unwrap().solde(), 2200.0);
+    }
+
+    #[test]
+    fn remise_puis_rejet_de_cheque_via_le_menu() {
+        let (gestionnaire, sortie) = jouer(
+            "n\n12\n1\n12345\n0000123\nBob Martin\n200\n\n1\n12345\n\n\
+             12\n4\n12345\n0000123\n\n",
+        );
+        assert!(sortie.contains("Chèque de 200,00€ remis, disponible le "));
+        assert!(sortie.contains("Solde: 1500,00€"));
+        assert!(sortie.contains("Chèques en attente d'encaissement: 200,00€"));
+        assert!(sortie.contains("Chèque n°0000123 rejeté. Nouveau solde: 1470,00€"));
+        assert_eq!(gestionnaire.montant_en_attente("12345"), 0.0);
+        assert_eq!(gestionnaire.compte("12345").unwrap().solde(), 1470.0);
+    }
+
+    #[test]
+    fn caisse_ouverte_puis_journee_cloturee_via_le_menu() {
+        let (gestionnaire, sortie) = jouer(
+            "n\n13\n1\n300\n\n3\n12345\n100\n\n\n2\n67890\n50\n\n\n\
+             13\n3\n345\n\n3\n12345\n10\n\n\n",
+        );
+        let rapport = &gestionnaire.clotures()[0];
+        assert_eq!(rapport.session.total_depots(), 100.0);
+        assert_eq!(rapport.session.total_retraits(), 50.0);
+        assert_eq!(rapport.session.especes_attendues(), 350.0);
+        assert_eq!(rapport.ecart(), -5.0);
+        assert!(sortie.contains("Caisse ouverte."));
+        assert!(sortie.contains("Dépôts en espèces (1): 100,00€"));
+        assert!(sortie.contains("Retraits en espèces (1): 50,00€"));
+        assert!(sortie.contains("Espèces attendues: 350,00€"));
+        assert!(sortie.contains("Écart: -5,00€"));
+        assert!(sortie.contains("La journée est clôturée"));
+    }
+
+    #[test]
+    fn releve_html_via_le_menu() {
+        let chemin = std::env::temp_dir().join(format!("tp1-releve-{}.html", std::process::id()));
+        let (_, sortie) = jouer(&format!(
+            "n\n7\n5\n12345\n01/01/2000\n31/12/2999\n{}\n\n",
+            chemin.display()
+        ));
+        assert!(sortie.contains("mouvement(s) écrit dans"));
+        let html = std::fs::read_to_string(&chemin).unwrap();
+        std::fs::remove_file(&chemin).unwrap();
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<dd>Alice Dupont</dd>"));
+        assert!(html.contains("Solde d&#39;ouverture"));
+    }
+
+    #[test]
+    fn rapport_des_comptes_inactifs_via_le_menu() {
+        let (gestionnaire, sortie) = jouer("n\n11\n4\n\n");
+        assert!(sortie.contains("=== Comptes inactifs au "));
+        assert!(sortie.contains("Aucun compte n'approche le seuil légal."));
+        assert!(!gestionnaire.est_dormant("12345"));
+    }
+
+    #[test]
+    fn operation_a_confirmer_puis_file_d_examen() {
+        let mut gestionnaire = demo();
+        ajouter_regles_fraude(&mut gestionnaire).unwrap();
+        definir_cours_devises(&mut gestionnaire).unwrap();
+        let sortie = jouer_sur(
+            &mut gestionnaire,
+            "n\n10\n12345\nACME\nusd\n100\n\nn\n\n\
+             10\n12345\nACME\nusd\n100\n\no\n\n11\n1\n\n",
+        );
+
+        assert!(sortie.contains("Opération suspecte: premier virement en USD depuis longtemps"));
+        assert!(sortie.contains("Opération à confirmer: premier virement en USD"));
+        assert!(sortie.contains(
+            "Virement de 100,00 USD vers ACME effectué, 92,00€ débités. Nouveau solde: 1408,00€"
+        ));
+        assert!(sortie.contains("1. [") && !sortie.contains("2. ["));
+        assert!(sortie.contains("(à confirmer, confirmée)"));
+        assert_eq!(gestionnaire.compte("12345").unwrap().solde(), 1408.0);
+    }
+
+    #[test]
+    fn connexion_puis_retrait_approuve_par_un_superviseur() {
+        let mut gestionnaire = demo();
+        let mut annuaire = Annuaire::new();
+        for (identifiant, role, mot_de_passe) in [
+            ("alice", Role::Guichetier, "a1"),
+            ("sam", Role::Superviseur, "s1"),
+        ] {
+            annuaire
+                .ajouter(Utilisateur::avec_iterations(
+                    identifiant,
+                    role,
+                    mot_de_passe,
+                    10,
+                ))
+                .unwrap();
         }
+        gestionnaire
+            .definir_seuil_approbation(SEUIL_APPROBATION)
+            .unwrap();
+        gestionnaire.definir_utilisateurs(annuaire).unwrap();
+
+        let mut console = console(
+            "alice\nfaux\nalice\na1\n\
+             n\n2\n67890\n1200\n\nsam\nmauvais\n\n\
+             2\n67890\n1200\n\nsam\ns1\n\n",
+        );
+        assert!(connexion(&mut console, &mut gestionnaire, None, None).unwrap());
+        executer_session(&mut console, &mut gestionnaire).unwrap();
+        let sortie = sortie(console);
 
-        println!("\nAppuyez sur Entrée pour continuer...");
-        let mut pause = String::new();
-        io::stdin().read_line(&mut pause).unwrap();
+        assert!(sortie.contains("Identifiant ou mot de passe incorrect"));
+        assert!(sortie.contains("Connecté en tant que alice (guichetier)."));
+        assert_eq!(
+            sortie
+                .matches("Approbation d'un superviseur requise")
+                .count(),
+            2
+        );
+        assert!(sortie.contains("Retrait de 1200,00€ effectué. Nouveau solde: 1100,00€"));
+        assert_eq!(gestionnaire.compte("67890").unwrap().solde(), 1100.0);
+    }
+
+    #[test]
+    fn mandat_prelevement_puis_remboursement_via_le_menu() {
+        let signe_le = Local::now().date_naive().format("%d/%m/%Y");
+        let script = format!(
+            "n\n14\n1\n12345\nFR12ZZZ123456\nEDF-001\n{}\n150\n\n\
+             14\n3\n12345\nFR12ZZZ123456\nEDF-001\n200\n\n\
+             14\n3\n12345\nFR12ZZZ123456\nEDF-001\n120\n\n\
+             14\n4\n12345\n1\n\n",
+            signe_le
+        );
+        let (gestionnaire, sortie) = jouer(&script);
+        assert!(sortie.contains("Mandat enregistré."));
+        assert!(sortie.contains("Le montant dépasse le plafond du mandat EDF-001"));
+        assert!(sortie.contains("Prélèvement de 120,00€ effectué. Nouveau solde: 1380,00€"));
+        assert!(sortie.contains("Prélèvement remboursé. Nouveau solde: 1500,00€"));
+        assert!(gestionnaire.prelevements("12345")[0].rembourse);
     }
 }
@@ -0,0 +1,169 @@
+//! Objectifs d'épargne attachés aux comptes et virements permanents qui les alimentent.
+
+use crate::erreur::ErreurBancaire;
+use crate::{langue, tr};
+use chrono::{Datelike, Months, NaiveDate};
+use std::io::{self, Write};
+
+/// Virement mensuel d'un autre compte vers le compte de l'objectif.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VirementPermanent {
+    pub source: String,
+    pub montant: f32,
+    pub prochaine_date: NaiveDate,
+}
+
+/// Somme à réunir sur un compte avant une date. L'épargne déjà réunie
+/// est le solde du compte auquel l'objectif est attaché.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ObjectifEpargne {
+    pub nom: String,
+    pub montant_cible: f32,
+    pub date_limite: NaiveDate,
+    pub virement: Option<VirementPermanent>,
+}
+
+impl ObjectifEpargne {
+    pub fn new(nom: String, montant_cible: f32, date_limite: NaiveDate) -> Self {
+        ObjectifEpargne {
+            nom,
+            montant_cible,
+            date_limite,
+            virement: None,
+        }
+    }
+
+    /// Part de l'objectif atteinte avec `solde`, en pourcentage borné à 100.
+    pub fn pourcentage(&self, solde: f32) -> f32 {
+        if self.montant_cible <= 0.0 {
+            return 100.0;
+        }
+        (solde.max(0.0) * 100.0 / self.montant_cible).min(100.0)
+    }
+
+    /// Montant qu'il reste à épargner, nul une fois l'objectif atteint.
+    pub fn reste(&self, solde: f32) -> f32 {
+        (self.montant_cible - solde.max(0.0)).max(0.0)
+    }
+
+    /// Montant à mettre de côté chaque mois pour atteindre l'objectif à temps ;
+    /// `None` si la date limite est dépassée.
+    pub fn mensualite_necessaire(&self, solde: f32, aujourd_hui: NaiveDate) -> Option<f32> {
+        match mois_restants(aujourd_hui, self.date_limite) {
+            0 => None,
+            mois => Some(self.reste(solde) / mois as f32),
+        }
+    }
+
+    pub fn afficher(
+        &self,
+        solde: f32,
+        aujourd_hui: NaiveDate,
+        sortie: &mut impl Write,
+    ) -> io::Result<()> {
+        writeln!(
+            sortie,
+            "{}",
+            tr!(
+                "objectif.progression",
+                self.nom,
+                langue::montant(solde),
+                langue::montant(self.montant_cible),
+                format!("{:.0}", self.pourcentage(solde)),
+                self.date_limite.format("%d/%m/%Y")
+            )
+        )?;
+        let reste = self.reste(solde);
+        if reste == 0.0 {
+            writeln!(sortie, "    {}", tr!("objectif.atteint"))?;
+        } else {
+            match self.mensualite_necessaire(solde, aujourd_hui) {
+                Some(mensualite) => writeln!(
+                    sortie,
+                    "    {}",
+                    tr!(
+                        "objectif.reste",
+                        langue::montant(reste),
+                        langue::montant(mensualite)
+                    )
+                )?,
+                None => writeln!(
+                    sortie,
+                    "    {}",
+                    tr!("objectif.depasse", langue::montant(reste))
+                )?,
+            }
+        }
+        if let Some(virement) = &self.virement {
+            writeln!(
+                sortie,
+                "    {}",
+                tr!(
+                    "objectif.virement",
+                    langue::montant(virement.montant),
+                    virement.source,
+                    virement.prochaine_date.format("%d/%m/%Y")
+                )
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Nombre de versements mensuels possibles de `debut` à `fin` incluses.
+pub fn mois_restants(debut: NaiveDate, fin: NaiveDate) -> u32 {
+    if fin < debut {
+        return 0;
+    }
+    let mois = (fin.year() - debut.year()) * 12 + fin.month() as i32 - debut.month() as i32;
+    let mois = mois as u32;
+    // Le dernier mois ne compte que si son jour de versement précède la date limite.
+    if debut + Months::new(mois) <= fin {
+        mois + 1
+    } else {
+        mois
+    }
+}
+
+/// Résultat d'un virement permanent exécuté pour un objectif.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VirementExecute {
+    pub numero: String,
+    pub objectif: String,
+    pub date: NaiveDate,
+    pub montant: f32,
+    pub resultat: Result<f32, ErreurBancaire>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(jour: u32, mois: u32, annee: i32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(annee, mois, jour).unwrap()
+    }
+
+    #[test]
+    fn progression_et_mensualite_necessaire() {
+        let objectif = ObjectifEpargne::new("Vacances".to_string(), 1500.0, date(30, 6, 2027));
+        assert_eq!(objectif.pourcentage(450.0), 30.0);
+        assert_eq!(objectif.pourcentage(2000.0), 100.0);
+        assert_eq!(objectif.pourcentage(-20.0), 0.0);
+        assert_eq!(objectif.reste(450.0), 1050.0);
+
+        assert_eq!(mois_restants(date(19, 10, 2026), date(30, 6, 2027)), 9);
+        assert_eq!(mois_restants(date(19, 10, 2026), date(10, 6, 2027)), 8);
+        assert_eq!(mois_restants(date(19, 10, 2026), date(19, 10, 2026)), 1);
+        assert_eq!(mois_restants(date(19, 10, 2026), date(18, 10, 2026)), 0);
+
+        let aujourd_hui = date(19, 10, 2026);
+        assert_eq!(
+            objectif.mensualite_necessaire(600.0, aujourd_hui),
+            Some(100.0)
+        );
+        assert_eq!(
+            objectif.mensualite_necessaire(600.0, date(1, 7, 2027)),
+            None
+        );
+    }
+}
@@ -0,0 +1,643 @@
+//! Observateurs notifiés avant et après chaque opération du gestionnaire.
+
+use crate::alerte::RegleAlerte;
+use crate::erreur::ErreurBancaire;
+use crate::fraude::Decision;
+use crate::pret::ModeAmortissement;
+use crate::transaction::Libelle;
+use crate::{langue, tr};
+use chrono::NaiveDate;
+use std::fmt;
+
+/// Description d'une opération soumise au gestionnaire de comptes.
+#[derive(Debug, Clone)]
+pub enum Evenement {
+    OuvertureCompte {
+        numero: String,
+        titulaire: String,
+        solde_initial: f32,
+    },
+    Depot {
+        numero: String,
+        montant: f32,
+        libelle: String,
+    },
+    Retrait {
+        numero: String,
+        montant: f32,
+        libelle: String,
+    },
+    Virement {
+        source: String,
+        destination: String,
+        montant: f32,
+        libelle: String,
+    },
+    VirementExterne {
+        source: String,
+        beneficiaire: String,
+        montant: f32,
+        devise: String,
+        libelle: String,
+    },
+    OuverturePret {
+        numero: String,
+        compte_lie: String,
+        capital: f32,
+        taux_annuel: f32,
+        duree_mois: u32,
+        mode: ModeAmortissement,
+    },
+    PrelevementEcheance {
+        numero_pret: String,
+    },
+    RemboursementAnticipe {
+        numero_pret: String,
+        montant: f32,
+    },
+    CategorieModifiee {
+        numero: String,
+        index: usize,
+        categorie: String,
+    },
+    RegleAlerteAjoutee {
+        numero: String,
+        regle: RegleAlerte,
+    },
+    ReglesAlerteSupprimees {
+        numero: String,
+    },
+    ReglesCategoriesChargees {
+        chemin: String,
+    },
+    ConditionsChequesDefinies {
+        delai_jours_ouvres: u32,
+        frais_rejet: f32,
+    },
+    DelaiRemboursementDefini {
+        jours: u32,
+    },
+    ConditionsDormanceDefinies {
+        dormance_mois: u32,
+        seuil_legal_mois: u32,
+        preavis_mois: u32,
+    },
+    SeuilApprobationDefini {
+        seuil: f32,
+    },
+    /// Cours en euros d'une unité de `devise`.
+    CoursDeviseDefini {
+        devise: String,
+        cours: f32,
+    },
+    UtilisateursDefinis {
+        identifiants: Vec<String>,
+    },
+    /// Règle désignée par son [`code`](crate::fraude::RegleFraude::code).
+    RegleFraudeAjoutee {
+        regle: String,
+        decision: Decision,
+    },
+    /// Sortie désignée par son [`code`](crate::alerte::SortieAlerte::code).
+    SortieAlerteAjoutee {
+        sortie: String,
+    },
+    ChangementActeur {
+        acteur: String,
+    },
+    ObjectifAjoute {
+        numero: String,
+        nom: String,
+        montant_cible: f32,
+        date_limite: NaiveDate,
+    },
+    ObjectifSupprime {
+        numero: String,
+        nom: String,
+    },
+    RemiseCheque {
+        numero: String,
+        cheque: String,
+        emetteur: String,
+        montant: f32,
+    },
+    EncaissementCheque {
+        numero: String,
+        cheque: String,
+        montant: f32,
+    },
+    RejetCheque {
+        numero: String,
+        cheque: String,
+    },
+    OuvertureCaisse {
+        fond: f32,
+    },
+    ClotureCaisse {
+        especes_comptees: f32,
+    },
+    MandatSigne {
+        numero: String,
+        creancier: String,
+        reference: String,
+        montant_max: f32,
+    },
+    PrelevementMandat {
+        numero: String,
+        creancier: String,
+        reference: String,
+        montant: f32,
+    },
+    RemboursementPrelevement {
+        numero: String,
+        index: usize,
+    },
+    RevocationMandat {
+        numero: String,
+        creancier: String,
+        reference: String,
+    },
+    MiseEnDormance {
+        numero: String,
+        dernier_mouvement: NaiveDate,
+    },
+    Connexion {
+        identifiant: String,
+    },
+    Approbation {
+        superviseur: String,
+    },
+    SignalementExamine {
+        index: usize,
+    },
+}
+
+// Chaque champ est préfixé de sa longueur : aucun libellé ne peut imiter
+// un séparateur et faire passer une opération pour une autre.
+fn canonique(nom: &str, champs: &[&dyn fmt::Display]) -> String {
+    let mut texte = nom.to_string();
+    for champ in champs {
+        let champ = champ.to_string();
+        texte.push_str(&format!(";{}:{}", champ.len(), champ));
+    }
+    texte
+}
+
+impl Evenement {
+    /// Sérialisation stable de l'opération, indépendante de la langue et de la
+    /// représentation `Debug` : sert d'empreinte aux clés d'idempotence.
+    pub fn forme_canonique(&self) -> String {
+        match self {
+            Evenement::OuvertureCompte {
+                numero,
+                titulaire,
+                solde_initial,
+            } => canonique("ouverture_compte", &[numero, titulaire, solde_initial]),
+            Evenement::Depot {
+                numero,
+                montant,
+                libelle,
+            } => canonique("depot", &[numero, montant, libelle]),
+            Evenement::Retrait {
+                numero,
+                montant,
+                libelle,
+            } => canonique("retrait", &[numero, montant, libelle]),
+            Evenement::Virement {
+                source,
+                destination,
+                montant,
+                libelle,
+            } => canonique("virement", &[source, destination, montant, libelle]),
+            Evenement::VirementExterne {
+                source,
+                beneficiaire,
+                montant,
+                devise,
+                libelle,
+            } => canonique(
+                "virement_externe",
+                &[source, beneficiaire, montant, devise, libelle],
+            ),
+            Evenement::OuverturePret {
+                numero,
+                compte_lie,
+                capital,
+                taux_annuel,
+                duree_mois,
+                mode,
+            } => canonique(
+                "ouverture_pret",
+                &[
+                    numero,
+                    compte_lie,
+                    capital,
+                    taux_annuel,
+                    duree_mois,
+                    &mode.code(),
+                ],
+            ),
+            Evenement::PrelevementEcheance { numero_pret } => {
+                canonique("prelevement_echeance", &[numero_pret])
+            }
+            Evenement::RemboursementAnticipe {
+                numero_pret,
+                montant,
+            } => canonique("remboursement_anticipe", &[numero_pret, montant]),
+            Evenement::CategorieModifiee {
+                numero,
+                index,
+                categorie,
+            } => canonique("categorie_modifiee", &[numero, index, categorie]),
+            Evenement::RegleAlerteAjoutee { numero, regle } => {
+                canonique("regle_alerte_ajoutee", &[numero, &regle.code()])
+            }
+            Evenement::ReglesAlerteSupprimees { numero } => {
+                canonique("regles_alerte_supprimees", &[numero])
+            }
+            Evenement::ReglesCategoriesChargees { chemin } => {
+                canonique("regles_categories_chargees", &[chemin])
+            }
+            Evenement::ConditionsChequesDefinies {
+                delai_jours_ouvres,
+                frais_rejet,
+            } => canonique(
+                "conditions_cheques_definies",
+                &[delai_jours_ouvres, frais_rejet],
+            ),
+            Evenement::DelaiRemboursementDefini { jours } => {
+                canonique("delai_remboursement_defini", &[jours])
+            }
+            Evenement::ConditionsDormanceDefinies {
+                dormance_mois,
+                seuil_legal_mois,
+                preavis_mois,
+            } => canonique(
+                "conditions_dormance_definies",
+                &[dormance_mois, seuil_legal_mois, preavis_mois],
+            ),
+            Evenement::SeuilApprobationDefini { seuil } => {
+                canonique("seuil_approbation_defini", &[seuil])
+            }
+            Evenement::CoursDeviseDefini { devise, cours } => {
+                canonique("cours_devise_defini", &[devise, cours])
+            }
+            Evenement::UtilisateursDefinis { identifiants } => {
+                let identifiants: Vec<&dyn fmt::Display> = identifiants
+                    .iter()
+                    .map(|i| i as &dyn fmt::Display)
+                    .collect();
+                canonique("utilisateurs_definis", &identifiants)
+            }
+            Evenement::RegleFraudeAjoutee { regle, decision } => {
+                canonique("regle_fraude_ajoutee", &[regle, &decision.code()])
+            }
+            Evenement::SortieAlerteAjoutee { sortie } => {
+                canonique("sortie_alerte_ajoutee", &[sortie])
+            }
+            Evenement::ChangementActeur { acteur } => canonique("changement_acteur", &[acteur]),
+            Evenement::ObjectifAjoute {
+                numero,
+                nom,
+                montant_cible,
+                date_limite,
+            } => canonique(
+                "objectif_ajoute",
+                &[numero, nom, montant_cible, date_limite],
+            ),
+            Evenement::ObjectifSupprime { numero, nom } => {
+                canonique("objectif_supprime", &[numero, nom])
+            }
+            Evenement::RemiseCheque {
+                numero,
+                cheque,
+                emetteur,
+                montant,
+            } => canonique("remise_cheque", &[numero, cheque, emetteur, montant]),
+            Evenement::EncaissementCheque {
+                numero,
+                cheque,
+                montant,
+            } => canonique("encaissement_cheque", &[numero, cheque, montant]),
+            Evenement::RejetCheque { numero, cheque } => {
+                canonique("rejet_cheque", &[numero, cheque])
+            }
+            Evenement::OuvertureCaisse { fond } => canonique("ouverture_caisse", &[fond]),
+            Evenement::ClotureCaisse { especes_comptees } => {
+                canonique("cloture_caisse", &[especes_comptees])
+            }
+            Evenement::MandatSigne {
+                numero,
+                creancier,
+                reference,
+                montant_max,
+            } => canonique("mandat_signe", &[numero, creancier, reference, montant_max]),
+            Evenement::PrelevementMandat {
+                numero,
+                creancier,
+                reference,
+                montant,
+            } => canonique(
+                "prelevement_mandat",
+                &[numero, creancier, reference, montant],
+            ),
+            Evenement::RemboursementPrelevement { numero, index } => {
+                canonique("remboursement_prelevement", &[numero, index])
+            }
+            Evenement::RevocationMandat {
+                numero,
+                creancier,
+                reference,
+            } => canonique("revocation_mandat", &[numero, creancier, reference]),
+            Evenement::MiseEnDormance {
+                numero,
+                dernier_mouvement,
+            } => canonique("mise_en_dormance", &[numero, dernier_mouvement]),
+            Evenement::Connexion { identifiant } => canonique("connexion", &[identifiant]),
+            Evenement::Approbation { superviseur } => canonique("approbation", &[superviseur]),
+            Evenement::SignalementExamine { index } => canonique("signalement_examine", &[index]),
+        }
+    }
+}
+
+impl fmt::Display for Evenement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let description = match self {
+            Evenement::OuvertureCompte {
+                numero,
+                titulaire,
+                solde_initial,
+            } => tr!(
+                "evenement.ouverture_compte",
+                numero,
+                titulaire,
+                langue::montant(*solde_initial)
+            ),
+            Evenement::Depot {
+                numero,
+                montant,
+                libelle,
+            } => tr!(
+                "evenement.depot",
+                langue::montant(*montant),
+                numero,
+                libelle
+            ),
+            Evenement::Retrait {
+                numero,
+                montant,
+                libelle,
+            } => tr!(
+                "evenement.retrait",
+                langue::montant(*montant),
+                numero,
+                libelle
+            ),
+            Evenement::Virement {
+                source,
+                destination,
+                montant,
+                libelle,
+            } => tr!(
+                "evenement.virement",
+                langue::montant(*montant),
+                source,
+                destination,
+                Libelle::depuis_code(libelle)
+            ),
+            Evenement::VirementExterne {
+                source,
+                beneficiaire,
+                montant,
+                devise,
+                libelle,
+            } => tr!(
+                "evenement.virement_externe",
+                langue::nombre(*montant),
+                devise,
+                source,
+                beneficiaire,
+                libelle
+            ),
+            Evenement::OuverturePret {
+                numero,
+                compte_lie,
+                capital,
+                taux_annuel,
+                duree_mois,
+                mode,
+            } => tr!(
+                "evenement.ouverture_pret",
+                numero,
+                langue::montant(*capital),
+                langue::nombre(*taux_annuel),
+                duree_mois,
+                mode.libelle(),
+                compte_lie
+            ),
+            Evenement::PrelevementEcheance { numero_pret } => {
+                tr!("evenement.prelevement_echeance", numero_pret)
+            }
+            Evenement::RemboursementAnticipe {
+                numero_pret,
+                montant,
+            } => tr!(
+                "evenement.remboursement_anticipe",
+                langue::montant(*montant),
+                numero_pret
+            ),
+            Evenement::CategorieModifiee {
+                numero,
+                index,
+                categorie,
+            } => tr!("evenement.categorie_modifiee", index, numero, categorie),
+            Evenement::RegleAlerteAjoutee { numero, regle } => {
+                tr!("evenement.regle_alerte_ajoutee", numero, regle)
+            }
+            Evenement::ReglesAlerteSupprimees { numero } => {
+                tr!("evenement.regles_alerte_supprimees", numero)
+            }
+            Evenement::ReglesCategoriesChargees { chemin } => {
+                tr!("evenement.regles_categories_chargees", chemin)
+            }
+            Evenement::ConditionsChequesDefinies {
+                delai_jours_ouvres,
+                frais_rejet,
+            } => tr!(
+                "evenement.conditions_cheques_definies",
+                delai_jours_ouvres,
+                langue::montant(*frais_rejet)
+            ),
+            Evenement::DelaiRemboursementDefini { jours } => {
+                tr!("evenement.delai_remboursement_defini", jours)
+            }
+            Evenement::ConditionsDormanceDefinies {
+                dormance_mois,
+                seuil_legal_mois,
+                preavis_mois,
+            } => tr!(
+                "evenement.conditions_dormance_definies",
+                dormance_mois,
+                seuil_legal_mois,
+                preavis_mois
+            ),
+            Evenement::SeuilApprobationDefini { seuil } => {
+                tr!(
+                    "evenement.seuil_approbation_defini",
+                    langue::montant(*seuil)
+                )
+            }
+            Evenement::CoursDeviseDefini { devise, cours } => {
+                tr!(
+                    "evenement.cours_devise_defini",
+                    devise,
+                    langue::nombre(*cours)
+                )
+            }
+            Evenement::UtilisateursDefinis { identifiants } => {
+                tr!("evenement.utilisateurs_definis", identifiants.join(", "))
+            }
+            Evenement::RegleFraudeAjoutee { regle, decision } => {
+                tr!("evenement.regle_fraude_ajoutee", regle, decision.libelle())
+            }
+            Evenement::SortieAlerteAjoutee { sortie } => {
+                tr!("evenement.sortie_alerte_ajoutee", sortie)
+            }
+            Evenement::ChangementActeur { acteur } => tr!("evenement.changement_acteur", acteur),
+            Evenement::ObjectifAjoute {
+                numero,
+                nom,
+                montant_cible,
+                date_limite,
+            } => tr!(
+                "evenement.objectif_ajoute",
+                nom,
+                langue::montant(*montant_cible),
+                date_limite.format("%d/%m/%Y"),
+                numero
+            ),
+            Evenement::ObjectifSupprime { numero, nom } => {
+                tr!("evenement.objectif_supprime", nom, numero)
+            }
+            Evenement::RemiseCheque {
+                numero,
+                cheque,
+                emetteur,
+                montant,
+            } => tr!(
+                "evenement.remise_cheque",
+                cheque,
+                emetteur,
+                langue::montant(*montant),
+                numero
+            ),
+            Evenement::EncaissementCheque {
+                numero,
+                cheque,
+                montant,
+            } => tr!(
+                "evenement.encaissement_cheque",
+                cheque,
+                langue::montant(*montant),
+                numero
+            ),
+            Evenement::RejetCheque { numero, cheque } => {
+                tr!("evenement.rejet_cheque", cheque, numero)
+            }
+            Evenement::OuvertureCaisse { fond } => {
+                tr!("evenement.ouverture_caisse", langue::montant(*fond))
+            }
+            Evenement::ClotureCaisse { especes_comptees } => {
+                tr!(
+                    "evenement.cloture_caisse",
+                    langue::montant(*especes_comptees)
+                )
+            }
+            Evenement::MandatSigne {
+                numero,
+                creancier,
+                reference,
+                montant_max,
+            } => tr!(
+                "evenement.mandat_signe",
+                reference,
+                creancier,
+                langue::montant(*montant_max),
+                numero
+            ),
+            Evenement::PrelevementMandat {
+                numero,
+                creancier,
+                reference,
+                montant,
+            } => tr!(
+                "evenement.prelevement_mandat",
+                langue::montant(*montant),
+                numero,
+                creancier,
+                reference
+            ),
+            Evenement::RemboursementPrelevement { numero, index } => {
+                tr!("evenement.remboursement_prelevement", index, numero)
+            }
+            Evenement::RevocationMandat {
+                numero,
+                creancier,
+                reference,
+            } => tr!("evenement.revocation_mandat", reference, creancier, numero),
+            Evenement::MiseEnDormance {
+                numero,
+                dernier_mouvement,
+            } => tr!(
+                "evenement.mise_en_dormance",
+                numero,
+                dernier_mouvement.format("%d/%m/%Y")
+            ),
+            Evenement::Connexion { identifiant } => tr!("evenement.connexion", identifiant),
+            Evenement::Approbation { superviseur } => {
+                tr!("evenement.approbation", superviseur)
+            }
+            Evenement::SignalementExamine { index } => {
+                tr!("evenement.signalement_examine", index)
+            }
+        };
+        write!(f, "{}", description)
+    }
+}
+
+/// Point d'extension appelé par le gestionnaire autour de chaque opération.
+///
+/// `avant` peut refuser l'opération en retournant un motif ; `apres` reçoit
+/// le résultat, y compris les échecs et les refus.
+pub trait Observateur {
+    fn avant(&mut self, _evenement: &Evenement) -> Result<(), String> {
+        Ok(())
+    }
+
+    fn apres(&mut self, _evenement: &Evenement, _resultat: Result<(), &ErreurBancaire>) {}
+
+    /// Incident qui ne fait pas échouer l'opération, comme un fichier
+    /// qui n'a pas pu être écrit.
+    fn incident(&mut self, _message: &str) {}
+}
+
+/// Affiche les incidents sur la sortie d'erreur.
+pub struct IncidentsConsole;
+
+impl Observateur for IncidentsConsole {
+    fn incident(&mut self, message: &str) {
+        eprintln!("{}", message);
+    }
+}
+
+/// Affiche chaque opération et son issue sur la sortie d'erreur.
+pub struct TraceConsole;
+
+impl Observateur for TraceConsole {
+    fn apres(&mut self, evenement: &Evenement, resultat: Result<(), &ErreurBancaire>) {
+        match resultat {
+            Ok(()) => eprintln!("[trace] {}", tr!("trace.succes", evenement)),
+            Err(e) => eprintln!("[trace] {}", tr!("trace.echec", evenement, e)),
+        }
+    }
+}
@@ -0,0 +1,116 @@
+//! Prélèvements : un créancier débite un compte courant dans la limite d'un
+//! mandat signé par le client, qui peut le révoquer et se faire rembourser un
+//! prélèvement pendant quelques semaines.
+
+use crate::{langue, tr};
+use chrono::{Days, NaiveDate};
+use std::fmt;
+
+/// Jours pendant lesquels un prélèvement peut être remboursé, par défaut (8 semaines).
+pub const DELAI_REMBOURSEMENT_PAR_DEFAUT: u32 = 56;
+
+/// Autorisation donnée par le titulaire d'un compte à un créancier.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mandat {
+    pub compte: String,
+    /// Identifiant du créancier.
+    pub creancier: String,
+    /// Référence unique du mandat chez ce créancier.
+    pub reference: String,
+    pub signe_le: NaiveDate,
+    /// Montant maximal d'un prélèvement.
+    pub montant_max: f32,
+    pub revoque_le: Option<NaiveDate>,
+}
+
+impl Mandat {
+    /// Le mandat est signé et n'est pas révoqué à cette date.
+    pub fn est_valide(&self, date: NaiveDate) -> bool {
+        self.signe_le <= date && self.revoque_le.is_none_or(|revocation| date < revocation)
+    }
+}
+
+impl fmt::Display for Mandat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let etat = match self.revoque_le {
+            Some(date) => tr!("prelevements.revoque_le", date.format("%d/%m/%Y")),
+            None => tr!("prelevements.actif"),
+        };
+        write!(
+            f,
+            "{}",
+            tr!(
+                "prelevements.mandat",
+                self.reference,
+                self.creancier,
+                self.signe_le.format("%d/%m/%Y"),
+                langue::montant(self.montant_max),
+                etat
+            )
+        )
+    }
+}
+
+/// Prélèvement effectué sur un compte en vertu d'un mandat.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Prelevement {
+    pub compte: String,
+    pub creancier: String,
+    pub reference: String,
+    pub montant: f32,
+    pub date: NaiveDate,
+    pub rembourse: bool,
+}
+
+impl Prelevement {
+    /// Dernier jour où le prélèvement peut être remboursé.
+    pub fn remboursable_jusqu_au(&self, delai_jours: u32) -> NaiveDate {
+        self.date + Days::new(delai_jours.into())
+    }
+}
+
+impl fmt::Display for Prelevement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            tr!(
+                "prelevements.prelevement",
+                self.date.format("%d/%m/%Y"),
+                self.creancier,
+                self.reference,
+                langue::montant(self.montant)
+            )
+        )?;
+        if self.rembourse {
+            write!(f, " ({})", langue::texte("prelevements.rembourse"))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(jour: u32, mois: u32, annee: i32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(annee, mois, jour).unwrap()
+    }
+
+    #[test]
+    fn validite_du_mandat() {
+        let mut mandat = Mandat {
+            compte: "12345".to_string(),
+            creancier: "FR12ZZZ123456".to_string(),
+            reference: "EDF-001".to_string(),
+            signe_le: date(1, 3, 2026),
+            montant_max: 150.0,
+            revoque_le: None,
+        };
+        assert!(!mandat.est_valide(date(28, 2, 2026)));
+        assert!(mandat.est_valide(date(1, 3, 2026)));
+        mandat.revoque_le = Some(date(10, 3, 2026));
+        assert!(mandat.est_valide(date(9, 3, 2026)));
+        assert!(!mandat.est_valide(date(10, 3, 2026)));
+    }
+}
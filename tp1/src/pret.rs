@@ -0,0 +1,321 @@
+//! Prêts amortissables et génération des tableaux d'amortissement.
+
+use crate::{langue, tr};
+use chrono::{Months, NaiveDate};
+use std::io::{self, Write};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ModeAmortissement {
+    AnnuiteConstante,
+    AmortissementConstant,
+}
+
+impl ModeAmortissement {
+    /// Code stable, indépendant de la langue.
+    pub fn code(&self) -> &'static str {
+        match self {
+            ModeAmortissement::AnnuiteConstante => "annuite",
+            ModeAmortissement::AmortissementConstant => "amortissement",
+        }
+    }
+
+    pub fn depuis_code(code: &str) -> Option<Self> {
+        [
+            ModeAmortissement::AnnuiteConstante,
+            ModeAmortissement::AmortissementConstant,
+        ]
+        .into_iter()
+        .find(|mode| mode.code() == code)
+    }
+
+    pub fn libelle(&self) -> &'static str {
+        match self {
+            ModeAmortissement::AnnuiteConstante => langue::texte("pret.annuite_constante"),
+            ModeAmortissement::AmortissementConstant => {
+                langue::texte("pret.amortissement_constant")
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Echeance {
+    pub numero: u32,
+    pub date: NaiveDate,
+    pub mensualite: f32,
+    pub interets: f32,
+    pub amortissement: f32,
+    pub capital_restant: f32,
+}
+
+#[derive(Debug)]
+pub struct Pret {
+    pub capital: f32,
+    pub taux_annuel: f32,
+    pub duree_mois: u32,
+    pub mode: ModeAmortissement,
+    pub compte_lie: String,
+    pub echeances: Vec<Echeance>,
+    /// Date de paiement de chaque échéance payée, dans l'ordre des échéances.
+    pub paiements: Vec<NaiveDate>,
+    pub capital_restant: f32,
+}
+
+fn arrondir(valeur: f64) -> f64 {
+    (valeur * 100.0).round() / 100.0
+}
+
+// Les calculs sont faits en f64 puis arrondis au centime pour éviter
+// que les erreurs d'arrondi de f32 ne s'accumulent sur des centaines d'échéances.
+pub fn generer_tableau(
+    capital: f32,
+    taux_annuel: f32,
+    duree_mois: u32,
+    mode: ModeAmortissement,
+    premiere_date: NaiveDate,
+    premier_numero: u32,
+) -> Vec<Echeance> {
+    let mut echeances = Vec::new();
+    if duree_mois == 0 || capital <= 0.0 {
+        return echeances;
+    }
+
+    let taux_mensuel = taux_annuel as f64 / 100.0 / 12.0;
+    let n = duree_mois as f64;
+    let mut restant = capital as f64;
+
+    let annuite = if taux_mensuel == 0.0 {
+        restant / n
+    } else {
+        restant * taux_mensuel / (1.0 - (1.0 + taux_mensuel).powf(-n))
+    };
+    let amortissement_fixe = restant / n;
+
+    for i in 0..duree_mois {
+        let interets = arrondir(restant * taux_mensuel);
+        let mut amortissement = match mode {
+            ModeAmortissement::AnnuiteConstante => arrondir(annuite) - interets,
+            ModeAmortissement::AmortissementConstant => arrondir(amortissement_fixe),
+        };
+        // La dernière échéance solde exactement le capital restant.
+        if i == duree_mois - 1 || amortissement > restant {
+            amortissement = restant;
+        }
+        restant = arrondir(restant - amortissement);
+
+        echeances.push(Echeance {
+            numero: premier_numero + i,
+            date: premiere_date + Months::new(i),
+            mensualite: arrondir(amortissement + interets) as f32,
+            interets: interets as f32,
+            amortissement: arrondir(amortissement) as f32,
+            capital_restant: restant as f32,
+        });
+    }
+
+    echeances
+}
+
+impl Pret {
+    pub fn new(
+        capital: f32,
+        taux_annuel: f32,
+        duree_mois: u32,
+        mode: ModeAmortissement,
+        compte_lie: String,
+        date_debut: NaiveDate,
+    ) -> Self {
+        let echeances = generer_tableau(
+            capital,
+            taux_annuel,
+            duree_mois,
+            mode,
+            date_debut + Months::new(1),
+            1,
+        );
+        Pret {
+            capital,
+            taux_annuel,
+            duree_mois,
+            mode,
+            compte_lie,
+            echeances,
+            paiements: Vec::new(),
+            capital_restant: capital,
+        }
+    }
+
+    pub fn echeances_payees(&self) -> usize {
+        self.paiements.len()
+    }
+
+    pub fn prochaine_echeance(&self) -> Option<&Echeance> {
+        self.echeances.get(self.echeances_payees())
+    }
+
+    /// Marque la prochaine échéance comme payée le `date`.
+    pub fn marquer_payee(&mut self, date: NaiveDate) {
+        if let Some(echeance) = self.prochaine_echeance() {
+            self.capital_restant = echeance.capital_restant;
+            self.paiements.push(date);
+        }
+    }
+
+    /// Échéances payées avec leur date de paiement.
+    pub fn echeances_reglees(&self) -> impl Iterator<Item = (&Echeance, NaiveDate)> {
+        self.echeances.iter().zip(self.paiements.iter().copied())
+    }
+
+    pub fn est_solde(&self) -> bool {
+        self.prochaine_echeance().is_none()
+    }
+
+    /// Réduit le capital restant et recalcule les échéances non payées
+    /// sur la durée restante. Retourne la nouvelle mensualité, s'il en reste une.
+    pub fn rembourser_par_anticipation(&mut self, montant: f32) -> Option<f32> {
+        let prochaine = self.prochaine_echeance()?.clone();
+        let restant = (self.capital_restant - montant).max(0.0);
+        let duree_restante = (self.echeances.len() - self.echeances_payees()) as u32;
+
+        self.echeances.truncate(self.echeances_payees());
+        self.capital_restant = restant;
+        let nouvelles = generer_tableau(
+            restant,
+            self.taux_annuel,
+            duree_restante,
+            self.mode,
+            prochaine.date,
+            prochaine.numero,
+        );
+
+        if nouvelles.is_empty() {
+            return None;
+        }
+
+        let mensualite = nouvelles[0].mensualite;
+        self.echeances.extend(nouvelles);
+        Some(mensualite)
+    }
+
+    pub fn afficher_tableau(&self, sortie: &mut impl Write) -> io::Result<()> {
+        writeln!(
+            sortie,
+            "{}",
+            tr!(
+                "pret.description",
+                langue::montant(self.capital),
+                langue::nombre(self.taux_annuel),
+                self.duree_mois,
+                self.mode.libelle(),
+                self.compte_lie
+            )
+        )?;
+        writeln!(
+            sortie,
+            "{:>4} | {:>10} | {:>10} | {:>10} | {:>12} | {:>12} |",
+            tr!("pret.numero"),
+            tr!("pret.date"),
+            tr!("pret.mensualite"),
+            tr!("pret.interets"),
+            tr!("pret.amortissement"),
+            tr!("pret.capital_du")
+        )?;
+        for (i, e) in self.echeances.iter().enumerate() {
+            let statut = if i < self.echeances_payees() {
+                tr!("pret.payee")
+            } else {
+                String::new()
+            };
+            writeln!(
+                sortie,
+                "{:>4} | {} | {:>10} | {:>10} | {:>12} | {:>12} | {}",
+                e.numero,
+                e.date.format("%d/%m/%Y"),
+                langue::nombre(e.mensualite),
+                langue::nombre(e.interets),
+                langue::nombre(e.amortissement),
+                langue::nombre(e.capital_restant),
+                statut
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(a: i32, m: u32, j: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(a, m, j).unwrap()
+    }
+
+    #[test]
+    fn annuite_constante_rembourse_tout_le_capital() {
+        let tableau = generer_tableau(
+            10_000.0,
+            3.6,
+            12,
+            ModeAmortissement::AnnuiteConstante,
+            date(2025, 1, 31),
+            1,
+        );
+        assert_eq!(tableau.len(), 12);
+        assert_eq!(tableau[0].interets, 30.0);
+        assert_eq!(tableau[0].mensualite, 849.67);
+        assert!(tableau[..11].iter().all(|e| e.mensualite == 849.67));
+        assert_eq!(tableau[11].capital_restant, 0.0);
+        assert_eq!(tableau[1].date, date(2025, 2, 28));
+
+        let amorti: f32 = tableau.iter().map(|e| e.amortissement).sum();
+        assert!((amorti - 10_000.0).abs() < 0.05);
+    }
+
+    #[test]
+    fn amortissement_constant_a_des_mensualites_decroissantes() {
+        let tableau = generer_tableau(
+            1_200.0,
+            12.0,
+            12,
+            ModeAmortissement::AmortissementConstant,
+            date(2025, 1, 1),
+            1,
+        );
+        assert!(tableau.iter().all(|e| e.amortissement == 100.0));
+        assert_eq!(tableau[0].mensualite, 112.0);
+        assert_eq!(tableau[11].mensualite, 101.0);
+        assert!(
+            tableau
+                .windows(2)
+                .all(|w| w[0].mensualite > w[1].mensualite)
+        );
+    }
+
+    #[test]
+    fn remboursement_anticipe_conserve_la_duree_restante() {
+        let mut pret = Pret::new(
+            1_200.0,
+            0.0,
+            12,
+            ModeAmortissement::AnnuiteConstante,
+            "12345".to_string(),
+            date(2025, 1, 1),
+        );
+        pret.marquer_payee(date(2025, 2, 3));
+        assert_eq!(pret.capital_restant, 1_100.0);
+        assert_eq!(
+            pret.echeances_reglees()
+                .map(|(e, paye_le)| (e.numero, paye_le))
+                .collect::<Vec<_>>(),
+            [(1, date(2025, 2, 3))]
+        );
+
+        assert_eq!(pret.rembourser_par_anticipation(550.0), Some(50.0));
+        assert_eq!(pret.echeances.len(), 12);
+        assert_eq!(pret.prochaine_echeance().unwrap().numero, 2);
+        assert_eq!(pret.capital_restant, 550.0);
+
+        assert_eq!(pret.rembourser_par_anticipation(550.0), None);
+        assert!(pret.est_solde());
+    }
+}
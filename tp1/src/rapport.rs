@@ -0,0 +1,232 @@
+//! Rapports mensuels des revenus et dépenses par catégorie.
+
+use crate::transaction::{Libelle, Transaction};
+use crate::{langue, tr};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::Path;
+
+/// Catégorie des transactions qui n'en ont pas.
+pub fn sans_categorie() -> String {
+    tr!("categorie.aucune")
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct LigneRapport {
+    pub mois: String,
+    pub categorie: String,
+    pub revenus: f32,
+    pub depenses: f32,
+}
+
+impl LigneRapport {
+    pub fn net(&self) -> f32 {
+        self.revenus - self.depenses
+    }
+}
+
+/// Regroupe les transactions par mois (AAAA-MM) puis par catégorie,
+/// triées chronologiquement puis par nom de catégorie.
+pub fn generer_rapport<'a>(
+    transactions: impl Iterator<Item = &'a Transaction>,
+) -> Vec<LigneRapport> {
+    let mut groupes: BTreeMap<(String, String), LigneRapport> = BTreeMap::new();
+
+    for t in transactions {
+        let mois = t.date.format("%Y-%m").to_string();
+        let categorie = t
+            .categorie
+            .as_ref()
+            .map_or_else(sans_categorie, Libelle::to_string);
+
+        let ligne = groupes
+            .entry((mois.clone(), categorie.clone()))
+            .or_insert_with(|| LigneRapport {
+                mois,
+                categorie,
+                ..Default::default()
+            });
+        if t.montant >= 0.0 {
+            ligne.revenus += t.montant;
+        } else {
+            ligne.depenses -= t.montant;
+        }
+    }
+
+    groupes.into_values().collect()
+}
+
+pub fn afficher_rapport(lignes: &[LigneRapport], sortie: &mut impl Write) -> io::Result<()> {
+    if lignes.is_empty() {
+        return writeln!(sortie, "{}", tr!("rapport.vide"));
+    }
+
+    writeln!(
+        sortie,
+        "{:<7} | {:<20} | {:>10} | {:>10} | {:>10}",
+        tr!("rapport.mois"),
+        tr!("rapport.categorie"),
+        tr!("rapport.revenus"),
+        tr!("rapport.depenses"),
+        tr!("rapport.net")
+    )?;
+
+    let mut mois_courant: Option<&str> = None;
+    let (mut revenus_mois, mut depenses_mois) = (0.0, 0.0);
+    let (mut revenus_total, mut depenses_total) = (0.0, 0.0);
+
+    for ligne in lignes {
+        if mois_courant.is_some_and(|mois| mois != ligne.mois) {
+            afficher_sous_total(revenus_mois, depenses_mois, sortie)?;
+            revenus_mois = 0.0;
+            depenses_mois = 0.0;
+        }
+        mois_courant = Some(&ligne.mois);
+
+        afficher_ligne(
+            &ligne.mois,
+            &ligne.categorie,
+            ligne.revenus,
+            ligne.depenses,
+            sortie,
+        )?;
+        revenus_mois += ligne.revenus;
+        depenses_mois += ligne.depenses;
+        revenus_total += ligne.revenus;
+        depenses_total += ligne.depenses;
+    }
+    afficher_sous_total(revenus_mois, depenses_mois, sortie)?;
+
+    afficher_ligne(
+        &tr!("rapport.total"),
+        "",
+        revenus_total,
+        depenses_total,
+        sortie,
+    )
+}
+
+fn afficher_sous_total(revenus: f32, depenses: f32, sortie: &mut impl Write) -> io::Result<()> {
+    afficher_ligne("", &tr!("rapport.sous_total"), revenus, depenses, sortie)
+}
+
+fn afficher_ligne(
+    mois: &str,
+    categorie: &str,
+    revenus: f32,
+    depenses: f32,
+    sortie: &mut impl Write,
+) -> io::Result<()> {
+    writeln!(
+        sortie,
+        "{:<7} | {:<20} | {:>10} | {:>10} | {:>10}",
+        mois,
+        categorie,
+        langue::nombre(revenus),
+        langue::nombre(depenses),
+        langue::nombre(revenus - depenses)
+    )
+}
+
+pub(crate) fn champ_csv(valeur: &str) -> String {
+    if valeur.contains([',', '"', '\n']) {
+        format!("\"{}\"", valeur.replace('"', "\"\""))
+    } else {
+        valeur.to_string()
+    }
+}
+
+pub fn exporter_csv(lignes: &[LigneRapport], chemin: &Path) -> io::Result<()> {
+    let mut fichier = File::create(chemin)?;
+    writeln!(fichier, "mois,categorie,revenus,depenses,net")?;
+    for ligne in lignes {
+        writeln!(
+            fichier,
+            "{},{},{:.2},{:.2},{:.2}",
+            ligne.mois,
+            champ_csv(&ligne.categorie),
+            ligne.revenus,
+            ligne.depenses,
+            ligne.net()
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn transaction(mois: u32, montant: f32, categorie: Option<&str>) -> Transaction {
+        let date = NaiveDate::from_ymd_opt(2025, mois, 10)
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap();
+        let mut t = Transaction::new(date, "libellé".into(), montant);
+        t.categorie = categorie.map(Libelle::from);
+        t
+    }
+
+    #[test]
+    fn regroupe_par_mois_et_categorie() {
+        let transactions = [
+            transaction(2, -20.0, Some("Alimentation")),
+            transaction(1, 1000.0, Some("Revenus")),
+            transaction(1, -30.0, Some("Alimentation")),
+            transaction(1, -15.0, Some("Alimentation")),
+            transaction(1, -5.0, None),
+        ];
+        let lignes = generer_rapport(transactions.iter());
+
+        let cles: Vec<(&str, &str)> = lignes
+            .iter()
+            .map(|l| (l.mois.as_str(), l.categorie.as_str()))
+            .collect();
+        assert_eq!(
+            cles,
+            [
+                ("2025-01", "Alimentation"),
+                ("2025-01", sans_categorie().as_str()),
+                ("2025-01", "Revenus"),
+                ("2025-02", "Alimentation"),
+            ]
+        );
+        assert_eq!(lignes[0].depenses, 45.0);
+        assert_eq!(lignes[2].revenus, 1000.0);
+        assert_eq!(lignes[2].net(), 1000.0);
+    }
+
+    #[test]
+    fn export_csv_puis_dossier_absent() {
+        let lignes = generer_rapport([transaction(3, -12.5, Some("Sorties, cinéma"))].iter());
+        let chemin = std::env::temp_dir().join(format!("tp1-rapport-{}.csv", std::process::id()));
+        exporter_csv(&lignes, &chemin).unwrap();
+        let csv = std::fs::read_to_string(&chemin).unwrap();
+        std::fs::remove_file(&chemin).unwrap();
+        assert_eq!(
+            csv,
+            "mois,categorie,revenus,depenses,net\n2025-03,\"Sorties, cinéma\",0.00,12.50,-12.50\n"
+        );
+
+        let absent = std::env::temp_dir()
+            .join("tp1-dossier-absent")
+            .join("rapport.csv");
+        assert!(exporter_csv(&lignes, &absent).is_err());
+
+        let mut sortie = Vec::new();
+        afficher_rapport(&[], &mut sortie).unwrap();
+        assert_eq!(
+            String::from_utf8(sortie).unwrap(),
+            tr!("rapport.vide") + "\n"
+        );
+    }
+
+    #[test]
+    fn champ_csv_echappe_les_virgules_et_guillemets() {
+        assert_eq!(champ_csv("Loisirs"), "Loisirs");
+        assert_eq!(champ_csv("Sorties, cinéma"), "\"Sorties, cinéma\"");
+        assert_eq!(champ_csv("dit \"x\""), "\"dit \"\"x\"\"\"");
+    }
+}
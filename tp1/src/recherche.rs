@@ -0,0 +1,214 @@
+//! Tri, filtrage et pagination de la liste des comptes.
+
+use crate::compte::{CompteBancaire, TypeCompte};
+use crate::langue;
+use std::cmp::Ordering;
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum TriComptes {
+    #[default]
+    Numero,
+    Titulaire,
+    Solde,
+}
+
+impl TriComptes {
+    pub fn libelle(&self) -> &'static str {
+        match self {
+            TriComptes::Numero => langue::texte("tri.numero"),
+            TriComptes::Titulaire => langue::texte("tri.titulaire"),
+            TriComptes::Solde => langue::texte("tri.solde"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EtatCompte {
+    Courant,
+    PretEnCours,
+    PretSolde,
+}
+
+impl EtatCompte {
+    pub fn de(compte: &CompteBancaire) -> Self {
+        match compte.type_compte() {
+            TypeCompte::Courant => EtatCompte::Courant,
+            TypeCompte::Pret(pret) if pret.est_solde() => EtatCompte::PretSolde,
+            TypeCompte::Pret(_) => EtatCompte::PretEnCours,
+        }
+    }
+
+    pub fn libelle(&self) -> &'static str {
+        match self {
+            EtatCompte::Courant => langue::texte("etat.courant"),
+            EtatCompte::PretEnCours => langue::texte("etat.pret_en_cours"),
+            EtatCompte::PretSolde => langue::texte("etat.pret_solde"),
+        }
+    }
+}
+
+/// Critères de filtrage ; un critère absent laisse passer tous les comptes.
+#[derive(Debug, Clone, Default)]
+pub struct FiltreComptes {
+    pub titulaire: Option<String>,
+    pub solde_min: Option<f32>,
+    pub solde_max: Option<f32>,
+    pub etat: Option<EtatCompte>,
+}
+
+impl FiltreComptes {
+    pub fn accepte(&self, compte: &CompteBancaire) -> bool {
+        let titulaire = self.titulaire.as_ref().is_none_or(|motif| {
+            compte
+                .titulaire()
+                .to_lowercase()
+                .contains(&motif.to_lowercase())
+        });
+        titulaire
+            && self.solde_min.is_none_or(|min| compte.solde() >= min)
+            && self.solde_max.is_none_or(|max| compte.solde() <= max)
+            && self.etat.is_none_or(|etat| EtatCompte::de(compte) == etat)
+    }
+
+    pub fn est_vide(&self) -> bool {
+        self.titulaire.is_none()
+            && self.solde_min.is_none()
+            && self.solde_max.is_none()
+            && self.etat.is_none()
+    }
+}
+
+/// Filtre puis trie les comptes. Les égalités sont départagées par numéro
+/// pour que l'ordre soit le même d'une exécution à l'autre.
+pub fn rechercher<'a>(
+    comptes: impl Iterator<Item = &'a CompteBancaire>,
+    filtre: &FiltreComptes,
+    tri: TriComptes,
+    decroissant: bool,
+) -> Vec<&'a CompteBancaire> {
+    let mut resultat: Vec<&CompteBancaire> = comptes.filter(|c| filtre.accepte(c)).collect();
+    resultat.sort_by(|a, b| {
+        let ordre = match tri {
+            TriComptes::Numero => Ordering::Equal,
+            TriComptes::Titulaire => a
+                .titulaire()
+                .to_lowercase()
+                .cmp(&b.titulaire().to_lowercase()),
+            TriComptes::Solde => a.solde().total_cmp(&b.solde()),
+        };
+        let ordre = ordre.then_with(|| a.numero().cmp(b.numero()));
+        if decroissant { ordre.reverse() } else { ordre }
+    });
+    resultat
+}
+
+/// Nombre de pages nécessaires pour `total` éléments (au moins une).
+pub fn nombre_pages(total: usize, taille_page: usize) -> usize {
+    total.div_ceil(taille_page.max(1)).max(1)
+}
+
+/// Éléments de la page `page` (numérotée à partir de 1) ; vide au-delà de la dernière.
+pub fn page<T>(elements: &[T], page: usize, taille_page: usize) -> &[T] {
+    let taille_page = taille_page.max(1);
+    let debut = page.saturating_sub(1).saturating_mul(taille_page);
+    if debut >= elements.len() {
+        return &[];
+    }
+    &elements[debut..(debut + taille_page).min(elements.len())]
+}
+
+/// Interprète une saisie comme un numéro de compte de la liste, sinon comme
+/// sa position (à partir de 1). Le numéro de compte est prioritaire.
+pub fn selectionner<'a>(
+    comptes: &[&'a CompteBancaire],
+    saisie: &str,
+) -> Option<&'a CompteBancaire> {
+    let saisie = saisie.trim();
+    if let Some(compte) = comptes.iter().find(|c| c.numero() == saisie) {
+        return Some(compte);
+    }
+    saisie
+        .parse::<usize>()
+        .ok()
+        .and_then(|index| index.checked_sub(1))
+        .and_then(|index| comptes.get(index).copied())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn comptes() -> Vec<CompteBancaire> {
+        [
+            ("300", "Claire Bernard", 800.0),
+            ("100", "alice Dupont", 1500.0),
+            ("200", "Bob Martin", 800.0),
+        ]
+        .into_iter()
+        .map(|(numero, titulaire, solde)| {
+            CompteBancaire::new(numero.to_string(), titulaire.to_string(), solde)
+        })
+        .collect()
+    }
+
+    fn numeros(comptes: &[&CompteBancaire]) -> Vec<String> {
+        comptes.iter().map(|c| c.numero().to_string()).collect()
+    }
+
+    #[test]
+    fn tri_deterministe_avec_departage_par_numero() {
+        let comptes = comptes();
+        let filtre = FiltreComptes::default();
+
+        let par_numero = rechercher(comptes.iter(), &filtre, TriComptes::Numero, false);
+        assert_eq!(numeros(&par_numero), ["100", "200", "300"]);
+
+        let par_titulaire = rechercher(comptes.iter(), &filtre, TriComptes::Titulaire, false);
+        assert_eq!(numeros(&par_titulaire), ["100", "200", "300"]);
+
+        let par_solde = rechercher(comptes.iter(), &filtre, TriComptes::Solde, false);
+        assert_eq!(numeros(&par_solde), ["200", "300", "100"]);
+
+        let decroissant = rechercher(comptes.iter(), &filtre, TriComptes::Solde, true);
+        assert_eq!(numeros(&decroissant), ["100", "300", "200"]);
+    }
+
+    #[test]
+    fn filtres_combines() {
+        let comptes = comptes();
+        let filtre = FiltreComptes {
+            titulaire: Some("AR".to_string()),
+            solde_max: Some(1000.0),
+            ..Default::default()
+        };
+        let resultat = rechercher(comptes.iter(), &filtre, TriComptes::Numero, false);
+        assert_eq!(numeros(&resultat), ["200", "300"]);
+
+        let filtre = FiltreComptes {
+            etat: Some(EtatCompte::PretEnCours),
+            ..Default::default()
+        };
+        assert!(rechercher(comptes.iter(), &filtre, TriComptes::Numero, false).is_empty());
+    }
+
+    #[test]
+    fn pagination_et_selection() {
+        let elements = [1, 2, 3, 4, 5];
+        assert_eq!(nombre_pages(elements.len(), 2), 3);
+        assert_eq!(nombre_pages(0, 2), 1);
+        assert_eq!(page(&elements, 3, 2), [5]);
+        assert!(page(&elements, 4, 2).is_empty());
+
+        let comptes = comptes();
+        let liste = rechercher(
+            comptes.iter(),
+            &FiltreComptes::default(),
+            TriComptes::Numero,
+            false,
+        );
+        assert_eq!(selectionner(&liste, "2").unwrap().numero(), "200");
+        assert_eq!(selectionner(&liste, "300").unwrap().numero(), "300");
+        assert!(selectionner(&liste, "4").is_none());
+        assert!(selectionner(&liste, "0").is_none());
+    }
+}
@@ -0,0 +1,379 @@
+//! Relevé de compte imprimable : en-tête du titulaire et du compte, tableau des
+//! mouvements de la période, soldes d'ouverture et de clôture et totaux, mis en
+//! page en HTML autonome d'après un modèle.
+//!
+//! Le modèle par défaut est `modeles/releve.html` ; un modèle personnalisé
+//! utilise les mêmes `{{champs}}`, décrits en tête de ce fichier.
+
+use crate::compte::{CompteBancaire, TypeCompte};
+use crate::erreur::ErreurBancaire;
+use crate::transaction::Libelle;
+use crate::{langue, rapport, tr};
+use chrono::{Local, NaiveDate, NaiveDateTime};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+const MODELE_PAR_DEFAUT: &str = include_str!("../modeles/releve.html");
+
+const DEBUT_MOUVEMENTS: &str = "{{#mouvements}}";
+const FIN_MOUVEMENTS: &str = "{{/mouvements}}";
+
+/// Champs du relevé, utilisables partout dans le modèle.
+const CHAMPS: [&str; 12] = [
+    "langue",
+    "titulaire",
+    "numero",
+    "type_compte",
+    "du",
+    "au",
+    "edite_le",
+    "solde_ouverture",
+    "solde_cloture",
+    "total_credits",
+    "total_debits",
+    "nombre_mouvements",
+];
+
+/// Champs d'un mouvement, utilisables dans le bloc des mouvements.
+const CHAMPS_MOUVEMENT: [&str; 6] = ["date", "libelle", "categorie", "debit", "credit", "solde"];
+
+/// Intitulés remplacés par leur texte dans la langue courante.
+const INTITULES: [&str; 14] = [
+    "releve.titre",
+    "releve.titulaire",
+    "releve.compte",
+    "releve.periode",
+    "releve.edite_le",
+    "releve.date",
+    "releve.libelle",
+    "releve.categorie",
+    "releve.debit",
+    "releve.credit",
+    "releve.solde",
+    "releve.solde_ouverture",
+    "releve.solde_cloture",
+    "releve.totaux",
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MouvementReleve {
+    pub date: NaiveDateTime,
+    pub libelle: String,
+    pub categorie: Option<String>,
+    pub montant: f32,
+    /// Solde du compte après ce mouvement.
+    pub solde: f32,
+}
+
+/// Relevé d'un compte du `du` au `au` inclus.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Releve {
+    pub numero: String,
+    pub titulaire: String,
+    pub pret: bool,
+    pub du: NaiveDate,
+    pub au: NaiveDate,
+    pub edite_le: NaiveDate,
+    /// Solde à la veille de `du`.
+    pub solde_ouverture: f32,
+    pub solde_cloture: f32,
+    pub mouvements: Vec<MouvementReleve>,
+}
+
+/// Établit le relevé de `compte` sur la période, à partir de son historique.
+pub fn releve(compte: &CompteBancaire, du: NaiveDate, au: NaiveDate) -> Releve {
+    let solde_ouverture = compte
+        .historique()
+        .iter()
+        .filter(|t| t.date.date() < du)
+        .fold(0.0, |solde, t| solde + t.montant);
+    let mut solde = solde_ouverture;
+    let mouvements = compte
+        .historique()
+        .iter()
+        .filter(|t| (du..=au).contains(&t.date.date()))
+        .map(|t| {
+            solde += t.montant;
+            MouvementReleve {
+                date: t.date,
+                libelle: t.libelle.to_string(),
+                categorie: t.categorie.as_ref().map(Libelle::to_string),
+                montant: t.montant,
+                solde,
+            }
+        })
+        .collect();
+
+    Releve {
+        numero: compte.numero().to_string(),
+        titulaire: compte.titulaire().to_string(),
+        pret: matches!(compte.type_compte(), TypeCompte::Pret(_)),
+        du,
+        au,
+        edite_le: Local::now().date_naive(),
+        solde_ouverture,
+        solde_cloture: solde,
+        mouvements,
+    }
+}
+
+impl Releve {
+    pub fn total_credits(&self) -> f32 {
+        self.mouvements
+            .iter()
+            .filter(|m| m.montant > 0.0)
+            .fold(0.0, |total, m| total + m.montant)
+    }
+
+    /// Total des débits, en valeur positive.
+    pub fn total_debits(&self) -> f32 {
+        self.mouvements
+            .iter()
+            .filter(|m| m.montant < 0.0)
+            .fold(0.0, |total, m| total - m.montant)
+    }
+
+    fn champ(&self, nom: &str) -> Option<String> {
+        let valeur = match nom {
+            "langue" => match langue::courante() {
+                langue::Langue::Francais => "fr".to_string(),
+                langue::Langue::Anglais => "en".to_string(),
+            },
+            "titulaire" => self.titulaire.clone(),
+            "numero" => self.numero.clone(),
+            "type_compte" if self.pret => tr!("releve.pret"),
+            "type_compte" => tr!("releve.courant"),
+            "du" => self.du.format("%d/%m/%Y").to_string(),
+            "au" => self.au.format("%d/%m/%Y").to_string(),
+            "edite_le" => self.edite_le.format("%d/%m/%Y").to_string(),
+            "solde_ouverture" => langue::montant(self.solde_ouverture),
+            "solde_cloture" => langue::montant(self.solde_cloture),
+            "total_credits" => langue::montant(self.total_credits()),
+            "total_debits" => langue::montant(self.total_debits()),
+            "nombre_mouvements" => self.mouvements.len().to_string(),
+            _ => return intitule(nom),
+        };
+        Some(valeur)
+    }
+
+    /// Écrit le relevé mis en page par `modele` dans un fichier HTML.
+    pub fn exporter_html(&self, modele: &ModeleReleve, chemin: &Path) -> io::Result<()> {
+        fs::write(chemin, modele.rendre(self))
+    }
+}
+
+impl MouvementReleve {
+    fn champ(&self, nom: &str) -> Option<String> {
+        let montant_si = |garder: bool| {
+            if garder {
+                langue::montant(self.montant.abs())
+            } else {
+                String::new()
+            }
+        };
+        let valeur = match nom {
+            "date" => self.date.format("%d/%m/%Y").to_string(),
+            "libelle" => self.libelle.clone(),
+            "categorie" => self
+                .categorie
+                .clone()
+                .unwrap_or_else(rapport::sans_categorie),
+            "debit" => montant_si(self.montant < 0.0),
+            "credit" => montant_si(self.montant > 0.0),
+            "solde" => langue::montant(self.solde),
+            _ => return None,
+        };
+        Some(valeur)
+    }
+}
+
+fn intitule(nom: &str) -> Option<String> {
+    INTITULES
+        .into_iter()
+        .find(|cle| *cle == nom)
+        .map(|cle| tr!(cle))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Morceau {
+    Texte(String),
+    Champ(String),
+}
+
+/// Modèle HTML du relevé, découpé en morceaux : avant les mouvements, une ligne
+/// répétée pour chaque mouvement, après les mouvements.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModeleReleve {
+    avant: Vec<Morceau>,
+    mouvement: Vec<Morceau>,
+    apres: Vec<Morceau>,
+}
+
+fn invalide(detail: String) -> ErreurBancaire {
+    ErreurBancaire::Fichier(tr!("releve.modele_invalide", detail))
+}
+
+/// Sépare le texte des `{{champs}}` ; un champ que `connu` refuse est une erreur.
+fn decouper(modele: &str, connu: impl Fn(&str) -> bool) -> Result<Vec<Morceau>, ErreurBancaire> {
+    let mut morceaux = Vec::new();
+    let mut reste = modele;
+    while let Some(debut) = reste.find("{{") {
+        morceaux.push(Morceau::Texte(reste[..debut].to_string()));
+        let fin = reste[debut..]
+            .find("}}")
+            .ok_or_else(|| invalide(tr!("releve.accolades_non_fermees")))?;
+        let nom = reste[debut + 2..debut + fin].trim();
+        if !connu(nom) {
+            return Err(invalide(tr!("releve.champ_inconnu", nom)));
+        }
+        morceaux.push(Morceau::Champ(nom.to_string()));
+        reste = &reste[debut + fin + 2..];
+    }
+    morceaux.push(Morceau::Texte(reste.to_string()));
+    Ok(morceaux)
+}
+
+// Un marqueur de bloc seul sur sa ligne ne laisse pas de ligne vide.
+fn sans_saut(texte: &str) -> &str {
+    texte
+        .strip_prefix("\r\n")
+        .or_else(|| texte.strip_prefix('\n'))
+        .unwrap_or(texte)
+}
+
+fn echapper(texte: &str) -> String {
+    let mut echappe = String::with_capacity(texte.len());
+    for c in texte.chars() {
+        match c {
+            '&' => echappe.push_str("&amp;"),
+            '<' => echappe.push_str("&lt;"),
+            '>' => echappe.push_str("&gt;"),
+            '"' => echappe.push_str("&quot;"),
+            '\'' => echappe.push_str("&#39;"),
+            _ => echappe.push(c),
+        }
+    }
+    echappe
+}
+
+fn remplir(morceaux: &[Morceau], valeur: impl Fn(&str) -> Option<String>, html: &mut String) {
+    for morceau in morceaux {
+        match morceau {
+            Morceau::Texte(texte) => html.push_str(texte),
+            Morceau::Champ(nom) => html.push_str(&echapper(&valeur(nom).unwrap_or_default())),
+        }
+    }
+}
+
+impl ModeleReleve {
+    /// Lit un modèle ; un champ inconnu ou un bloc des mouvements incomplet est
+    /// signalé. Le bloc des mouvements est facultatif.
+    pub fn lire(contenu: &str) -> Result<Self, ErreurBancaire> {
+        let champ_releve = |nom: &str| CHAMPS.contains(&nom) || INTITULES.contains(&nom);
+        let champ_mouvement = |nom: &str| champ_releve(nom) || CHAMPS_MOUVEMENT.contains(&nom);
+
+        let (avant, mouvement, apres) = match contenu.split_once(DEBUT_MOUVEMENTS) {
+            Some((avant, suite)) => {
+                let (mouvement, apres) = suite
+                    .split_once(FIN_MOUVEMENTS)
+                    .ok_or_else(|| invalide(tr!("releve.bloc_non_ferme", FIN_MOUVEMENTS)))?;
+                (avant, sans_saut(mouvement), sans_saut(apres))
+            }
+            None => (contenu, "", ""),
+        };
+        Ok(ModeleReleve {
+            avant: decouper(avant, champ_releve)?,
+            mouvement: decouper(mouvement, champ_mouvement)?,
+            apres: decouper(apres, champ_releve)?,
+        })
+    }
+
+    pub fn par_defaut() -> Self {
+        ModeleReleve::lire(MODELE_PAR_DEFAUT).expect("le modèle de relevé par défaut est valide")
+    }
+
+    /// Modèle lu dans `chemin`, ou le modèle par défaut si ce fichier n'existe pas.
+    pub fn charger(chemin: &Path) -> Result<Self, ErreurBancaire> {
+        match fs::read_to_string(chemin) {
+            Ok(contenu) => ModeleReleve::lire(&contenu),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(ModeleReleve::par_defaut()),
+            Err(e) => Err(ErreurBancaire::Fichier(e.to_string())),
+        }
+    }
+
+    /// Page HTML du relevé ; les valeurs y sont échappées.
+    pub fn rendre(&self, releve: &Releve) -> String {
+        let mut html = String::new();
+        remplir(&self.avant, |nom| releve.champ(nom), &mut html);
+        for mouvement in &releve.mouvements {
+            remplir(
+                &self.mouvement,
+                |nom| mouvement.champ(nom).or_else(|| releve.champ(nom)),
+                &mut html,
+            );
+        }
+        remplir(&self.apres, |nom| releve.champ(nom), &mut html);
+        html
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transaction::Transaction;
+
+    fn date(texte: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(texte, "%d/%m/%Y").unwrap()
+    }
+
+    #[test]
+    fn releve_de_la_periode_et_modele() {
+        let mut compte = CompteBancaire::ouvert_le(
+            "12345".to_string(),
+            "Alice <Dupont>".to_string(),
+            100.0,
+            date("02/01/2026").and_hms_opt(9, 0, 0).unwrap(),
+        );
+        for (jour, montant, libelle) in [
+            ("15/01/2026", 50.0, "Salaire"),
+            ("03/02/2026", -20.0, "Courses & co"),
+            ("10/02/2026", 30.0, "Remboursement"),
+            ("01/03/2026", -5.0, "Frais"),
+        ] {
+            compte.solde += montant;
+            compte.historique.push(Transaction::new(
+                date(jour).and_hms_opt(12, 0, 0).unwrap(),
+                libelle.into(),
+                montant,
+            ));
+        }
+
+        let releve = releve(&compte, date("01/02/2026"), date("28/02/2026"));
+        assert_eq!(releve.solde_ouverture, 150.0);
+        assert_eq!(releve.solde_cloture, 160.0);
+        assert_eq!(releve.mouvements.len(), 2);
+        assert_eq!(releve.mouvements[0].solde, 130.0);
+        assert_eq!(releve.total_credits(), 30.0);
+        assert_eq!(releve.total_debits(), 20.0);
+
+        let html = ModeleReleve::par_defaut().rendre(&releve);
+        assert!(html.contains("Alice &lt;Dupont&gt;"));
+        assert!(html.contains("<td>Courses &amp; co</td>"));
+        assert!(html.contains("Solde de clôture"));
+        assert!(html.contains("160,00€"));
+        assert!(!html.contains("Salaire"));
+
+        let modele = ModeleReleve::lire(
+            "{{numero}}:{{#mouvements}}[{{ libelle }} {{debit}}|{{credit}}]{{/mouvements}}{{nombre_mouvements}}",
+        )
+        .unwrap();
+        assert_eq!(
+            modele.rendre(&releve),
+            "12345:[Courses &amp; co 20,00€|][Remboursement |30,00€]2"
+        );
+        assert!(ModeleReleve::lire("{{libelle}}").is_err());
+        assert!(ModeleReleve::lire("{{#mouvements}}{{date}}").is_err());
+        assert!(ModeleReleve::lire("{{numero").is_err());
+    }
+}
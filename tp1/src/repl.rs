@@ -0,0 +1,449 @@
+//! Invite de commandes du guichet (`--repl`) : `depot 12345 50`, `solde 67890`…
+//! La ligne s'édite comme dans un shell, l'historique est conservé d'une session
+//! à l'autre et la touche Tab complète les commandes et les numéros de compte.
+
+use crate::console::Console;
+use chrono::Local;
+use rustyline::completion::Completer;
+use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::history::DefaultHistory;
+use rustyline::validate::Validator;
+use rustyline::{Context, Editor, Helper};
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::path::Path;
+use tp1::{GestionnaireComptes, langue, tr};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Action {
+    Solde,
+    Depot,
+    Retrait,
+    Virement,
+    Historique,
+    Comptes,
+    Aide,
+    Quitter,
+}
+
+// Noms de chaque commande en français et en anglais ; les deux sont toujours
+// acceptés, seuls ceux de la langue courante sont proposés à la complétion.
+const COMMANDES: [(Action, &str, &str); 8] = [
+    (Action::Solde, "solde", "show"),
+    (Action::Depot, "depot", "deposit"),
+    (Action::Retrait, "retrait", "withdraw"),
+    (Action::Virement, "virement", "transfer"),
+    (Action::Historique, "historique", "history"),
+    (Action::Comptes, "comptes", "accounts"),
+    (Action::Aide, "aide", "help"),
+    (Action::Quitter, "quitter", "quit"),
+];
+
+impl Action {
+    fn depuis_nom(nom: &str) -> Option<Action> {
+        let nom = nom.to_lowercase();
+        COMMANDES
+            .iter()
+            .find(|(_, fr, en)| *fr == nom || *en == nom)
+            .map(|(action, _, _)| *action)
+    }
+
+    fn nom(&self) -> &'static str {
+        let (_, fr, en) = COMMANDES
+            .iter()
+            .find(|(action, _, _)| action == self)
+            .unwrap();
+        match langue::courante() {
+            langue::Langue::Francais => fr,
+            langue::Langue::Anglais => en,
+        }
+    }
+
+    /// Nombre de numéros de compte en tête des arguments.
+    fn comptes(&self) -> usize {
+        match self {
+            Action::Virement => 2,
+            Action::Solde | Action::Depot | Action::Retrait | Action::Historique => 1,
+            Action::Comptes | Action::Aide | Action::Quitter => 0,
+        }
+    }
+
+    fn usage(&self) -> &'static str {
+        match self {
+            Action::Solde => langue::texte("repl.usage_solde"),
+            Action::Depot => langue::texte("repl.usage_depot"),
+            Action::Retrait => langue::texte("repl.usage_retrait"),
+            Action::Virement => langue::texte("repl.usage_virement"),
+            Action::Historique => langue::texte("repl.usage_historique"),
+            Action::Comptes => langue::texte("repl.usage_comptes"),
+            Action::Aide => langue::texte("repl.usage_aide"),
+            Action::Quitter => langue::texte("repl.usage_quitter"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Commande {
+    Solde(String),
+    Depot {
+        numero: String,
+        montant: f32,
+        libelle: Option<String>,
+    },
+    Retrait {
+        numero: String,
+        montant: f32,
+        libelle: Option<String>,
+    },
+    Virement {
+        source: String,
+        destination: String,
+        montant: f32,
+        libelle: Option<String>,
+    },
+    Historique(String),
+    Comptes,
+    Aide,
+    Quitter,
+}
+
+/// Lit une ligne de commande ; `None` pour une ligne vide. Le libellé facultatif
+/// d'une opération est le reste de la ligne après le montant.
+fn analyser(ligne: &str) -> Option<Result<Commande, String>> {
+    let mots: Vec<&str> = ligne.split_whitespace().collect();
+    let (&nom, arguments) = mots.split_first()?;
+    let Some(action) = Action::depuis_nom(nom) else {
+        return Some(Err(tr!("repl.commande_inconnue", nom)));
+    };
+    let usage = || tr!("repl.usage", action.usage());
+    let montant =
+        |texte: &str| langue::lire_nombre(texte).ok_or_else(|| tr!("repl.montant_invalide", texte));
+    let libelle = |mots: &[&str]| (!mots.is_empty()).then(|| mots.join(" "));
+
+    let commande = match (action, arguments) {
+        (Action::Solde, [numero]) => Ok(Commande::Solde(numero.to_string())),
+        (Action::Historique, [numero]) => Ok(Commande::Historique(numero.to_string())),
+        (Action::Depot, [numero, somme, reste @ ..]) => {
+            montant(somme).map(|montant| Commande::Depot {
+                numero: numero.to_string(),
+                montant,
+                libelle: libelle(reste),
+            })
+        }
+        (Action::Retrait, [numero, somme, reste @ ..]) => {
+            montant(somme).map(|montant| Commande::Retrait {
+                numero: numero.to_string(),
+                montant,
+                libelle: libelle(reste),
+            })
+        }
+        (Action::Virement, [source, destination, somme, reste @ ..]) => {
+            montant(somme).map(|montant| Commande::Virement {
+                source: source.to_string(),
+                destination: destination.to_string(),
+                montant,
+                libelle: libelle(reste),
+            })
+        }
+        (Action::Comptes, []) => Ok(Commande::Comptes),
+        (Action::Aide, []) => Ok(Commande::Aide),
+        (Action::Quitter, []) => Ok(Commande::Quitter),
+        _ => Err(usage()),
+    };
+    Some(commande)
+}
+
+/// Position du mot à compléter dans `ligne` et propositions pour ce mot : un
+/// nom de commande en tête de ligne, un numéro de compte là où la commande en
+/// attend un.
+fn completer(ligne: &str, numeros: &[String]) -> (usize, Vec<String>) {
+    let debut = ligne.rfind(char::is_whitespace).map_or(0, |i| i + 1);
+    let prefixe = &ligne[debut..];
+    let precedents: Vec<&str> = ligne[..debut].split_whitespace().collect();
+    let propositions = match precedents.split_first() {
+        None => COMMANDES
+            .iter()
+            .map(|(action, _, _)| action.nom())
+            .filter(|nom| nom.starts_with(prefixe))
+            .map(str::to_string)
+            .collect(),
+        Some((nom, arguments)) => match Action::depuis_nom(nom) {
+            Some(action) if arguments.len() < action.comptes() => numeros
+                .iter()
+                .filter(|numero| numero.starts_with(prefixe))
+                .cloned()
+                .collect(),
+            _ => Vec::new(),
+        },
+    };
+    (debut, propositions)
+}
+
+/// Complétion de l'éditeur de ligne, sur les comptes connus à l'invite.
+struct AideSaisie {
+    numeros: Vec<String>,
+}
+
+impl Completer for AideSaisie {
+    type Candidate = String;
+
+    fn complete(
+        &self,
+        ligne: &str,
+        position: usize,
+        _contexte: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<String>)> {
+        Ok(completer(&ligne[..position], &self.numeros))
+    }
+}
+
+impl Hinter for AideSaisie {
+    type Hint = String;
+}
+
+impl Highlighter for AideSaisie {}
+
+impl Validator for AideSaisie {}
+
+impl Helper for AideSaisie {}
+
+fn afficher_aide(sortie: &mut impl Write) -> io::Result<()> {
+    writeln!(sortie, "{}", tr!("repl.aide"))?;
+    for (action, _, _) in COMMANDES {
+        writeln!(sortie, "  {}", action.usage())?;
+    }
+    Ok(())
+}
+
+/// Exécute une ligne de commande ; retourne `false` quand la session doit
+/// s'arrêter. Les confirmations et approbations sont demandées sur `console`.
+fn executer_ligne<R: BufRead, W: Write>(
+    console: &mut Console<R, W>,
+    gestionnaire: &mut GestionnaireComptes,
+    ligne: &str,
+) -> io::Result<bool> {
+    let commande = match analyser(ligne) {
+        None => return Ok(true),
+        Some(Ok(commande)) => commande,
+        Some(Err(message)) => {
+            writeln!(console.sortie, "{}", message)?;
+            return Ok(true);
+        }
+    };
+
+    match commande {
+        Commande::Solde(numero) => match gestionnaire.compte(&numero) {
+            Ok(compte) => {
+                compte.afficher_solde(&mut console.sortie)?;
+                let en_attente = gestionnaire.montant_en_attente(&numero);
+                if en_attente > 0.0 {
+                    writeln!(
+                        console.sortie,
+                        "{}",
+                        tr!("cheques.solde_en_attente", langue::montant(en_attente))
+                    )?;
+                }
+            }
+            Err(e) => writeln!(console.sortie, "{}", e)?,
+        },
+        Commande::Historique(numero) => match gestionnaire.compte(&numero) {
+            Ok(compte) => {
+                compte.afficher_historique(Local::now().date_naive(), &mut console.sortie)?
+            }
+            Err(e) => writeln!(console.sortie, "{}", e)?,
+        },
+        Commande::Depot {
+            numero,
+            montant,
+            libelle,
+        } => {
+            let libelle = libelle.unwrap_or_else(|| tr!("session.libelle_depot"));
+            let depot = crate::confirmer_si_requis(console, gestionnaire, |g| {
+                g.depot(&numero, montant, &libelle)
+            })?;
+            match depot {
+                Ok(solde) => writeln!(
+                    console.sortie,
+                    "{}",
+                    tr!(
+                        "session.depot_effectue",
+                        langue::montant(montant),
+                        langue::montant(solde)
+                    )
+                )?,
+                Err(e) => writeln!(console.sortie, "{}", e)?,
+            }
+        }
+        Commande::Retrait {
+            numero,
+            montant,
+            libelle,
+        } => {
+            let libelle = libelle.unwrap_or_else(|| tr!("session.libelle_retrait"));
+            let retrait = crate::confirmer_si_requis(console, gestionnaire, |g| {
+                g.retrait(&numero, montant, &libelle)
+            })?;
+            match retrait {
+                Ok(solde) => writeln!(
+                    console.sortie,
+                    "{}",
+                    tr!(
+                        "session.retrait_effectue",
+                        langue::montant(montant),
+                        langue::montant(solde)
+                    )
+                )?,
+                Err(e) => writeln!(console.sortie, "{}", e)?,
+            }
+        }
+        Commande::Virement {
+            source,
+            destination,
+            montant,
+            libelle,
+        } => {
+            let libelle = libelle.unwrap_or_else(|| tr!("tui.virement"));
+            let virement = crate::confirmer_si_requis(console, gestionnaire, |g| {
+                g.virement(&source, &destination, montant, &libelle)
+            })?;
+            match virement {
+                Ok(solde) => writeln!(
+                    console.sortie,
+                    "{}",
+                    tr!(
+                        "repl.virement_effectue",
+                        langue::montant(montant),
+                        destination,
+                        langue::montant(solde)
+                    )
+                )?,
+                Err(e) => writeln!(console.sortie, "{}", e)?,
+            }
+        }
+        Commande::Comptes => gestionnaire.lister_comptes(&mut console.sortie)?,
+        Commande::Aide => afficher_aide(&mut console.sortie)?,
+        Commande::Quitter => {
+            writeln!(console.sortie, "{}", tr!("menu.au_revoir"))?;
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+/// Boucle de l'invite jusqu'à `quitter` ou Ctrl-D. L'historique est relu depuis
+/// `historique` au démarrage et y est réécrit à la fin.
+pub fn executer(gestionnaire: &mut GestionnaireComptes, historique: &Path) -> io::Result<()> {
+    let mut editeur: Editor<AideSaisie, DefaultHistory> =
+        Editor::new().map_err(io::Error::other)?;
+    editeur.set_helper(Some(AideSaisie {
+        numeros: Vec::new(),
+    }));
+    // Un historique absent ou illisible repart de zéro.
+    let _ = editeur.load_history(historique);
+    println!("{}", tr!("repl.bienvenue"));
+
+    loop {
+        if let Some(aide) = editeur.helper_mut() {
+            aide.numeros = gestionnaire
+                .comptes()
+                .iter()
+                .map(|compte| compte.numero().to_string())
+                .collect();
+        }
+        let ligne = match editeur.readline("> ") {
+            Ok(ligne) => ligne,
+            Err(ReadlineError::Interrupted) => continue,
+            Err(ReadlineError::Eof) => {
+                println!("{}", tr!("menu.au_revoir"));
+                break;
+            }
+            Err(e) => return Err(io::Error::other(e)),
+        };
+        if !ligne.trim().is_empty() {
+            let _ = editeur.add_history_entry(ligne.as_str());
+        }
+        let mut console = Console::new(io::stdin().lock(), io::stdout())
+            .saisie_sans_echo(io::stdin().is_terminal());
+        if !executer_ligne(&mut console, gestionnaire, &ligne)? {
+            break;
+        }
+    }
+
+    editeur.save_history(historique).map_err(io::Error::other)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+    use tp1::CompteBancaire;
+
+    #[test]
+    fn analyse_des_commandes() {
+        assert_eq!(analyser("  "), None);
+        assert_eq!(
+            analyser("deposit 12345 50,5 Vente du marché"),
+            Some(Ok(Commande::Depot {
+                numero: "12345".to_string(),
+                montant: 50.5,
+                libelle: Some("Vente du marché".to_string()),
+            }))
+        );
+        assert_eq!(
+            analyser("SOLDE 67890"),
+            Some(Ok(Commande::Solde("67890".to_string())))
+        );
+        assert_eq!(
+            analyser("virement 12345 67890 abc"),
+            Some(Err("Montant invalide « abc »".to_string()))
+        );
+        assert!(
+            matches!(analyser("retrait 12345"), Some(Err(usage)) if usage.starts_with("Usage"))
+        );
+        assert_eq!(
+            analyser("ouvrir 1"),
+            Some(Err(
+                "Commande inconnue « ouvrir » : tapez « aide » pour la liste.".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn completion_des_commandes_et_des_comptes() {
+        let numeros = [
+            "12345".to_string(),
+            "12999".to_string(),
+            "67890".to_string(),
+        ];
+        assert_eq!(completer("de", &numeros), (0, vec!["depot".to_string()]));
+        assert_eq!(
+            completer("virement 12345 12", &numeros),
+            (15, vec!["12345".to_string(), "12999".to_string()])
+        );
+        // Le montant ne se complète pas.
+        assert_eq!(completer("deposit 12345 1", &numeros), (14, Vec::new()));
+    }
+
+    #[test]
+    fn session_de_commandes() {
+        let mut gestionnaire = GestionnaireComptes::new();
+        gestionnaire
+            .ajouter_compte(CompteBancaire::new(
+                "12345".to_string(),
+                "Alice Dupont".to_string(),
+                100.0,
+            ))
+            .unwrap();
+        let mut console = Console::new(Cursor::new(Vec::new()), Vec::new());
+        for ligne in ["depot 12345 50", "retrait 12345 500", "solde 12345"] {
+            assert!(executer_ligne(&mut console, &mut gestionnaire, ligne).unwrap());
+        }
+        assert!(!executer_ligne(&mut console, &mut gestionnaire, "quit").unwrap());
+
+        let sortie = String::from_utf8(console.sortie).unwrap();
+        assert!(sortie.contains("Dépôt de 50,00€ effectué. Nouveau solde: 150,00€"));
+        assert!(sortie.contains("Solde insuffisant"));
+        assert!(sortie.contains("Solde: 150,00€"));
+    }
+}
@@ -0,0 +1,434 @@
+//! Sauvegarde, restauration et retour à un instant passé de l'état de la banque.
+//!
+//! Le journal des opérations inscrit, après chaque opération, même refusée, les
+//! lignes de jeu de données qui reprennent ce qu'elle a changé, chacune précédée
+//! de son horodatage : ouvertures de comptes et de prêts, mouvements, échéances
+//! payées, remboursements anticipés et catégories (voir `fixture`), puis chèques,
+//! mandats, objectifs d'épargne, règles d'alerte, caisse, dormance et
+//! signalements (voir `etat`). Le jeu de données complété du journal redonne
+//! l'état de la banque ; arrêté à un horodatage, il redonne l'état à cet instant.
+//!
+//! N'y figurent pas la configuration relue au démarrage (règles de catégories
+//! et de fraude, délais), les alertes déjà émises, l'utilisateur connecté, ni
+//! les clés d'idempotence et les utilisateurs, qui ont leurs propres fichiers.
+//!
+//! Une archive est un dossier nommé d'après sa date (`AAAAMMJJ-HHMMSS`) qui
+//! contient une copie de chaque fichier d'état, tel quel, et un manifeste qui
+//! donne leur chemin d'origine et leur empreinte SHA-256.
+
+use crate::chiffrement::Coffre;
+use crate::erreur::ErreurBancaire;
+use crate::tr;
+use chrono::{Local, NaiveDateTime};
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Format des horodatages du journal et du manifeste.
+const FORMAT_HORODATAGE: &str = "%Y-%m-%dT%H:%M:%S";
+/// Format du nom des archives.
+const FORMAT_ARCHIVE: &str = "%Y%m%d-%H%M%S";
+/// Début de la première ligne du journal, suivi de l'empreinte de son jeu de données.
+const ENTETE_JOURNAL: &str = "#jeu";
+/// Fichier des empreintes dans une archive.
+pub const MANIFESTE: &str = "MANIFESTE";
+
+fn empreinte(octets: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(octets))
+}
+
+fn erreur_ligne(ligne: usize, texte: &str) -> ErreurBancaire {
+    ErreurBancaire::Fichier(tr!(
+        "fixture.erreur_ligne",
+        ligne,
+        tr!("fixture.ligne_invalide", texte)
+    ))
+}
+
+/// Ligne de jeu de données inscrite au journal par une opération.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LigneJournal {
+    pub horodatage: NaiveDateTime,
+    pub ligne: String,
+}
+
+impl fmt::Display for LigneJournal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}\t{}",
+            self.horodatage.format(FORMAT_HORODATAGE),
+            self.ligne
+        )
+    }
+}
+
+/// Empreinte du jeu de données de l'en-tête et lignes du journal, dans l'ordre.
+fn lire_journal(contenu: &str) -> Result<(Option<&str>, Vec<LigneJournal>), ErreurBancaire> {
+    let mut base = None;
+    let mut lignes = Vec::new();
+    for (i, ligne) in contenu.lines().enumerate() {
+        if let Some(empreinte) = ligne.strip_prefix(ENTETE_JOURNAL) {
+            base = Some(empreinte.trim());
+            continue;
+        }
+        if ligne.trim().is_empty() {
+            continue;
+        }
+        let lue = ligne.split_once('\t').and_then(|(horodatage, texte)| {
+            Some(LigneJournal {
+                horodatage: NaiveDateTime::parse_from_str(horodatage, FORMAT_HORODATAGE).ok()?,
+                ligne: texte.to_string(),
+            })
+        });
+        lignes.push(lue.ok_or_else(|| erreur_ligne(i + 1, ligne))?);
+    }
+    Ok((base, lignes))
+}
+
+/// Journal des opérations tenu sur un jeu de données. Il n'est jamais réécrit,
+/// seulement complété, sauf par un retour à un instant passé ; avec un coffre
+/// chiffré, chaque ajout est chiffré séparément.
+pub struct JournalOperations {
+    chemin: PathBuf,
+    coffre: Coffre,
+    lignes: Vec<LigneJournal>,
+}
+
+impl JournalOperations {
+    /// Ouvre le journal tenu sur le jeu de données `jeu`, créé s'il n'existe pas.
+    /// Un journal tenu sur un autre jeu est refusé s'il contient des opérations ;
+    /// vide, il est rattaché à `jeu`.
+    pub fn ouvrir(chemin: PathBuf, coffre: Coffre, jeu: &str) -> Result<Self, ErreurBancaire> {
+        let contenu = match coffre.lire(&chemin) {
+            Ok(contenu) => contenu,
+            Err(e) if e.introuvable() => String::new(),
+            Err(e) => return Err(ErreurBancaire::Fichier(e.to_string())),
+        };
+        let (base, lignes) = lire_journal(&contenu)?;
+        let attendue = empreinte(jeu.as_bytes());
+        if base != Some(attendue.as_str()) {
+            if !lignes.is_empty() {
+                return Err(ErreurBancaire::Fichier(tr!(
+                    "sauvegarde.autre_jeu",
+                    chemin.display()
+                )));
+            }
+            coffre
+                .ecrire(&chemin, &format!("{}\t{}\n", ENTETE_JOURNAL, attendue))
+                .map_err(|e| ErreurBancaire::Fichier(e.to_string()))?;
+        }
+        Ok(JournalOperations {
+            chemin,
+            coffre,
+            lignes,
+        })
+    }
+
+    /// Lignes présentes dans le journal à son ouverture.
+    pub fn lignes(&self) -> &[LigneJournal] {
+        &self.lignes
+    }
+
+    /// Inscrit les lignes d'une opération, horodatées de l'instant présent.
+    pub(crate) fn ajouter(&self, lignes: Vec<String>) -> io::Result<()> {
+        let horodatage = Local::now().naive_local();
+        for ligne in lignes {
+            let ligne = LigneJournal { horodatage, ligne };
+            self.coffre
+                .ajouter_ligne(&self.chemin, &ligne.to_string())?;
+        }
+        Ok(())
+    }
+}
+
+/// Jeu de données complété des lignes du journal.
+pub fn reconstituer(jeu: &str, lignes: &[LigneJournal]) -> String {
+    let mut contenu = jeu.to_string();
+    if !contenu.is_empty() && !contenu.ends_with('\n') {
+        contenu.push('\n');
+    }
+    for ligne in lignes {
+        contenu.push_str(&ligne.ligne);
+        contenu.push('\n');
+    }
+    contenu
+}
+
+/// Contenu du journal sans les lignes inscrites après `jusqu_au`, et nombre de
+/// lignes retirées.
+pub fn tronquer(contenu: &str, jusqu_au: NaiveDateTime) -> Result<(String, usize), ErreurBancaire> {
+    let (base, lignes) = lire_journal(contenu)?;
+    let mut tronque = String::new();
+    if let Some(base) = base {
+        tronque.push_str(&format!("{}\t{}\n", ENTETE_JOURNAL, base));
+    }
+    let mut retirees = 0;
+    for ligne in lignes {
+        if ligne.horodatage <= jusqu_au {
+            tronque.push_str(&format!("{}\n", ligne));
+        } else {
+            retirees += 1;
+        }
+    }
+    Ok((tronque, retirees))
+}
+
+// Nom d'un fichier dans une archive.
+fn nom_archive(chemin: &Path) -> String {
+    chemin
+        .file_name()
+        .map(|nom| nom.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+/// Copie dans `dossier` les fichiers d'état qui existent, dans une archive datée
+/// de `date`, et retourne son chemin. L'archive est écrite sous un nom provisoire
+/// puis renommée : une archive interrompue n'est jamais prise pour une archive
+/// complète. Si un fichier change pendant la copie, la sauvegarde échoue plutôt
+/// que de mélanger deux états.
+pub fn sauvegarder(fichiers: &[&Path], dossier: &Path, date: NaiveDateTime) -> io::Result<PathBuf> {
+    let archive = dossier.join(date.format(FORMAT_ARCHIVE).to_string());
+    if archive.exists() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            tr!("sauvegarde.archive_existante", archive.display()),
+        ));
+    }
+    let mut partielle = archive.as_os_str().to_owned();
+    partielle.push(".partielle");
+    let partielle = PathBuf::from(partielle);
+    if partielle.exists() {
+        fs::remove_dir_all(&partielle)?;
+    }
+    fs::create_dir_all(&partielle)?;
+
+    let mut manifeste = format!("date\t{}\n", date.format(FORMAT_HORODATAGE));
+    let mut copies: Vec<(&Path, String)> = Vec::new();
+    for &fichier in fichiers {
+        let octets = match fs::read(fichier) {
+            Ok(octets) => octets,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(e),
+        };
+        let nom = nom_archive(fichier);
+        if copies.iter().any(|(copie, _)| nom_archive(copie) == nom) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                tr!("sauvegarde.nom_en_double", nom),
+            ));
+        }
+        fs::write(partielle.join(&nom), &octets)?;
+        let empreinte = empreinte(&octets);
+        manifeste.push_str(&format!("{}\t{}\t{}\n", empreinte, nom, fichier.display()));
+        copies.push((fichier, empreinte));
+    }
+    for (fichier, attendue) in &copies {
+        if empreinte(&fs::read(fichier)?) != *attendue {
+            fs::remove_dir_all(&partielle)?;
+            return Err(io::Error::other(tr!(
+                "sauvegarde.fichier_modifie",
+                fichier.display()
+            )));
+        }
+    }
+    fs::write(partielle.join(MANIFESTE), manifeste)?;
+    fs::rename(&partielle, &archive)?;
+    Ok(archive)
+}
+
+/// Archives complètes du dossier, de la plus ancienne à la plus récente.
+pub fn archives(dossier: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut archives = match fs::read_dir(dossier) {
+        Ok(entrees) => entrees
+            .filter_map(|entree| Some(entree.ok()?.path()))
+            .filter(|chemin| chemin.join(MANIFESTE).is_file())
+            .collect::<Vec<_>>(),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Vec::new(),
+        Err(e) => return Err(e),
+    };
+    archives.sort();
+    Ok(archives)
+}
+
+/// Archive d'où revenir à `date` : la plus récente prise au plus tard à cet
+/// instant, sinon la plus ancienne, dont le journal sera arrêté plus tôt. Sans
+/// date, la plus récente de toutes.
+pub fn archive_pour(dossier: &Path, date: Option<NaiveDateTime>) -> io::Result<Option<PathBuf>> {
+    let archives = archives(dossier)?;
+    let Some(date) = date else {
+        return Ok(archives.last().cloned());
+    };
+    let limite = date.format(FORMAT_ARCHIVE).to_string();
+    let avant = archives
+        .iter()
+        .rev()
+        .find(|archive| nom_archive(archive) <= limite);
+    Ok(avant.or(archives.first()).cloned())
+}
+
+/// Archive dont le manifeste a été lu et les copies contrôlées.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Archive {
+    pub dossier: PathBuf,
+    pub date: NaiveDateTime,
+    /// Chemin d'origine et nom dans l'archive de chaque fichier copié.
+    fichiers: Vec<(PathBuf, String)>,
+}
+
+impl Archive {
+    /// Lit le manifeste de l'archive et vérifie l'empreinte de chaque copie.
+    pub fn lire(dossier: &Path) -> Result<Self, ErreurBancaire> {
+        let illisible =
+            || ErreurBancaire::Fichier(tr!("sauvegarde.archive_illisible", dossier.display()));
+        let manifeste = fs::read_to_string(dossier.join(MANIFESTE)).map_err(|_| illisible())?;
+        let mut lignes = manifeste.lines();
+        let date = lignes
+            .next()
+            .and_then(|ligne| ligne.strip_prefix("date\t"))
+            .and_then(|date| NaiveDateTime::parse_from_str(date, FORMAT_HORODATAGE).ok())
+            .ok_or_else(illisible)?;
+
+        let mut fichiers = Vec::new();
+        for ligne in lignes {
+            let champs: Vec<&str> = ligne.split('\t').collect();
+            let [attendue, nom, origine] = champs[..] else {
+                return Err(illisible());
+            };
+            let intacte =
+                fs::read(dossier.join(nom)).is_ok_and(|octets| empreinte(&octets) == attendue);
+            if !intacte {
+                return Err(ErreurBancaire::Fichier(tr!(
+                    "sauvegarde.copie_alteree",
+                    dossier.display(),
+                    nom
+                )));
+            }
+            fichiers.push((PathBuf::from(origine), nom.to_string()));
+        }
+        Ok(Archive {
+            dossier: dossier.to_path_buf(),
+            date,
+            fichiers,
+        })
+    }
+
+    /// Copie archivée du fichier d'état `chemin`, s'il existait à la sauvegarde.
+    pub fn copie(&self, chemin: &Path) -> Option<PathBuf> {
+        self.fichiers
+            .iter()
+            .find(|(origine, _)| origine == chemin)
+            .map(|(_, nom)| self.dossier.join(nom))
+    }
+
+    /// Remet les fichiers d'état dans leur état archivé : ceux qui n'existaient
+    /// pas à la sauvegarde sont supprimés. Les fichiers de `conserver` qui
+    /// existent encore sont laissés tels quels. Les copies sont écrites à côté
+    /// de chaque fichier avant d'en remplacer un.
+    pub fn restaurer(&self, fichiers: &[&Path], conserver: &[&Path]) -> io::Result<()> {
+        let a_restaurer: Vec<&Path> = fichiers
+            .iter()
+            .copied()
+            .filter(|fichier| !(conserver.contains(fichier) && fichier.exists()))
+            .collect();
+        let temporaire = |fichier: &Path| {
+            let mut nom = fichier.as_os_str().to_owned();
+            nom.push(".restauration");
+            PathBuf::from(nom)
+        };
+        for &fichier in &a_restaurer {
+            if let Some(copie) = self.copie(fichier) {
+                fs::copy(copie, temporaire(fichier))?;
+            }
+        }
+        for &fichier in &a_restaurer {
+            if self.copie(fichier).is_some() {
+                fs::rename(temporaire(fichier), fichier)?;
+            } else if fichier.exists() {
+                fs::remove_file(fichier)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture;
+    use chrono::NaiveDate;
+
+    fn horodatage(jour: u32, heure: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2026, 10, jour)
+            .unwrap()
+            .and_hms_opt(heure, 0, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn journal_rejoue_jusqu_a_un_instant() {
+        let jeu = "client;C1;Alice Dupont\ncompte;12345;C1;02/01/2026;100.00\n";
+        let mut journal = format!("{}\t{}\n", ENTETE_JOURNAL, empreinte(jeu.as_bytes()));
+        for (jour, ligne) in [
+            (17, "mouvement;12345;17/10/2026 10:00;50.00;Dépôt"),
+            (18, "client;Bob Martin;Bob Martin"),
+            (18, "compte;67890;Bob Martin;18/10/2026;20.00"),
+            (19, "mouvement;12345;19/10/2026 09:00;-500.00;Import erroné"),
+        ] {
+            let ligne = LigneJournal {
+                horodatage: horodatage(jour, 10),
+                ligne: ligne.to_string(),
+            };
+            journal.push_str(&format!("{}\n", ligne));
+        }
+
+        let (tronque, retirees) = tronquer(&journal, horodatage(18, 17)).unwrap();
+        assert_eq!(retirees, 1);
+        let (base, lignes) = lire_journal(&tronque).unwrap();
+        assert_eq!(base, Some(empreinte(jeu.as_bytes()).as_str()));
+        let comptes = fixture::lire(&reconstituer(jeu, &lignes)).unwrap();
+        assert_eq!(comptes.len(), 2);
+        assert_eq!(comptes[0].solde(), 150.0);
+        assert_eq!(comptes[1].titulaire(), "Bob Martin");
+
+        assert!(lire_journal("2026-10-18 10:00\tligne").is_err());
+    }
+
+    #[test]
+    fn archive_controlee_puis_restauree() {
+        let dossier = std::env::temp_dir().join(format!("tp1-sauvegarde-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dossier);
+        fs::create_dir_all(&dossier).unwrap();
+        let etat = dossier.join("etat.txt");
+        let absent = dossier.join("absent.txt");
+        let conserve = dossier.join("audit.log");
+        fs::write(&etat, "avant").unwrap();
+        fs::write(&conserve, "entrée 1").unwrap();
+        let fichiers = [etat.as_path(), absent.as_path(), conserve.as_path()];
+
+        let chemin = sauvegarder(&fichiers, &dossier.join("archives"), horodatage(18, 17)).unwrap();
+        assert!(sauvegarder(&fichiers, &dossier.join("archives"), horodatage(18, 17)).is_err());
+        assert_eq!(archives(&dossier.join("archives")).unwrap().len(), 1);
+        let avant = Some(horodatage(18, 16));
+        assert_eq!(
+            archive_pour(&dossier.join("archives"), avant).unwrap(),
+            Some(chemin.clone())
+        );
+
+        fs::write(&etat, "après").unwrap();
+        fs::write(&absent, "créé ensuite").unwrap();
+        fs::write(&conserve, "entrée 1\nentrée 2").unwrap();
+        let archive = Archive::lire(&chemin).unwrap();
+        assert_eq!(archive.date, horodatage(18, 17));
+        archive.restaurer(&fichiers, &[conserve.as_path()]).unwrap();
+        assert_eq!(fs::read_to_string(&etat).unwrap(), "avant");
+        assert!(!absent.exists());
+        assert_eq!(fs::read_to_string(&conserve).unwrap(), "entrée 1\nentrée 2");
+
+        fs::write(chemin.join("etat.txt"), "altéré").unwrap();
+        assert!(Archive::lire(&chemin).is_err());
+        fs::remove_dir_all(&dossier).unwrap();
+    }
+}
@@ -0,0 +1,118 @@
+//! Mouvement inscrit dans l'historique d'un compte.
+
+use crate::langue::{self, Langue};
+use chrono::NaiveDateTime;
+use std::fmt::{self, Display};
+
+/// Libellé ou catégorie d'un mouvement. Ce que produit la banque est gardé
+/// sous forme de clé du catalogue et traduit à l'affichage ; ce que saisit
+/// l'utilisateur est gardé tel quel.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Libelle {
+    Saisi(String),
+    Banque {
+        cle: &'static str,
+        arguments: Vec<String>,
+    },
+}
+
+impl Libelle {
+    // Le `|` sépare les arguments dans la forme enregistrée.
+    pub fn banque(cle: &'static str, arguments: &[&dyn Display]) -> Self {
+        Libelle::Banque {
+            cle,
+            arguments: arguments
+                .iter()
+                .map(|argument| argument.to_string().replace('|', "/"))
+                .collect(),
+        }
+    }
+
+    /// Texte du libellé dans la langue `langue`.
+    pub fn en(&self, langue: Langue) -> String {
+        match self {
+            Libelle::Saisi(texte) => texte.clone(),
+            Libelle::Banque { cle, arguments } => {
+                let arguments: Vec<&dyn Display> =
+                    arguments.iter().map(|a| a as &dyn Display).collect();
+                langue::formater_en(langue, cle, &arguments)
+            }
+        }
+    }
+
+    /// Forme enregistrée dans les fichiers, indépendante de la langue :
+    /// `@cle|argument|…` pour un libellé de la banque, sinon le texte saisi,
+    /// dont un `@` initial est doublé.
+    pub fn code(&self) -> String {
+        match self {
+            Libelle::Saisi(texte) if texte.starts_with('@') => format!("@{}", texte),
+            Libelle::Saisi(texte) => texte.clone(),
+            Libelle::Banque { cle, arguments } => {
+                let mut code = format!("@{}", cle);
+                for argument in arguments {
+                    code.push('|');
+                    code.push_str(argument);
+                }
+                code
+            }
+        }
+    }
+
+    /// Relit la forme produite par [`Libelle::code`] ; une clé inconnue du
+    /// catalogue est reprise comme texte saisi.
+    pub fn depuis_code(code: &str) -> Self {
+        if let Some(texte) = code.strip_prefix("@@") {
+            return Libelle::Saisi(format!("@{}", texte));
+        }
+        if let Some(reste) = code.strip_prefix('@') {
+            let mut morceaux = reste.split('|');
+            if let Some(cle) = morceaux.next().and_then(langue::cle) {
+                return Libelle::Banque {
+                    cle,
+                    arguments: morceaux.map(String::from).collect(),
+                };
+            }
+        }
+        Libelle::Saisi(code.to_string())
+    }
+}
+
+impl fmt::Display for Libelle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad(&self.en(langue::courante()))
+    }
+}
+
+impl From<&str> for Libelle {
+    fn from(texte: &str) -> Self {
+        Libelle::Saisi(texte.to_string())
+    }
+}
+
+impl From<String> for Libelle {
+    fn from(texte: String) -> Self {
+        Libelle::Saisi(texte)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Transaction {
+    pub date: NaiveDateTime,
+    pub libelle: Libelle,
+    pub montant: f32,
+    pub categorie: Option<Libelle>,
+    /// Devise d'un virement émis en devise étrangère ; `None` pour l'euro.
+    pub devise: Option<String>,
+}
+
+impl Transaction {
+    pub fn new(date: NaiveDateTime, libelle: Libelle, montant: f32) -> Self {
+        Transaction {
+            date,
+            libelle,
+            montant,
+            categorie: None,
+            devise: None,
+        }
+    }
+}
@@ -0,0 +1,568 @@
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, Paragraph, Row, Table, TableState};
+use ratatui::{DefaultTerminal, Frame};
+use std::io;
+use tp1::{CompteBancaire, GestionnaireComptes, langue, tr};
+
+const TRANSACTIONS_AFFICHEES: usize = 12;
+
+// Clés des champs du formulaire, qui servent aussi à traduire leur libellé.
+const MONTANT: &str = "tui.montant";
+const LIBELLE: &str = "tui.libelle";
+const DESTINATAIRE: &str = "tui.destinataire";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum TypeOperation {
+    Depot,
+    Retrait,
+    Virement,
+}
+
+impl TypeOperation {
+    fn titre(&self) -> &'static str {
+        match self {
+            TypeOperation::Depot => langue::texte("tui.depot"),
+            TypeOperation::Retrait => langue::texte("tui.retrait"),
+            TypeOperation::Virement => langue::texte("tui.virement"),
+        }
+    }
+}
+
+struct Champ {
+    cle: &'static str,
+    valeur: String,
+}
+
+/// Formulaire de saisie d'une opération sur le compte sélectionné.
+struct Formulaire {
+    operation: TypeOperation,
+    numero: String,
+    champs: Vec<Champ>,
+    actif: usize,
+}
+
+impl Formulaire {
+    fn new(operation: TypeOperation, numero: String) -> Self {
+        let mut cles = vec![MONTANT, LIBELLE];
+        if operation == TypeOperation::Virement {
+            cles.insert(0, DESTINATAIRE);
+        }
+        let champs = cles
+            .into_iter()
+            .map(|cle| Champ {
+                cle,
+                valeur: String::new(),
+            })
+            .collect();
+        Formulaire {
+            operation,
+            numero,
+            champs,
+            actif: 0,
+        }
+    }
+
+    fn valeur(&self, cle: &str) -> &str {
+        self.champs
+            .iter()
+            .find(|champ| champ.cle == cle)
+            .map(|champ| champ.valeur.trim())
+            .unwrap_or("")
+    }
+
+    fn montant(&self) -> Option<f32> {
+        langue::lire_nombre(self.valeur(MONTANT))
+    }
+
+    fn libelle(&self) -> String {
+        match self.valeur(LIBELLE) {
+            "" => self.operation.titre().to_string(),
+            libelle => libelle.to_string(),
+        }
+    }
+
+    /// Erreur de validation de chaque champ, recalculée à chaque frappe.
+    fn erreurs(&self, gestionnaire: &GestionnaireComptes) -> Vec<Option<String>> {
+        let solde = gestionnaire
+            .compte(&self.numero)
+            .map(|compte| compte.solde())
+            .unwrap_or(0.0);
+
+        self.champs
+            .iter()
+            .map(|champ| match champ.cle {
+                MONTANT => match self.montant() {
+                    None if champ.valeur.is_empty() => Some(tr!("tui.montant_requis")),
+                    None => Some(tr!("tui.nombre_invalide")),
+                    Some(m) if m <= 0.0 => Some(tr!("erreur.montant_non_positif")),
+                    Some(m) if self.operation != TypeOperation::Depot && m > solde => {
+                        Some(tr!("tui.solde_insuffisant", langue::montant(solde)))
+                    }
+                    Some(_) => None,
+                },
+                DESTINATAIRE => {
+                    let destination = champ.valeur.trim();
+                    if destination.is_empty() {
+                        Some(tr!("tui.compte_requis"))
+                    } else if destination == self.numero {
+                        Some(tr!("tui.autre_compte"))
+                    } else if !gestionnaire.contient(destination) {
+                        Some(tr!("tui.compte_inconnu"))
+                    } else {
+                        None
+                    }
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn soumettre(&self, gestionnaire: &mut GestionnaireComptes) -> String {
+        let Some(montant) = self.montant() else {
+            return tr!("tui.nombre_invalide");
+        };
+        let libelle = self.libelle();
+        let resultat = match self.operation {
+            TypeOperation::Depot => gestionnaire.depot(&self.numero, montant, &libelle),
+            TypeOperation::Retrait => gestionnaire.retrait(&self.numero, montant, &libelle),
+            TypeOperation::Virement => {
+                gestionnaire.virement(&self.numero, self.valeur(DESTINATAIRE), montant, &libelle)
+            }
+        };
+        match resultat {
+            Ok(solde) => tr!(
+                "tui.operation_effectuee",
+                self.operation.titre(),
+                langue::montant(montant),
+                langue::montant(solde)
+            ),
+            Err(e) => e.to_string(),
+        }
+    }
+}
+
+struct Application {
+    etat_table: TableState,
+    formulaire: Option<Formulaire>,
+    message: String,
+    quitter: bool,
+}
+
+/// Lance l'interface plein écran et rend le terminal dans son état initial à la sortie.
+pub fn executer(gestionnaire: &mut GestionnaireComptes) -> io::Result<()> {
+    let mut terminal = ratatui::init();
+    let resultat = Application::new().boucle(&mut terminal, gestionnaire);
+    ratatui::restore();
+    resultat
+}
+
+impl Application {
+    fn new() -> Self {
+        Application {
+            etat_table: TableState::default().with_selected(Some(0)),
+            formulaire: None,
+            message: String::new(),
+            quitter: false,
+        }
+    }
+
+    fn boucle(
+        &mut self,
+        terminal: &mut DefaultTerminal,
+        gestionnaire: &mut GestionnaireComptes,
+    ) -> io::Result<()> {
+        while !self.quitter {
+            terminal.draw(|frame| self.dessiner(frame, gestionnaire))?;
+            if let Event::Key(touche) = event::read()?
+                && touche.kind == KeyEventKind::Press
+            {
+                self.traiter_touche(touche, gestionnaire);
+            }
+        }
+        Ok(())
+    }
+
+    fn compte_selectionne<'a>(
+        &self,
+        gestionnaire: &'a GestionnaireComptes,
+    ) -> Option<&'a CompteBancaire> {
+        let comptes = gestionnaire.comptes();
+        self.etat_table
+            .selected()
+            .and_then(|i| comptes.get(i).copied())
+    }
+
+    fn traiter_touche(&mut self, touche: KeyEvent, gestionnaire: &mut GestionnaireComptes) {
+        if let Some(formulaire) = self.formulaire.as_mut() {
+            match touche.code {
+                KeyCode::Esc => {
+                    self.formulaire = None;
+                    self.message = tr!("tui.annulee");
+                }
+                KeyCode::Tab | KeyCode::Down => {
+                    formulaire.actif = (formulaire.actif + 1) % formulaire.champs.len();
+                }
+                KeyCode::BackTab | KeyCode::Up => {
+                    formulaire.actif =
+                        (formulaire.actif + formulaire.champs.len() - 1) % formulaire.champs.len();
+                }
+                KeyCode::Backspace => {
+                    formulaire.champs[formulaire.actif].valeur.pop();
+                }
+                KeyCode::Char(c) => formulaire.champs[formulaire.actif].valeur.push(c),
+                KeyCode::Enter => {
+                    let erreurs = formulaire.erreurs(gestionnaire);
+                    if let Some(i) = erreurs.iter().position(Option::is_some) {
+                        formulaire.actif = i;
+                    } else {
+                        self.message = formulaire.soumettre(gestionnaire);
+                        self.formulaire = None;
+                    }
+                }
+                _ => {}
+            }
+            return;
+        }
+
+        let nombre = gestionnaire.comptes().len();
+        match touche.code {
+            KeyCode::Char('q') | KeyCode::Esc => self.quitter = true,
+            KeyCode::Down | KeyCode::Char('j') if nombre > 0 => {
+                let i = self.etat_table.selected().map_or(0, |i| (i + 1) % nombre);
+                self.etat_table.select(Some(i));
+            }
+            KeyCode::Up | KeyCode::Char('k') if nombre > 0 => {
+                let i = self
+                    .etat_table
+                    .selected()
+                    .map_or(0, |i| (i + nombre - 1) % nombre);
+                self.etat_table.select(Some(i));
+            }
+            KeyCode::Char(c @ ('d' | 'r' | 'v')) => {
+                let operation = match c {
+                    'd' => TypeOperation::Depot,
+                    'r' => TypeOperation::Retrait,
+                    _ => TypeOperation::Virement,
+                };
+                match self.compte_selectionne(gestionnaire) {
+                    Some(compte) if compte.pret().is_some() => {
+                        self.message = tr!("erreur.operation_sur_pret");
+                    }
+                    Some(compte) => {
+                        self.formulaire =
+                            Some(Formulaire::new(operation, compte.numero().to_string()));
+                        self.message.clear();
+                    }
+                    None => self.message = tr!("tui.aucune_selection"),
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn dessiner(&mut self, frame: &mut Frame, gestionnaire: &GestionnaireComptes) {
+        let [haut, bas] =
+            Layout::vertical([Constraint::Min(5), Constraint::Length(3)]).areas(frame.area());
+        let [gauche, droite] =
+            Layout::horizontal([Constraint::Percentage(45), Constraint::Percentage(55)])
+                .areas(haut);
+
+        self.dessiner_comptes(frame, gauche, gestionnaire);
+        self.dessiner_detail(frame, droite, gestionnaire);
+
+        let aide = if self.formulaire.is_some() {
+            langue::texte("tui.aide_formulaire")
+        } else {
+            langue::texte("tui.aide")
+        };
+        let mut lignes = vec![Line::from(aide)];
+        if let Some(alerte) = gestionnaire.alertes().last() {
+            lignes.push(Line::styled(
+                format!("⚠ {}", alerte),
+                Style::default().fg(Color::Yellow),
+            ));
+        }
+        if !self.message.is_empty() {
+            lignes.insert(
+                0,
+                Line::styled(
+                    self.message.clone(),
+                    Style::default().add_modifier(Modifier::BOLD),
+                ),
+            );
+        }
+        frame.render_widget(
+            Paragraph::new(lignes).block(Block::default().borders(Borders::TOP)),
+            bas,
+        );
+
+        if let Some(formulaire) = &self.formulaire {
+            dessiner_formulaire(frame, formulaire, gestionnaire);
+        }
+    }
+
+    fn dessiner_comptes(
+        &mut self,
+        frame: &mut Frame,
+        zone: Rect,
+        gestionnaire: &GestionnaireComptes,
+    ) {
+        let lignes = gestionnaire.comptes().into_iter().map(|compte| {
+            let style = if compte.solde() < 0.0 {
+                Style::default().fg(Color::Red)
+            } else {
+                Style::default()
+            };
+            Row::new(vec![
+                compte.numero().to_string(),
+                compte.titulaire().to_string(),
+                format!("{:>12.2}€", compte.solde()),
+            ])
+            .style(style)
+        });
+        let table = Table::new(
+            lignes,
+            [
+                Constraint::Length(10),
+                Constraint::Min(15),
+                Constraint::Length(14),
+            ],
+        )
+        .header(
+            Row::new(vec![
+                tr!("tui.numero"),
+                tr!("tui.titulaire"),
+                tr!("tui.solde"),
+            ])
+            .style(Style::default().add_modifier(Modifier::BOLD)),
+        )
+        .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+        .block(
+            Block::default()
+                .title(format!(" {} ", tr!("tui.comptes")))
+                .borders(Borders::ALL),
+        );
+        frame.render_stateful_widget(table, zone, &mut self.etat_table);
+    }
+
+    fn dessiner_detail(&self, frame: &mut Frame, zone: Rect, gestionnaire: &GestionnaireComptes) {
+        let bloc = Block::default()
+            .title(format!(" {} ", tr!("tui.detail")))
+            .borders(Borders::ALL);
+        let Some(compte) = self.compte_selectionne(gestionnaire) else {
+            frame.render_widget(Paragraph::new(tr!("liste.vide")).block(bloc), zone);
+            return;
+        };
+
+        let mut lignes = vec![
+            Line::from(vec![
+                Span::styled(
+                    format!("{}: ", tr!("tui.compte")),
+                    Style::default().add_modifier(Modifier::BOLD),
+                ),
+                Span::raw(compte.numero()),
+            ]),
+            Line::from(vec![
+                Span::styled(
+                    format!("{}: ", tr!("tui.titulaire")),
+                    Style::default().add_modifier(Modifier::BOLD),
+                ),
+                Span::raw(compte.titulaire()),
+            ]),
+            Line::from(vec![
+                Span::styled(
+                    format!("{}: ", tr!("tui.solde")),
+                    Style::default().add_modifier(Modifier::BOLD),
+                ),
+                Span::raw(langue::montant(compte.solde())),
+            ]),
+        ];
+        if let Some(pret) = compte.pret() {
+            let mensualite = match pret.prochaine_echeance() {
+                Some(echeance) => tr!(
+                    "tui.echeance",
+                    langue::montant(echeance.mensualite),
+                    echeance.date.format("%d/%m/%Y")
+                ),
+                None => tr!("etat.pret_solde"),
+            };
+            lignes.push(Line::from(tr!("tui.pret_lie", pret.compte_lie, mensualite)));
+        }
+
+        lignes.push(Line::from(""));
+        lignes.push(Line::styled(
+            tr!("tui.dernieres_transactions"),
+            Style::default().add_modifier(Modifier::BOLD),
+        ));
+        for t in compte
+            .historique()
+            .iter()
+            .rev()
+            .take(TRANSACTIONS_AFFICHEES)
+        {
+            let couleur = if t.montant < 0.0 {
+                Color::Red
+            } else {
+                Color::Green
+            };
+            lignes.push(Line::from(vec![
+                Span::raw(format!("{} ", t.date.format("%d/%m/%Y %H:%M"))),
+                Span::styled(
+                    format!("{:>11} ", langue::montant(t.montant)),
+                    Style::default().fg(couleur),
+                ),
+                Span::raw(t.libelle.to_string()),
+            ]));
+        }
+
+        frame.render_widget(Paragraph::new(lignes).block(bloc), zone);
+    }
+}
+
+fn dessiner_formulaire(
+    frame: &mut Frame,
+    formulaire: &Formulaire,
+    gestionnaire: &GestionnaireComptes,
+) {
+    let erreurs = formulaire.erreurs(gestionnaire);
+    let mut lignes = Vec::new();
+    for (i, (champ, erreur)) in formulaire.champs.iter().zip(&erreurs).enumerate() {
+        let curseur = if i == formulaire.actif { "▶ " } else { "  " };
+        let style = if i == formulaire.actif {
+            Style::default().add_modifier(Modifier::BOLD)
+        } else {
+            Style::default()
+        };
+        lignes.push(Line::styled(
+            format!("{}{}: {}", curseur, langue::texte(champ.cle), champ.valeur),
+            style,
+        ));
+        match erreur {
+            Some(message) => lignes.push(Line::styled(
+                format!("    {}", message),
+                Style::default().fg(Color::Red),
+            )),
+            None => lignes.push(Line::from("")),
+        }
+    }
+
+    let hauteur = lignes.len() as u16 + 2;
+    let zone = centrer(frame.area(), 50, hauteur);
+    let titre = format!(
+        " {} ",
+        tr!(
+            "tui.titre_formulaire",
+            formulaire.operation.titre(),
+            formulaire.numero
+        )
+    );
+    frame.render_widget(Clear, zone);
+    frame.render_widget(
+        Paragraph::new(lignes).block(Block::default().title(titre).borders(Borders::ALL)),
+        zone,
+    );
+}
+
+fn centrer(zone: Rect, largeur: u16, hauteur: u16) -> Rect {
+    let largeur = largeur.min(zone.width);
+    let hauteur = hauteur.min(zone.height);
+    Rect {
+        x: zone.x + (zone.width - largeur) / 2,
+        y: zone.y + (zone.height - hauteur) / 2,
+        width: largeur,
+        height: hauteur,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gestionnaire() -> GestionnaireComptes {
+        let mut gestionnaire = GestionnaireComptes::new();
+        for (numero, solde) in [("12345", 100.0), ("67890", 50.0)] {
+            gestionnaire
+                .ajouter_compte(CompteBancaire::new(
+                    numero.to_string(),
+                    "Titulaire".to_string(),
+                    solde,
+                ))
+                .unwrap();
+        }
+        gestionnaire
+    }
+
+    fn saisir(formulaire: &mut Formulaire, cle: &str, valeur: &str) {
+        let champ = formulaire
+            .champs
+            .iter_mut()
+            .find(|champ| champ.cle == cle)
+            .unwrap();
+        champ.valeur = valeur.to_string();
+    }
+
+    #[test]
+    fn validation_du_montant_en_ligne() {
+        let gestionnaire = gestionnaire();
+        let mut formulaire = Formulaire::new(TypeOperation::Retrait, "12345".to_string());
+
+        assert_eq!(
+            formulaire.erreurs(&gestionnaire)[0].as_deref(),
+            Some("Montant requis")
+        );
+        saisir(&mut formulaire, MONTANT, "12a");
+        assert_eq!(
+            formulaire.erreurs(&gestionnaire)[0].as_deref(),
+            Some("Nombre invalide")
+        );
+        saisir(&mut formulaire, MONTANT, "150");
+        assert!(
+            formulaire.erreurs(&gestionnaire)[0]
+                .as_deref()
+                .unwrap()
+                .starts_with("Solde insuffisant")
+        );
+        saisir(&mut formulaire, MONTANT, "80");
+        assert!(
+            formulaire
+                .erreurs(&gestionnaire)
+                .iter()
+                .all(Option::is_none)
+        );
+    }
+
+    #[test]
+    fn virement_valide_le_destinataire_puis_s_execute() {
+        let mut gestionnaire = gestionnaire();
+        let mut formulaire = Formulaire::new(TypeOperation::Virement, "12345".to_string());
+        saisir(&mut formulaire, MONTANT, "30");
+
+        saisir(&mut formulaire, DESTINATAIRE, "12345");
+        assert_eq!(
+            formulaire.erreurs(&gestionnaire)[0].as_deref(),
+            Some("Choisissez un autre compte")
+        );
+        saisir(&mut formulaire, DESTINATAIRE, "99999");
+        assert_eq!(
+            formulaire.erreurs(&gestionnaire)[0].as_deref(),
+            Some("Compte inconnu")
+        );
+        saisir(&mut formulaire, DESTINATAIRE, "67890");
+        assert!(
+            formulaire
+                .erreurs(&gestionnaire)
+                .iter()
+                .all(Option::is_none)
+        );
+
+        let message = formulaire.soumettre(&mut gestionnaire);
+        assert_eq!(
+            message,
+            "Virement de 30,00€ effectué. Nouveau solde: 70,00€"
+        );
+        assert_eq!(gestionnaire.compte("67890").unwrap().solde(), 80.0);
+    }
+}